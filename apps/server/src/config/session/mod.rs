@@ -2,6 +2,9 @@ mod cleanup;
 mod store;
 mod utils;
 
-pub use cleanup::SessionCleanupJob;
+pub use cleanup::{SessionCleanupJob, SESSION_CLEANUP_JOB_NAME};
 pub use store::{PrismaSessionStore, SessionError};
-pub use utils::{get_session_layer, handle_session_service_error, SESSION_USER_KEY};
+pub use utils::{
+	get_session_layer, handle_session_service_error, SESSION_ELEVATION_KEY,
+	SESSION_USER_KEY,
+};
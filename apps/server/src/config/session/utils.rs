@@ -13,6 +13,7 @@ use tower_sessions::{cookie::SameSite, SessionManagerLayer};
 use super::store::PrismaSessionStore;
 
 pub const SESSION_USER_KEY: &str = "user";
+pub const SESSION_ELEVATION_KEY: &str = "elevation";
 pub const SESSION_NAME: &str = "stump_session";
 pub const SESSION_PATH: &str = "/";
 
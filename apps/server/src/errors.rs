@@ -2,15 +2,20 @@ use axum::{
 	extract::multipart::MultipartError,
 	http::StatusCode,
 	response::{IntoResponse, Response},
+	Json,
 };
 use cli::CliError;
 use prisma_client_rust::{
 	prisma_errors::query_engine::{RecordNotFound, UniqueKeyViolation},
 	QueryError,
 };
+use serde::Serialize;
 use stump_core::{
-	error::CoreError, event::InternalCoreTask, filesystem::FileError,
-	job::JobManagerError,
+	db::{bundle::BundleError, TransactionFailure},
+	error::CoreError,
+	event::InternalCoreTask,
+	filesystem::FileError,
+	job::{CancelJobFailure, JobError, JobManagerError},
 };
 use tokio::sync::mpsc;
 use tower_sessions::session::SessionError;
@@ -107,6 +112,16 @@ pub enum ApiError {
 	#[error("{0}")]
 	ServiceUnavailable(String),
 	#[error("{0}")]
+	MaintenanceMode(String),
+	#[error("{0}")]
+	ElevationRequired(String),
+	#[error("{0}")]
+	SetupRequired(String),
+	#[error("{0}")]
+	Conflict(String),
+	#[error("{0}")]
+	Gone(String),
+	#[error("{0}")]
 	BadGateway(String),
 	#[error("{0}")]
 	Unknown(String),
@@ -164,9 +179,42 @@ impl From<CoreError> for ApiError {
 	}
 }
 
+impl From<BundleError> for ApiError {
+	fn from(error: BundleError) -> Self {
+		match error {
+			BundleError::UnsupportedVersion(_)
+			| BundleError::MissingPassphrase
+			| BundleError::InvalidPassphrase
+			| BundleError::Malformed(_) => ApiError::BadRequest(error.to_string()),
+			_ => ApiError::InternalServerError(error.to_string()),
+		}
+	}
+}
+
+impl From<JobError> for ApiError {
+	fn from(error: JobError) -> Self {
+		match error {
+			JobError::InvalidJob(message) => ApiError::BadRequest(message),
+			JobError::MaintenanceMode(message) => ApiError::MaintenanceMode(message),
+			JobError::LibraryArchived(message) => ApiError::Conflict(message),
+			_ => ApiError::InternalServerError(format!("{:?}", error)),
+		}
+	}
+}
+
 impl From<JobManagerError> for ApiError {
 	fn from(error: JobManagerError) -> Self {
-		ApiError::InternalServerError(error.to_string())
+		match error {
+			JobManagerError::CancelFailed(ref failure) => match failure {
+				CancelJobFailure::NotFound => ApiError::NotFound(error.to_string()),
+				CancelJobFailure::AlreadyTerminal(_)
+				| CancelJobFailure::NotManaged(_) => ApiError::Conflict(error.to_string()),
+				CancelJobFailure::Unresponsive => {
+					ApiError::InternalServerError(error.to_string())
+				},
+			},
+			_ => ApiError::InternalServerError(error.to_string()),
+		}
 	}
 }
 
@@ -218,8 +266,42 @@ impl From<std::io::Error> for ApiError {
 	}
 }
 
+/// The body of an error response which carries a machine-readable code alongside the
+/// message, so clients can react to it (e.g. by prompting for re-authentication) rather
+/// than treat it as a plain status code.
+#[derive(Serialize, ToSchema)]
+pub struct CodedErrorBody {
+	/// One of `MAINTENANCE_MODE`, `ELEVATION_REQUIRED` or `SETUP_REQUIRED`
+	pub code: &'static str,
+	pub message: String,
+}
+
+impl ApiError {
+	/// Returns the status and code of errors which respond with a [CodedErrorBody]. Their
+	/// message is the error itself.
+	fn coded(&self) -> Option<(StatusCode, &'static str)> {
+		match self {
+			ApiError::MaintenanceMode(_) => {
+				Some((StatusCode::SERVICE_UNAVAILABLE, "MAINTENANCE_MODE"))
+			},
+			ApiError::ElevationRequired(_) => {
+				Some((StatusCode::FORBIDDEN, "ELEVATION_REQUIRED"))
+			},
+			ApiError::SetupRequired(_) => {
+				Some((StatusCode::SERVICE_UNAVAILABLE, "SETUP_REQUIRED"))
+			},
+			_ => None,
+		}
+	}
+}
+
 impl IntoResponse for ApiError {
 	fn into_response(self) -> Response {
+		if let Some((status, code)) = self.coded() {
+			let message = self.to_string();
+			return (status, Json(CodedErrorBody { code, message })).into_response();
+		}
+
 		match self {
 			ApiError::BadRequest(err) => (StatusCode::BAD_REQUEST, err),
 			ApiError::NotFound(err) => (StatusCode::NOT_FOUND, err),
@@ -230,9 +312,20 @@ impl IntoResponse for ApiError {
 			ApiError::Forbidden(err) => (StatusCode::FORBIDDEN, err),
 			ApiError::NotImplemented => (StatusCode::NOT_IMPLEMENTED, self.to_string()),
 			ApiError::ServiceUnavailable(err) => (StatusCode::SERVICE_UNAVAILABLE, err),
+			ApiError::Conflict(err) => (StatusCode::CONFLICT, err),
+			ApiError::Gone(err) => (StatusCode::GONE, err),
 			ApiError::BadGateway(err) => (StatusCode::BAD_GATEWAY, err),
 			ApiError::PrismaError(e) => {
-				if e.is_prisma_error::<RecordNotFound>() {
+				if let Some(failure) = TransactionFailure::from_query_error(&e) {
+					match failure {
+						TransactionFailure::Conflict => {
+							(StatusCode::CONFLICT, e.to_string())
+						},
+						TransactionFailure::Timeout => {
+							(StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+						},
+					}
+				} else if e.is_prisma_error::<RecordNotFound>() {
 					(StatusCode::NOT_FOUND, e.to_string())
 				} else if e.is_prisma_error::<UniqueKeyViolation>() {
 					(StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
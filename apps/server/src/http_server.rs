@@ -4,6 +4,7 @@ use axum::{error_handling::HandleErrorLayer, extract::connect_info::Connected, R
 use hyper::server::conn::AddrStream;
 use stump_core::{
 	config::{bootstrap_config_dir, logging::init_tracing},
+	db::lock_database,
 	event::InternalCoreTask,
 	StumpCore,
 };
@@ -15,19 +16,33 @@ use crate::{
 	config::{
 		cors,
 		session::{self, handle_session_service_error},
+		state::AppState,
 	},
 	errors::{EntryError, ServerError, ServerResult},
 	routers,
+	telemetry::{continuously_send_telemetry, TELEMETRY_SEND_INTERVAL},
 	utils::shutdown_signal_with_cleanup,
 };
 use stump_core::config::StumpConfig;
 
 pub async fn run_http_server(config: StumpConfig) -> ServerResult<()> {
+	// Held until the server stops, so offline tools (e.g. the CLI) can't write to the
+	// database while it is running
+	let _database_lock = lock_database(&config)
+		.map_err(|e| ServerError::ServerStartError(e.to_string()))?;
 	let core = StumpCore::new(config.clone()).await;
 
-	if let Err(err) = core.run_migrations().await {
-		tracing::error!("Failed to run migrations: {:?}", err);
-		return Err(ServerError::ServerStartError(err.to_string()));
+	if let Err(error) = core.run_migrations().await {
+		tracing::error!(?error, "Failed to run migrations");
+		tracing::warn!(
+			"Starting in degraded mode! Only health, version, and migration status endpoints will be available"
+		);
+
+		let app_state = core.get_context().arced();
+		let app =
+			routers::mount_degraded(app_state.clone()).with_state(app_state.clone());
+
+		return serve(core, config, app_state, app).await;
 	}
 
 	// Initialize the server configuration. If it already exists, nothing will happen.
@@ -39,12 +54,29 @@ pub async fn run_http_server(config: StumpConfig) -> ServerResult<()> {
 		.await
 		.map_err(|e| ServerError::ServerStartError(e.to_string()))?;
 
+	// Restore maintenance mode before any jobs can be enqueued
+	core.init_maintenance_mode()
+		.await
+		.map_err(|e| ServerError::ServerStartError(e.to_string()))?;
+
+	// A server without users only serves the setup routes until setup is completed
+	core.init_setup_mode()
+		.await
+		.map_err(|e| ServerError::ServerStartError(e.to_string()))?;
+
+	// Clean up after any conversions which were interrupted by a crash
+	core.init_temp_dir()
+		.map_err(|e| ServerError::ServerStartError(e.to_string()))?;
+
 	// Initialize the job manager
 	core.get_job_manager()
 		.init()
 		.await
 		.map_err(|e| ServerError::ServerStartError(e.to_string()))?;
 
+	// Wait for library directories (e.g. network shares) before anything scans them
+	core.init_warm_up();
+
 	// Initialize the scheduler
 	core.init_scheduler()
 		.await
@@ -52,17 +84,37 @@ pub async fn run_http_server(config: StumpConfig) -> ServerResult<()> {
 
 	let server_ctx = core.get_context();
 	let app_state = server_ctx.arced();
-	let cors_layer = cors::get_cors_layer(config.clone());
+
+	// Nothing is sent unless telemetry has been opted into
+	tokio::task::spawn(continuously_send_telemetry(
+		app_state.clone(),
+		TELEMETRY_SEND_INTERVAL,
+	));
 
 	tracing::info!("{}", core.get_shadow_text());
 
+	let app = Router::new()
+		.merge(routers::mount(app_state.clone()))
+		.with_state(app_state.clone());
+
+	serve(core, config, app_state, app).await
+}
+
+/// Applies the common layers (sessions, CORS, tracing) to the app and serves it until a
+/// shutdown signal is received.
+async fn serve(
+	core: StumpCore,
+	config: StumpConfig,
+	app_state: AppState,
+	app: Router,
+) -> ServerResult<()> {
+	let cors_layer = cors::get_cors_layer(config.clone());
+
 	let session_service = ServiceBuilder::new()
 		.layer(HandleErrorLayer::new(handle_session_service_error))
-		.layer(session::get_session_layer(app_state.clone()));
+		.layer(session::get_session_layer(app_state));
 
-	let app = Router::new()
-		.merge(routers::mount(app_state.clone()))
-		.with_state(app_state.clone())
+	let app = app
 		.layer(session_service)
 		.layer(cors_layer)
 		.layer(TraceLayer::new_for_http());
@@ -4,6 +4,7 @@ mod filter;
 mod http_server;
 mod middleware;
 mod routers;
+mod telemetry;
 mod utils;
 
 pub use http_server::{bootstrap_http_server_config, run_http_server};
@@ -10,6 +10,7 @@ mod filter;
 mod http_server;
 mod middleware;
 mod routers;
+mod telemetry;
 mod utils;
 
 #[cfg(debug_assertions)]
@@ -33,9 +34,16 @@ async fn main() -> Result<(), EntryError> {
 		.map_err(|e| EntryError::InvalidConfig(e.to_string()))?;
 
 	let cli = Cli::parse();
+	let config = cli.config.merge_stump_config(config);
 
 	if let Some(command) = cli.command {
-		Ok(handle_command(command, &cli.config.merge_stump_config(config)).await?)
+		// Any output (e.g. JSON) has already been printed, so just exit with a failure
+		// rather than also printing the debug representation of the error
+		if let Err(error) = handle_command(command, &config, cli.json).await {
+			eprintln!("{}", error);
+			std::process::exit(1);
+		}
+		Ok(())
 	} else {
 		// Note: init_tracing after loading the environment so the correct verbosity
 		// level is used for logging.
@@ -45,6 +53,10 @@ async fn main() -> Result<(), EntryError> {
 			tracing::trace!(?config, "App config");
 		}
 
+		for issue in config.validate() {
+			tracing::warn!(issue, "Configuration issue detected");
+		}
+
 		Ok(http_server::run_http_server(config).await?)
 	}
 }
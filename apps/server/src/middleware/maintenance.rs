@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use axum::{
+	extract::{FromRef, FromRequestParts},
+	http::{request::Parts, Method},
+	response::{IntoResponse, Response},
+};
+
+use crate::{config::state::AppState, errors::ApiError};
+
+/// Mutating routes which must remain available while in maintenance mode, otherwise it
+/// would be impossible to log in and exit it.
+const MAINTENANCE_EXEMPT_PATHS: [&str; 3] = [
+	"/api/v1/auth/login",
+	"/api/v1/auth/logout",
+	"/api/v1/server/maintenance",
+];
+
+/// An extractor which rejects any mutating request while the server is in maintenance
+/// mode. Reads (including streaming and OPDS feeds) are always allowed through.
+pub struct MaintenanceGuard;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for MaintenanceGuard
+where
+	AppState: FromRef<S>,
+	S: Send + Sync,
+{
+	type Rejection = Response;
+
+	async fn from_request_parts(
+		parts: &mut Parts,
+		state: &S,
+	) -> Result<Self, Self::Rejection> {
+		if matches!(parts.method, Method::GET | Method::HEAD | Method::OPTIONS)
+			|| MAINTENANCE_EXEMPT_PATHS.contains(&parts.uri.path())
+		{
+			return Ok(Self);
+		}
+
+		let state = AppState::from_ref(state);
+		match state.maintenance_mode() {
+			Some(mode) => {
+				tracing::trace!(uri = ?parts.uri, "Rejecting request during maintenance");
+				Err(ApiError::MaintenanceMode(mode.message.unwrap_or_else(|| {
+					String::from("Stump is in maintenance mode, please try again later")
+				}))
+				.into_response())
+			},
+			None => Ok(Self),
+		}
+	}
+}
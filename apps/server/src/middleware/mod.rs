@@ -1,2 +1,4 @@
 pub mod auth;
 pub(crate) mod logging;
+pub mod maintenance;
+pub mod setup;
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use axum::{
+	extract::{FromRef, FromRequestParts},
+	http::request::Parts,
+	response::{IntoResponse, Response},
+};
+
+use crate::{config::state::AppState, errors::ApiError};
+
+/// The routes which remain available while the server is awaiting first-run setup.
+const SETUP_EXEMPT_PATHS: [&str; 2] = ["/api/v1/setup/status", "/api/v1/setup/complete"];
+
+/// Returns true if the path must be rejected while setup is pending. Only the API and OPDS
+/// routes are guarded, so that the web client (which hosts the setup wizard) still loads.
+fn is_guarded_path(path: &str) -> bool {
+	(path.starts_with("/api") || path.starts_with("/opds"))
+		&& !SETUP_EXEMPT_PATHS.contains(&path)
+}
+
+/// An extractor which rejects every request other than the setup routes while the server
+/// is awaiting first-run setup.
+pub struct SetupGuard;
+
+#[async_trait]
+impl<S> FromRequestParts<S> for SetupGuard
+where
+	AppState: FromRef<S>,
+	S: Send + Sync,
+{
+	type Rejection = Response;
+
+	async fn from_request_parts(
+		parts: &mut Parts,
+		state: &S,
+	) -> Result<Self, Self::Rejection> {
+		let state = AppState::from_ref(state);
+		if !state.is_setup_required() || !is_guarded_path(parts.uri.path()) {
+			return Ok(Self);
+		}
+
+		tracing::trace!(uri = ?parts.uri, "Rejecting request until setup is completed");
+		Err(ApiError::SetupRequired(String::from(
+			"Stump has not been set up yet. Complete setup using the token in the server logs",
+		))
+		.into_response())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_guarded_path() {
+		assert!(is_guarded_path("/api/v1/libraries"));
+		assert!(is_guarded_path("/api/v1/auth/register"));
+		assert!(is_guarded_path("/opds/v1.2/catalog"));
+		assert!(!is_guarded_path("/api/v1/setup/status"));
+		assert!(!is_guarded_path("/api/v1/setup/complete"));
+		assert!(!is_guarded_path("/"));
+		assert!(!is_guarded_path("/assets/index.js"));
+	}
+}
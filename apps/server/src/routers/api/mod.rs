@@ -8,6 +8,13 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 	Router::new().nest("/api", Router::new().nest("/v1", v1::mount(app_state)))
 }
 
+pub(crate) fn mount_degraded(app_state: AppState) -> Router<AppState> {
+	Router::new().nest(
+		"/api",
+		Router::new().nest("/v1", v1::mount_degraded(app_state)),
+	)
+}
+
 #[allow(unused_imports)]
 mod tests {
 	use std::{fs::File, io::Write, path::PathBuf};
@@ -19,7 +26,8 @@ mod tests {
 
 	use super::v1::{
 		auth::*, book_club::*, epub::*, job::*, library::*, media::*, metadata::*,
-		series::*, smart_list::*, user::*, ClaimResponse, StumpVersion, UpdateCheck,
+		series::*, server::*, smart_list::*, user::*, ClaimResponse, MaintenanceStatus,
+		StumpVersion, UpdateCheck,
 	};
 
 	#[allow(dead_code)]
@@ -88,6 +96,9 @@ mod tests {
 		file.write_all(
 			format!("{}\n\n", ts_export::<UpdateSchedulerConfig>()?).as_bytes(),
 		)?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<UpdatePausedJobType>()?).as_bytes(),
+		)?;
 
 		file.write_all(format!("{}\n\n", ts_export::<GetBookClubsParams>()?).as_bytes())?;
 		file.write_all(format!("{}\n\n", ts_export::<CreateBookClub>()?).as_bytes())?;
@@ -141,6 +152,16 @@ mod tests {
 		file.write_all(
 			format!("{}\n\n", ts_export::<CreateOrUpdateSmartListView>()?).as_bytes(),
 		)?;
+		file.write_all(format!("{}\n\n", ts_export::<ExportBundleParams>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<ImportBundle>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<TelemetryPreview>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<MaintenanceStatus>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<UpdateMaintenanceMode>()?).as_bytes(),
+		)?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<RunDatabaseMaintenance>()?).as_bytes(),
+		)?;
 
 		Ok(())
 	}
@@ -5,13 +5,13 @@ use axum::{
 	Json, Router, TypedHeader,
 };
 use prisma_client_rust::{
-	chrono::{DateTime, Duration, FixedOffset, Utc},
+	chrono::{DateTime, Duration, FixedOffset, TimeZone, Utc},
 	Direction,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use specta::Type;
 use stump_core::{
-	db::entity::User,
+	db::{entity::User, run_in_transaction},
 	prisma::{session, user, user_login_activity, user_preferences, PrismaClient},
 };
 use tower_sessions::{session::SessionDeletion, Session};
@@ -19,10 +19,13 @@ use tracing::error;
 use utoipa::ToSchema;
 
 use crate::{
-	config::{session::SESSION_USER_KEY, state::AppState},
+	config::{
+		session::{SESSION_ELEVATION_KEY, SESSION_USER_KEY},
+		state::AppState,
+	},
 	errors::{ApiError, ApiResult},
 	http_server::StumpRequestInfo,
-	utils::verify_password,
+	utils::{get_session_user, verify_password, SessionElevation},
 };
 
 pub(crate) fn mount() -> Router<AppState> {
@@ -32,6 +35,7 @@ pub(crate) fn mount() -> Router<AppState> {
 			.route("/me", get(viewer))
 			.route("/login", post(login))
 			.route("/logout", post(logout))
+			.route("/reverify", post(reverify))
 			.route("/register", post(register)),
 	)
 }
@@ -285,6 +289,64 @@ async fn logout(session: Session) -> ApiResult<()> {
 	Ok(())
 }
 
+#[derive(Deserialize, Type, ToSchema)]
+pub struct ReverifyArgs {
+	pub password: String,
+}
+
+#[derive(Serialize, Type, ToSchema)]
+pub struct SessionElevationStatus {
+	/// The datetime stamp of when the session's elevation expires
+	pub expires_at: String,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/auth/reverify",
+	tag = "auth",
+	request_body = ReverifyArgs,
+	responses(
+		(status = 200, description = "Successfully elevated the session.", body = SessionElevationStatus),
+		(status = 401, description = "No user is logged in, or the password is incorrect."),
+		(status = 500, description = "An internal server error occurred.")
+	)
+)]
+/// Re-authenticates the logged in user with their password and elevates the session, which
+/// allows destructive operations (e.g. deleting a library) for a short time afterwards. The
+/// elevation only applies to this session, and is revoked if the password changes.
+async fn reverify(
+	session: Session,
+	State(ctx): State<AppState>,
+	Json(input): Json<ReverifyArgs>,
+) -> ApiResult<Json<SessionElevationStatus>> {
+	let user = get_session_user(&session)?;
+
+	let db_user = ctx
+		.db
+		.user()
+		.find_first(vec![
+			user::id::equals(user.id.clone()),
+			user::deleted_at::equals(None),
+		])
+		.exec()
+		.await?
+		.ok_or(ApiError::Unauthorized)?;
+
+	if db_user.is_locked || !verify_password(&db_user.hashed_password, &input.password)? {
+		return Err(ApiError::Unauthorized);
+	}
+
+	let elevation = SessionElevation::new(&db_user.hashed_password);
+	let expires_at = Utc
+		.timestamp_opt(elevation.expires_at, 0)
+		.single()
+		.map(|expires_at| expires_at.to_rfc3339())
+		.unwrap_or_default();
+	session.insert(SESSION_ELEVATION_KEY, elevation)?;
+
+	Ok(Json(SessionElevationStatus { expires_at }))
+}
+
 #[utoipa::path(
 	post,
 	path = "/api/v1/auth/register",
@@ -328,25 +390,30 @@ pub async fn register(
 
 	let hashed_password = bcrypt::hash(&input.password, ctx.config.password_hash_cost)?;
 
-	let created_user = db
-		.user()
-		.create(
-			input.username.to_owned(),
-			hashed_password,
-			vec![user::is_server_owner::set(is_server_owner)],
-		)
-		.exec()
-		.await?;
-
 	// FIXME: these next two queries will be removed once nested create statements are
 	// supported on the prisma client. Until then, this ugly mess is necessary.
-	let _user_preferences = db
-		.user_preferences()
-		.create(vec![user_preferences::user::connect(user::id::equals(
-			created_user.id.clone(),
-		))])
-		.exec()
-		.await?;
+	let created_user: user::Data = run_in_transaction(db, |client| async move {
+		let created_user = client
+			.user()
+			.create(
+				input.username.to_owned(),
+				hashed_password,
+				vec![user::is_server_owner::set(is_server_owner)],
+			)
+			.exec()
+			.await?;
+
+		let _user_preferences = client
+			.user_preferences()
+			.create(vec![user_preferences::user::connect(user::id::equals(
+				created_user.id.clone(),
+			))])
+			.exec()
+			.await?;
+
+		Ok::<_, ApiError>(created_user)
+	})
+	.await?;
 
 	// This *really* shouldn't fail, so I am using expect here. It also doesn't
 	// matter too much in the long run since this query will go away once above fixme
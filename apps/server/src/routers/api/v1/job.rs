@@ -1,32 +1,41 @@
 use axum::{
 	extract::{Path, Query, State},
 	middleware::{from_extractor, from_extractor_with_state},
-	routing::{delete, get},
+	routing::{delete, get, post},
 	Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_qs::axum::QsQuery;
 use stump_core::{
 	db::{
-		entity::JobSchedulerConfig,
+		entity::{JobSchedulerConfig, LogLevel, ScheduleCatchUpPolicy},
+		maintenance::DATABASE_MAINTENANCE_JOB_NAME,
 		query::{
 			ordering::QueryOrder,
 			pagination::{Pageable, Pagination, PaginationQuery},
 		},
 	},
 	event::InternalCoreTask,
-	job::JobDetail,
+	filesystem::{
+		image::THUMBNAIL_JOB_NAME,
+		scanner::{LIBRARY_SCAN_JOB_NAME, SERIES_SCAN_JOB_NAME},
+	},
+	job::{
+		get_wait_times, JobDetail, JobManagerConfig, JobQueueSnapshot, JobQueueStatus,
+		JobWaitTimes, UpdateJobLimits,
+	},
 	prisma::{
 		job::{self, OrderByParam as JobOrderByParam},
 		job_schedule_config, library, server_config,
 	},
+	Ctx,
 };
 use tokio::sync::oneshot;
 use tracing::{debug, trace};
 use utoipa::ToSchema;
 
 use crate::{
-	config::state::AppState,
+	config::{session::SESSION_CLEANUP_JOB_NAME, state::AppState},
 	errors::{ApiError, ApiResult},
 	filter::chain_optional_iter,
 	middleware::auth::{Auth, ServerOwnerGuard},
@@ -38,15 +47,27 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 			"/jobs",
 			Router::new()
 				.route("/", get(get_jobs).delete(delete_jobs))
+				.route("/queue", get(get_job_queue))
+				.route("/queue/paused", post(update_queue_paused))
+				.route("/wait-times", get(get_job_wait_times))
+				.route(
+					"/config",
+					get(get_job_manager_config).post(update_job_limits),
+				)
 				.nest(
 					"/:id",
 					Router::new()
-						.route("/", delete(delete_job_by_id))
-						.route("/cancel", delete(cancel_job_by_id)),
+						.route("/", get(get_job_by_id).delete(delete_job_by_id))
+						.route("/cancel", delete(cancel_job_by_id))
+						.route("/kill", post(kill_job_by_id)),
 				)
 				.route(
 					"/scheduler-config",
 					get(get_scheduler_config).post(update_scheduler_config),
+				)
+				.route(
+					"/paused-types",
+					get(get_paused_job_types).post(update_paused_job_type),
 				),
 		)
 		.layer(from_extractor::<ServerOwnerGuard>())
@@ -150,6 +171,104 @@ async fn delete_jobs(State(ctx): State<AppState>) -> ApiResult<()> {
 	Ok(())
 }
 
+#[utoipa::path(
+	get,
+	path = "/api/v1/jobs/queue",
+	tag = "job",
+	responses(
+		(status = 200, description = "Successfully fetched the job queue", body = JobQueueSnapshot),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Get the running job and the jobs waiting to run, including the position of each queued
+/// job, an estimate of when it will start, and an estimate of when the queue will be empty.
+async fn get_job_queue(State(ctx): State<AppState>) -> ApiResult<Json<JobQueueSnapshot>> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::GetJobQueue(task_tx))
+		.map_err(|e| {
+			ApiError::InternalServerError(format!(
+				"Failed to submit internal task: {}",
+				e
+			))
+		})?;
+
+	Ok(Json(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to get job queue: {}", e))
+	})??))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/jobs/wait-times",
+	tag = "job",
+	responses(
+		(status = 200, description = "Successfully fetched job wait times", body = [JobWaitTimes]),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Get how long recently started jobs waited in the queue before starting, by job type.
+async fn get_job_wait_times(
+	State(ctx): State<AppState>,
+) -> ApiResult<Json<Vec<JobWaitTimes>>> {
+	Ok(Json(get_wait_times(ctx.get_db()).await?))
+}
+
+/// Fetches the status of a job by ID through the job manager, so that queued jobs (which
+/// aren't persisted until they start) are found too.
+pub(crate) async fn get_job_queue_status(
+	ctx: &Ctx,
+	job_id: String,
+) -> ApiResult<JobQueueStatus> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::GetJobQueueStatus {
+		job_id: job_id.clone(),
+		return_sender: task_tx,
+	})
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to submit internal task: {}", e))
+	})?;
+
+	task_rx
+		.await
+		.map_err(|e| {
+			ApiError::InternalServerError(format!("Failed to get job status: {}", e))
+		})??
+		.ok_or(ApiError::NotFound(format!(
+			"Job with id {} not found",
+			job_id
+		)))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/jobs/:id",
+	tag = "job",
+	params(
+		("id" = String, Path, description = "The ID of the job")
+	),
+	responses(
+		(status = 200, description = "Successfully fetched job", body = JobQueueStatus),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 404, description = "Job not found."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Get a job by its ID. A job which has yet to start includes its position in the queue
+/// and an estimate of when it will start, the same as the queue snapshot.
+async fn get_job_by_id(
+	State(ctx): State<AppState>,
+	Path(job_id): Path<String>,
+) -> ApiResult<Json<JobQueueStatus>> {
+	Ok(Json(get_job_queue_status(&ctx, job_id).await?))
+}
+
 #[utoipa::path(
 	delete,
 	path = "/api/v1/jobs/:id",
@@ -183,10 +302,13 @@ async fn delete_job_by_id(
 		(status = 200, description = "Successfully cancelled job"),
 		(status = 401, description = "No user is logged in (unauthorized)."),
 		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 404, description = "The job does not exist."),
+		(status = 409, description = "The job has already finished, or is not queued or running."),
 		(status = 500, description = "Internal server error."),
 	)
 )]
-/// Cancel a running job. This will not delete the job report.
+/// Cancel a running job. This will not delete the job report. If the job can't be cancelled,
+/// the response explains why.
 async fn cancel_job_by_id(
 	State(ctx): State<AppState>,
 	Path(job_id): Path<String>,
@@ -206,6 +328,44 @@ async fn cancel_job_by_id(
 	})??)
 }
 
+#[utoipa::path(
+	post,
+	path = "/api/v1/jobs/:id/kill",
+	tag = "job",
+	params(
+		("id" = String, Path, description = "The ID of the job to kill.")
+	),
+	responses(
+		(status = 200, description = "Successfully killed job"),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 404, description = "The job does not exist."),
+		(status = 409, description = "The job has already finished, or is not queued or running."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Kill a running job, for emergencies. Unlike cancelling, the job abandons its work
+/// immediately, losing any partial progress, and is recorded as `KILLED`. A job which isn't
+/// running yet is cancelled instead.
+async fn kill_job_by_id(
+	State(ctx): State<AppState>,
+	Path(job_id): Path<String>,
+) -> ApiResult<()> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::KillJob {
+		job_id,
+		return_sender: task_tx,
+	})
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to submit internal task: {}", e))
+	})?;
+
+	Ok(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to kill job: {}", e))
+	})??)
+}
+
 #[utoipa::path(
 	get,
 	path = "/api/v1/jobs/scheduler-config",
@@ -249,6 +409,10 @@ async fn get_scheduler_config(
 pub struct UpdateSchedulerConfig {
 	pub interval_secs: Option<i32>,
 	pub excluded_library_ids: Option<Vec<String>>,
+	/// What to do, on startup, about the scheduled runs missed while the server was down
+	pub catch_up_policy: Option<ScheduleCatchUpPolicy>,
+	/// The level of logs captured for scheduled scans
+	pub log_level: Option<LogLevel>,
 }
 
 #[utoipa::path(
@@ -268,8 +432,10 @@ async fn update_scheduler_config(
 ) -> ApiResult<Json<Option<JobSchedulerConfig>>> {
 	let db = ctx.get_db();
 
-	let should_remove_config =
-		input.excluded_library_ids.is_none() && input.interval_secs.is_none();
+	let should_remove_config = input.excluded_library_ids.is_none()
+		&& input.interval_secs.is_none()
+		&& input.catch_up_policy.is_none()
+		&& input.log_level.is_none();
 
 	tracing::trace!(should_remove_config, ?input, "update_scheduler_config");
 
@@ -306,6 +472,16 @@ async fn update_scheduler_config(
 								input
 									.interval_secs
 									.map(job_schedule_config::interval_secs::set),
+								input.catch_up_policy.map(|policy| {
+									job_schedule_config::catch_up_policy::set(
+										policy.to_string(),
+									)
+								}),
+								input.log_level.map(|level| {
+									job_schedule_config::log_level::set(Some(
+										level.to_string(),
+									))
+								}),
 								input.excluded_library_ids.map(|list| {
 									job_schedule_config::excluded_libraries::set(
 										list.into_iter()
@@ -331,6 +507,16 @@ async fn update_scheduler_config(
 							input
 								.interval_secs
 								.map(job_schedule_config::interval_secs::set),
+							input.catch_up_policy.map(|policy| {
+								job_schedule_config::catch_up_policy::set(
+									policy.to_string(),
+								)
+							}),
+							input.log_level.map(|level| {
+								job_schedule_config::log_level::set(Some(
+									level.to_string(),
+								))
+							}),
 							input.excluded_library_ids.map(|list| {
 								job_schedule_config::excluded_libraries::connect(
 									list.into_iter().map(library::id::equals).collect(),
@@ -349,3 +535,200 @@ async fn update_scheduler_config(
 
 	Ok(Json(updated_or_deleted_config))
 }
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/jobs/config",
+	tag = "job",
+	responses(
+		(status = 200, description = "Successfully fetched the job system configuration", body = JobManagerConfig),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Get the job system configuration the server is actually using. This may differ from the
+/// config on disk, since some settings (e.g. paused job types and the job limits) can be
+/// changed at runtime.
+async fn get_job_manager_config(
+	State(ctx): State<AppState>,
+) -> ApiResult<Json<JobManagerConfig>> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::GetJobManagerConfig(task_tx))
+		.map_err(|e| {
+			ApiError::InternalServerError(format!(
+				"Failed to submit internal task: {}",
+				e
+			))
+		})?;
+
+	Ok(Json(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!(
+			"Failed to get the job system configuration: {}",
+			e
+		))
+	})?))
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/jobs/config",
+	tag = "job",
+	request_body = UpdateJobLimits,
+	responses(
+		(status = 200, description = "Successfully updated the job limits", body = JobManagerConfig),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Change the job limits (e.g. the memory usage above which jobs are deferred) without
+/// restarting the server. Changes are not persisted, so the limits in the config on disk
+/// apply again after a restart. Returns the job system configuration now in effect.
+async fn update_job_limits(
+	State(ctx): State<AppState>,
+	Json(input): Json<UpdateJobLimits>,
+) -> ApiResult<Json<JobManagerConfig>> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::UpdateJobLimits {
+		limits: input,
+		return_sender: task_tx,
+	})
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to submit internal task: {}", e))
+	})?;
+
+	Ok(Json(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to update the job limits: {}", e))
+	})?))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/jobs/paused-types",
+	tag = "job",
+	responses(
+		(status = 200, description = "Successfully fetched paused job types", body = [String]),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Get the names of the job types which are paused, e.g. library_scan
+async fn get_paused_job_types(
+	State(ctx): State<AppState>,
+) -> ApiResult<Json<Vec<String>>> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::GetPausedJobTypes(task_tx))
+		.map_err(|e| {
+			ApiError::InternalServerError(format!(
+				"Failed to submit internal task: {}",
+				e
+			))
+		})?;
+
+	Ok(Json(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to get paused job types: {}", e))
+	})?))
+}
+
+/// The names of every job type, i.e. the job types which can be paused
+const JOB_NAMES: [&str; 5] = [
+	LIBRARY_SCAN_JOB_NAME,
+	SERIES_SCAN_JOB_NAME,
+	THUMBNAIL_JOB_NAME,
+	DATABASE_MAINTENANCE_JOB_NAME,
+	SESSION_CLEANUP_JOB_NAME,
+];
+
+#[derive(Debug, Deserialize, ToSchema, specta::Type)]
+pub struct UpdatePausedJobType {
+	/// The name of the job type, e.g. library_scan
+	pub job_name: String,
+	pub paused: bool,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/jobs/paused-types",
+	tag = "job",
+	request_body = UpdatePausedJobType,
+	responses(
+		(status = 200, description = "Successfully updated paused job types", body = [String]),
+		(status = 400, description = "Unknown job type."),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Pause or unpause a job type. Jobs of a paused type stay queued (even once enqueued)
+/// while jobs of other types continue to run. Returns the job types which are now paused.
+async fn update_paused_job_type(
+	State(ctx): State<AppState>,
+	Json(input): Json<UpdatePausedJobType>,
+) -> ApiResult<Json<Vec<String>>> {
+	if !JOB_NAMES.contains(&input.job_name.as_str()) {
+		return Err(ApiError::BadRequest(format!(
+			"Unknown job type {}, expected one of: {}",
+			input.job_name,
+			JOB_NAMES.join(", ")
+		)));
+	}
+
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::SetJobTypePaused {
+		job_name: input.job_name,
+		paused: input.paused,
+		return_sender: task_tx,
+	})
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to submit internal task: {}", e))
+	})?;
+
+	Ok(Json(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to update paused job type: {}", e))
+	})?))
+}
+
+#[derive(Debug, Deserialize, ToSchema, specta::Type)]
+pub struct UpdateQueuePaused {
+	pub paused: bool,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/jobs/queue/paused",
+	tag = "job",
+	request_body = UpdateQueuePaused,
+	responses(
+		(status = 200, description = "Successfully updated the queue", body = bool),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Pause or resume the job queue. While paused, the running job continues and jobs are still
+/// queued, but no queued job is started until the queue is resumed. Returns whether the queue
+/// is now paused.
+async fn update_queue_paused(
+	State(ctx): State<AppState>,
+	Json(input): Json<UpdateQueuePaused>,
+) -> ApiResult<Json<bool>> {
+	let (task_tx, task_rx) = oneshot::channel();
+
+	ctx.dispatch_task(InternalCoreTask::SetQueuePaused {
+		paused: input.paused,
+		return_sender: task_tx,
+	})
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to submit internal task: {}", e))
+	})?;
+
+	Ok(Json(task_rx.await.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to update the queue: {}", e))
+	})?))
+}
@@ -19,11 +19,11 @@ use stump_core::{
 	db::{
 		entity::{
 			library_series_ids_media_ids_include, library_thumbnails_deletion_include,
-			FileStatus, LibrariesStats, Library, LibraryOptions, LibraryScanMode, Media,
-			Series, Tag, UserPermission,
+			FileStatus, LibrariesStats, Library, LibraryOptions, LibraryScanMode,
+			LogLevel, Media, Series, Tag, UserPermission,
 		},
 		query::pagination::{Pageable, Pagination, PaginationQuery},
-		PrismaCountTrait,
+		run_in_transaction, PrismaCountTrait,
 	},
 	filesystem::{
 		get_unknown_thumnail,
@@ -36,6 +36,7 @@ use stump_core::{
 		scanner::LibraryScanJob,
 		ContentType, FileParts, PathUtils,
 	},
+	job::JobQueueStatus,
 	prisma::{
 		last_library_visit,
 		library::{self, WhereParam},
@@ -48,19 +49,21 @@ use stump_core::{
 
 use crate::{
 	config::state::AppState,
-	errors::{ApiError, ApiResult},
+	errors::{ApiError, ApiResult, CodedErrorBody},
 	filter::{
 		chain_optional_iter, decode_path_filter, FilterableQuery, LibraryBaseFilter,
 		LibraryFilter, LibraryRelationFilter, MediaFilter, SeriesFilter,
 	},
 	middleware::auth::Auth,
 	utils::{
-		enforce_session_permissions, get_session_server_owner_user, get_session_user,
-		get_user_and_enforce_permission, http::ImageResponse, validate_image_upload,
+		enforce_elevated_session, enforce_session_permissions,
+		get_session_server_owner_user, get_session_user, get_user_and_enforce_permission,
+		http::ImageResponse, validate_image_upload,
 	},
 };
 
 use super::{
+	job::get_job_queue_status,
 	media::{apply_media_age_restriction, apply_media_filters, apply_media_pagination},
 	series::{
 		apply_series_age_restriction, apply_series_base_filters, apply_series_filters,
@@ -800,6 +803,9 @@ pub struct GenerateLibraryThumbnails {
 	pub image_options: Option<ImageProcessorOptions>,
 	#[serde(default)]
 	pub force_regenerate: bool,
+	/// Whether to start the job even if the system is under pressure
+	#[serde(default)]
+	pub force: bool,
 }
 
 /// Generate thumbnails for all the media in a library by id, if the current user has access to it.
@@ -845,7 +851,8 @@ async fn generate_library_thumbnails(
 	};
 	tracing::trace!(?options, ?config, "Dispatching thumbnail job");
 
-	ctx.dispatch_job(ThumbnailJob::new(options, config))?;
+	ctx.enqueue_job(ThumbnailJob::new(options, config).with_force(input.force))
+		.await?;
 
 	Ok(Json(()))
 }
@@ -853,6 +860,10 @@ async fn generate_library_thumbnails(
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct ScanQueryParam {
 	scan_mode: Option<String>,
+	/// The level of logs captured for the scan, e.g. DEBUG. Defaults to the configured level
+	log_level: Option<String>,
+	/// Whether to start the scan even if the system is under pressure
+	force: Option<bool>,
 }
 
 #[utoipa::path(
@@ -864,20 +875,22 @@ pub struct ScanQueryParam {
 		("query" = ScanQueryParam, Query, description = "The scan options"),
 	),
 	responses(
-		(status = 200, description = "Successfully queued library scan"),
+		(status = 200, description = "Successfully queued library scan", body = JobQueueStatus),
 		(status = 401, description = "Unauthorized"),
 		(status = 404, description = "Library not found"),
+		(status = 409, description = "Library is archived"),
 		(status = 500, description = "Internal server error")
 	)
 )]
 /// Queue a ScannerJob to scan the library by id. The job, when started, is
-/// executed in a separate thread.
+/// executed in a separate thread. Responds with the job's position in the queue and an
+/// estimate of when it will start.
 async fn scan_library(
 	Path(id): Path<String>,
 	State(ctx): State<AppState>,
 	query: Query<ScanQueryParam>,
 	session: Session,
-) -> Result<(), ApiError> {
+) -> ApiResult<Json<JobQueueStatus>> {
 	let db = ctx.get_db();
 
 	get_user_and_enforce_permission(&session, UserPermission::ScanLibrary)?;
@@ -896,9 +909,22 @@ async fn scan_library(
 	let scan_mode = LibraryScanMode::from_str(&scan_mode)
 		.map_err(|e| ApiError::BadRequest(format!("Invalid scan mode: {}", e)))?;
 
-	ctx.dispatch_job(LibraryScanJob::new(library.path, scan_mode))?;
+	let log_level = query
+		.log_level
+		.as_deref()
+		.map(LogLevel::from_str)
+		.transpose()
+		.map_err(ApiError::BadRequest)?;
+
+	let job_id = ctx
+		.enqueue_job(
+			LibraryScanJob::new(library.path, scan_mode)
+				.with_log_level(log_level)
+				.with_force(query.force.unwrap_or_default()),
+		)
+		.await?;
 
-	Ok(())
+	Ok(Json(get_job_queue_status(&ctx, job_id).await?))
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema, Type)]
@@ -1104,9 +1130,8 @@ async fn create_library(
 	// TODO: refactor once nested create is supported
 	// https://github.com/Brendonovich/prisma-client-rust/issues/44
 	let library_options_arg = input.library_options.unwrap_or_default();
-	let transaction_result: Result<Library, ApiError> = db
-		._transaction()
-		.run(|client| async move {
+	let transaction_result: Result<Library, ApiError> =
+		run_in_transaction(db, |client| async move {
 			let library_options = client
 				.library_options()
 				.create(vec![
@@ -1119,6 +1144,12 @@ async fn create_library(
 					library_options::library_pattern::set(
 						library_options_arg.library_pattern.to_string(),
 					),
+					library_options::extract_metadata::set(
+						library_options_arg.extract_metadata,
+					),
+					library_options::generate_file_hashes::set(
+						library_options_arg.generate_file_hashes,
+					),
 					library_options::thumbnail_config::set(
 						library_options_arg.thumbnail_config.map(|options| {
 							serde_json::to_vec(&options).unwrap_or_default()
@@ -1173,7 +1204,10 @@ async fn create_library(
 
 	let library = transaction_result?;
 	let scan_mode = input.scan_mode.unwrap_or_default();
-	ctx.dispatch_job(LibraryScanJob::new(library.path.clone(), scan_mode))?;
+	if scan_mode != LibraryScanMode::None {
+		ctx.enqueue_job(LibraryScanJob::new(library.path.clone(), scan_mode))
+			.await?;
+	}
 
 	Ok(Json(library))
 }
@@ -1199,6 +1233,10 @@ pub struct UpdateLibrary {
 	/// Optional flag to indicate how the library should be automatically scanned after update. Default is `BATCHED`.
 	#[serde(default)]
 	pub scan_mode: Option<LibraryScanMode>,
+	/// Whether the library is archived (i.e. offline). Archived libraries are never scanned,
+	/// and their files are expected to be unavailable. Omit to leave unchanged.
+	#[serde(default)]
+	pub archived: Option<bool>,
 }
 
 #[utoipa::path(
@@ -1229,7 +1267,20 @@ async fn update_library(
 
 	let db = ctx.get_db();
 
-	if !path::Path::new(&input.path).exists() {
+	let existing_library = db
+		.library()
+		.find_unique(library::id::equals(id.clone()))
+		.exec()
+		.await?
+		.ok_or(ApiError::NotFound(format!(
+			"Library with id {} not found",
+			id
+		)))?;
+	let is_archived = input.archived.unwrap_or(existing_library.archived);
+
+	// The files of an archived library are expected to be unavailable, e.g. on a drive
+	// which isn't plugged in
+	if !is_archived && !path::Path::new(&input.path).exists() {
 		return Err(ApiError::BadRequest(format!(
 			"Updated path does not exist: {}",
 			input.path
@@ -1249,6 +1300,12 @@ async fn update_library(
 					library_options::hard_delete_conversions::set(
 						library_options.hard_delete_conversions,
 					),
+					library_options::extract_metadata::set(
+						library_options.extract_metadata,
+					),
+					library_options::generate_file_hashes::set(
+						library_options.generate_file_hashes,
+					),
 				],
 				[library_options.thumbnail_config.map(|config| {
 					library_options::thumbnail_config::set(Some(
@@ -1302,16 +1359,21 @@ async fn update_library(
 				library::path::set(input.path),
 				library::description::set(input.description),
 				library::emoji::set(input.emoji),
+				library::archived::set(is_archived),
 			],
 		)
 		.with(library::tags::fetch(vec![]))
+		.with(library::library_options::fetch())
 		.exec()
 		.await?;
 
+	// Archived libraries are never scanned. This means un-archiving a library scans it (unless
+	// the scan mode is NONE), verifying which of its files are still present
 	let scan_mode = input.scan_mode.unwrap_or_default();
 
-	if scan_mode != LibraryScanMode::None {
-		ctx.dispatch_job(LibraryScanJob::new(updated.path.clone(), scan_mode))?;
+	if !is_archived && scan_mode != LibraryScanMode::None {
+		ctx.enqueue_job(LibraryScanJob::new(updated.path.clone(), scan_mode))
+			.await?;
 	}
 
 	Ok(Json(updated.into()))
@@ -1328,12 +1390,12 @@ async fn update_library(
 		(status = 200, description = "Successfully deleted library"),
 		(status = 400, description = "Bad request"),
 		(status = 401, description = "Unauthorized"),
-		(status = 403, description = "Forbidden"),
+		(status = 403, description = "Forbidden, or the session must be re-authenticated", body = CodedErrorBody),
 		(status = 404, description = "Not found"),
 		(status = 500, description = "Internal server error")
 	)
 )]
-/// Delete a library by id
+/// Delete a library by id. This requires a recently re-authenticated session.
 async fn delete_library(
 	session: Session,
 	Path(id): Path<String>,
@@ -1341,6 +1403,7 @@ async fn delete_library(
 ) -> ApiResult<Json<String>> {
 	get_session_server_owner_user(&session)?;
 	let db = ctx.get_db();
+	enforce_elevated_session(&session, db).await?;
 	let thumbnails_dir = ctx.config.get_thumbnails_dir();
 
 	trace!(?id, "Attempting to delete library");
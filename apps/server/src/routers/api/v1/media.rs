@@ -22,8 +22,8 @@ use stump_core::{
 	filesystem::{
 		get_unknown_thumnail,
 		image::{
-			generate_thumbnail, place_thumbnail, remove_thumbnails, ImageFormat,
-			ImageProcessorOptions,
+			generate_thumbnail, get_resized_page, place_thumbnail, remove_thumbnails,
+			ImageFormat, ImageProcessorOptions, PageResizeOptions,
 		},
 		media::get_page,
 		read_entire_file, ContentType, FileParts, PathUtils,
@@ -742,6 +742,7 @@ async fn get_media_by_id(
 		(status = 401, description = "Unauthorized"),
 		(status = 403, description = "Forbidden"),
 		(status = 404, description = "Media not found"),
+		(status = 409, description = "The media's library is archived"),
 		(status = 500, description = "Internal server error"),
 	)
 )]
@@ -768,12 +769,36 @@ async fn get_media_file(
 		.exec()
 		.await?
 		.ok_or(ApiError::NotFound(String::from("Media not found")))?;
+	enforce_library_online(db, &media.id).await?;
 
 	tracing::trace!(?media, "Downloading media file");
 
 	Ok(NamedFile::open(media.path.clone()).await?)
 }
 
+/// Errors if the media belongs to an archived library, since its files are expected to be
+/// unavailable (e.g. on a drive which isn't plugged in)
+async fn enforce_library_online(db: &PrismaClient, media_id: &str) -> ApiResult<()> {
+	let archived_library = db
+		.library()
+		.find_first(vec![
+			library::archived::equals(true),
+			library::series::some(vec![series::media::some(vec![media::id::equals(
+				media_id.to_string(),
+			)])]),
+		])
+		.exec()
+		.await?;
+
+	match archived_library {
+		Some(library) => Err(ApiError::Conflict(format!(
+			"The library {} is offline (archived), so its files are unavailable",
+			library.name
+		))),
+		None => Ok(()),
+	}
+}
+
 #[utoipa::path(
 	get,
 	path = "/api/v1/media/:id/convert",
@@ -823,6 +848,14 @@ async fn convert_media(
 	Err(ApiError::NotImplemented)
 }
 
+#[derive(Deserialize)]
+struct PageImageParams {
+	#[serde(default)]
+	width: Option<u32>,
+	#[serde(default)]
+	quality: Option<u8>,
+}
+
 // TODO: ImageResponse as body type
 #[utoipa::path(
 	get,
@@ -830,19 +863,24 @@ async fn convert_media(
 	tag = "media",
 	params(
 		("id" = String, Path, description = "The ID of the media to get"),
-		("page" = i32, Path, description = "The page to get")
+		("page" = i32, Path, description = "The page to get"),
+		("width" = Option<u32>, Query, description = "The width to resize the page to. This is clamped to the configured resize widths"),
+		("quality" = Option<u8>, Query, description = "The JPEG quality (1-100) to re-encode the page with")
 	),
 	responses(
 		(status = 200, description = "Successfully fetched media"),
 		(status = 401, description = "Unauthorized"),
 		(status = 403, description = "Forbidden"),
 		(status = 404, description = "Media not found"),
+		(status = 409, description = "The media's library is archived"),
 		(status = 500, description = "Internal server error"),
 	)
 )]
-/// Get a page of a media
+/// Get a page of a media. If a `width` or `quality` is provided, the page is resized and
+/// re-encoded as a JPEG. Otherwise, the original page is returned untouched.
 async fn get_media_page(
 	Path((id, page)): Path<(String, i32)>,
+	params: Query<PageImageParams>,
 	State(ctx): State<AppState>,
 	session: Session,
 ) -> ApiResult<ImageResponse> {
@@ -869,13 +907,32 @@ async fn get_media_page(
 		.ok_or(ApiError::NotFound(String::from("Media not found")))?;
 
 	if page > media.pages {
-		Err(ApiError::BadRequest(format!(
+		return Err(ApiError::BadRequest(format!(
 			"Page {} is out of bounds for media {}",
 			page, id
-		)))
-	} else {
-		Ok(get_page(&media.path, page, &ctx.config)?.into())
+		)));
+	}
+	enforce_library_online(db, &media.id).await?;
+
+	let PageImageParams { width, quality } = params.0;
+	if width.is_none() && quality.is_none() {
+		return Ok(get_page(&media.path, page, &ctx.config)?.into());
 	}
+
+	let options = PageResizeOptions::new(width, quality, &ctx.config.page_resize_widths)
+		.ok_or(ApiError::BadRequest(String::from(
+			"Resizing pages is disabled on this server",
+		)))?;
+	let config = ctx.config.clone();
+	let resized = tokio::task::spawn_blocking(move || {
+		get_resized_page(&media.id, &media.path, page, options, &config)
+	})
+	.await
+	.map_err(|e| {
+		ApiError::InternalServerError(format!("Failed to resize page: {}", e))
+	})??;
+
+	Ok(ImageResponse::from(resized).immutable())
 }
 
 pub(crate) async fn get_media_thumbnail_by_id(
@@ -6,11 +6,13 @@ use axum::{
 use hyper::header::USER_AGENT;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use stump_core::maintenance::MaintenanceMode;
 use utoipa::ToSchema;
 
 use crate::{
 	config::state::AppState,
 	errors::{ApiError, ApiResult},
+	utils::outbound_client,
 };
 
 pub(crate) mod auth;
@@ -25,6 +27,8 @@ pub(crate) mod metadata;
 pub(crate) mod notifier;
 pub(crate) mod reading_list;
 pub(crate) mod series;
+pub(crate) mod server;
+pub(crate) mod setup;
 pub(crate) mod smart_list;
 pub(crate) mod tag;
 pub(crate) mod user;
@@ -41,6 +45,8 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 		.merge(job::mount(app_state.clone()))
 		.merge(log::mount(app_state.clone()))
 		.merge(series::mount(app_state.clone()))
+		.merge(server::mount(app_state.clone()))
+		.merge(setup::mount())
 		.merge(tag::mount(app_state.clone()))
 		.merge(user::mount(app_state.clone()))
 		.merge(reading_list::mount())
@@ -48,11 +54,23 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 		.merge(book_club::mount(app_state))
 		.route("/claim", get(claim))
 		.route("/ping", get(ping))
+		.route("/maintenance", get(get_maintenance_status))
 		// TODO: should /version or /check-for-updates be behind any auth reqs?
 		.route("/version", post(version))
 		.route("/check-for-update", get(check_for_updates))
 }
 
+/// Mounts the minimal set of routes served while the server is running in degraded
+/// mode, which is the case when it failed to migrate the database on startup.
+pub(crate) fn mount_degraded(app_state: AppState) -> Router<AppState> {
+	Router::new()
+		.merge(auth::mount())
+		.merge(server::mount_degraded(app_state))
+		.route("/claim", get(claim))
+		.route("/ping", get(ping))
+		.route("/version", post(version))
+}
+
 #[derive(Serialize, Type, ToSchema)]
 pub struct ClaimResponse {
 	pub is_claimed: bool,
@@ -86,6 +104,35 @@ async fn ping() -> ApiResult<String> {
 	Ok("pong".to_string())
 }
 
+#[derive(Serialize, Type, ToSchema)]
+pub struct MaintenanceStatus {
+	/// Whether the server is in (read-only) maintenance mode
+	pub active: bool,
+	/// The details of the maintenance mode, if it is active
+	pub mode: Option<MaintenanceMode>,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/maintenance",
+	tag = "util",
+	responses(
+		(status = 200, description = "Maintenance status successfully determined", body = MaintenanceStatus)
+	)
+)]
+/// Get whether the server is in maintenance mode. This is public so that clients can
+/// explain why writes are being rejected, even before logging in.
+async fn get_maintenance_status(
+	State(ctx): State<AppState>,
+) -> ApiResult<Json<MaintenanceStatus>> {
+	let mode = ctx.maintenance_mode();
+
+	Ok(Json(MaintenanceStatus {
+		active: mode.is_some(),
+		mode,
+	}))
+}
+
 #[derive(Serialize, Deserialize, Type, ToSchema)]
 pub struct StumpVersion {
 	pub semver: String,
@@ -124,10 +171,10 @@ pub struct UpdateCheck {
 		(status = 200, description = "Check for updates", body = UpdateCheck)
 	)
 )]
-async fn check_for_updates() -> ApiResult<Json<UpdateCheck>> {
+async fn check_for_updates(State(ctx): State<AppState>) -> ApiResult<Json<UpdateCheck>> {
 	let current_semver = env!("CARGO_PKG_VERSION").to_string();
 
-	let client = reqwest::Client::new();
+	let client = outbound_client(&ctx.config)?;
 	let github_response = client
 		.get("https://api.github.com/repos/stumpapp/stump/releases/latest")
 		.header(USER_AGENT, "stumpapp/stump")
@@ -8,6 +8,7 @@ use serde::Deserialize;
 use specta::Type;
 use stump_core::{
 	db::entity::{Notifier, NotifierConfig, NotifierType, UserPermission},
+	event::CoreEvent,
 	prisma::notifier,
 };
 use tower_sessions::Session;
@@ -142,8 +143,14 @@ async fn create_notifier(
 		)
 		.exec()
 		.await?;
+	let notifier = Notifier::try_from(notifier)?;
 
-	Ok(Json(Notifier::try_from(notifier)?))
+	ctx.emit_event(CoreEvent::NotifierCreated {
+		id: notifier.id(),
+		notifier_type: notifier.notifier_type(),
+	});
+
+	Ok(Json(notifier))
 }
 
 #[utoipa::path(
@@ -183,8 +190,14 @@ async fn update_notifier(
 		)
 		.exec()
 		.await?;
+	let notifier = Notifier::try_from(notifier)?;
 
-	Ok(Json(Notifier::try_from(notifier)?))
+	ctx.emit_event(CoreEvent::NotifierUpdated {
+		id: notifier.id(),
+		notifier_type: notifier.notifier_type(),
+	});
+
+	Ok(Json(notifier))
 }
 
 #[derive(Deserialize, ToSchema, Type)]
@@ -241,7 +254,14 @@ async fn patch_notifier(
 		.exec()
 		.await?;
 
-	Ok(Json(Notifier::try_from(patched_notifier)?))
+	let patched_notifier = Notifier::try_from(patched_notifier)?;
+
+	ctx.emit_event(CoreEvent::NotifierUpdated {
+		id: patched_notifier.id(),
+		notifier_type: patched_notifier.notifier_type(),
+	});
+
+	Ok(Json(patched_notifier))
 }
 
 #[utoipa::path(
@@ -273,5 +293,11 @@ async fn delete_notifier(
 		.exec()
 		.await?;
 
-	Ok(Json(Notifier::try_from(deleted_notifier)?))
+	let deleted_notifier = Notifier::try_from(deleted_notifier)?;
+
+	ctx.emit_event(CoreEvent::NotifierDeleted {
+		id: deleted_notifier.id(),
+	});
+
+	Ok(Json(deleted_notifier))
 }
@@ -0,0 +1,399 @@
+use std::time::Duration;
+
+use axum::{
+	extract::State,
+	http::{header, HeaderMap},
+	middleware::{from_extractor, from_extractor_with_state},
+	routing::{get, post},
+	Json, Router,
+};
+use prisma_client_rust::chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_qs::axum::QsQuery;
+use specta::Type;
+use stump_core::{
+	db::{
+		bundle::{
+			export_server_bundle, import_server_bundle, BundleExportOptions,
+			BundleImportOptions, ImportPlan, ServerBundle,
+		},
+		maintenance::{DatabaseMaintenanceJob, DatabaseMaintenanceStage},
+		migration::{get_migration_status, MigrationStatus},
+	},
+	event::InternalCoreTask,
+	filesystem::ContentType,
+	job::JobQueueStatus,
+	maintenance::{enter_maintenance_mode, exit_maintenance_mode},
+	support::{
+		build_support_bundle, SupportBundleOptions, DEFAULT_SUPPORT_BUNDLE_JOB_LIMIT,
+	},
+	telemetry::{collect_telemetry_report, is_telemetry_enabled, TelemetryReport},
+	warm_up::WarmUpStatus,
+};
+use tokio::sync::oneshot;
+use utoipa::ToSchema;
+
+use crate::{
+	config::state::AppState,
+	errors::{ApiError, ApiResult},
+	middleware::auth::{Auth, ServerOwnerGuard},
+	utils::http::BufferResponse,
+};
+
+use super::{job::get_job_queue_status, MaintenanceStatus};
+
+/// The header used to provide the passphrase for encrypting secrets during export. A
+/// header is used so the passphrase doesn't end up in any request logs.
+const BUNDLE_PASSPHRASE_HEADER: &str = "x-bundle-passphrase";
+/// How long a support bundle waits for the job queue before it is built without it
+const SUPPORT_BUNDLE_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
+	owner_router(
+		migration_router()
+			.route("/export", get(export_bundle))
+			.route("/import", post(import_bundle))
+			.route("/telemetry/preview", get(preview_telemetry))
+			.route("/maintenance", post(update_maintenance_mode))
+			.route("/db-maintenance", post(run_database_maintenance))
+			.route("/support-bundle", get(get_support_bundle))
+			.route("/warm-up", get(get_warm_up_status)),
+		app_state,
+	)
+}
+
+/// Mounts only the routes which are safe to serve while the server is running in
+/// degraded mode, i.e. after a failed migration.
+pub(crate) fn mount_degraded(app_state: AppState) -> Router<AppState> {
+	owner_router(migration_router(), app_state)
+}
+
+/// Nests the given routes under `/server`, restricted to the server owner
+fn owner_router(routes: Router<AppState>, app_state: AppState) -> Router<AppState> {
+	Router::new()
+		.nest("/server", routes)
+		.layer(from_extractor::<ServerOwnerGuard>())
+		.layer(from_extractor_with_state::<Auth, AppState>(app_state))
+}
+
+fn migration_router() -> Router<AppState> {
+	Router::new().route("/migrations", get(get_migrations))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/server/migrations",
+	tag = "server",
+	responses(
+		(status = 200, description = "Successfully fetched migration status", body = MigrationStatus),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Get the migration history of the database, alongside any migrations bundled with
+/// this version of Stump which have not been applied yet.
+async fn get_migrations(State(ctx): State<AppState>) -> ApiResult<Json<MigrationStatus>> {
+	Ok(Json(get_migration_status(ctx.get_db()).await?))
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/server/warm-up",
+	tag = "server",
+	responses(
+		(status = 200, description = "Successfully fetched warm-up status", body = WarmUpStatus),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+	)
+)]
+/// Get the status of the startup warm-up, i.e. which library directories are available,
+/// still being waited on, or were flagged as unavailable.
+async fn get_warm_up_status(
+	State(ctx): State<AppState>,
+) -> ApiResult<Json<WarmUpStatus>> {
+	Ok(Json(ctx.warm_up.status()))
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct ExportBundleParams {
+	/// Whether to include the read progress of every user
+	#[serde(default)]
+	include_progress: bool,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/server/export",
+	tag = "server",
+	params(
+		("include_progress" = Option<bool>, Query, description = "Whether to include read progress"),
+		("x-bundle-passphrase" = Option<String>, Header, description = "The passphrase used to encrypt secrets. Secrets are omitted if not provided")
+	),
+	responses(
+		(status = 200, description = "Successfully exported server bundle", body = ServerBundle),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Export the configuration of the server (users, libraries, notifiers and schedules)
+/// as a versioned bundle which can later be imported for disaster recovery.
+async fn export_bundle(
+	State(ctx): State<AppState>,
+	QsQuery(params): QsQuery<ExportBundleParams>,
+	headers: HeaderMap,
+) -> ApiResult<Json<ServerBundle>> {
+	let passphrase = headers
+		.get(BUNDLE_PASSPHRASE_HEADER)
+		.and_then(|value| value.to_str().ok())
+		.filter(|value| !value.is_empty())
+		.map(String::from);
+
+	let bundle = export_server_bundle(
+		ctx.get_db(),
+		BundleExportOptions {
+			include_progress: params.include_progress,
+			passphrase,
+		},
+	)
+	.await?;
+
+	Ok(Json(bundle))
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct ImportBundle {
+	bundle: ServerBundle,
+	#[serde(default)]
+	options: BundleImportOptions,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/server/import",
+	tag = "server",
+	request_body = ImportBundle,
+	responses(
+		(status = 200, description = "Successfully imported (or planned the import of) the bundle", body = ImportPlan),
+		(status = 400, description = "The bundle is invalid or the passphrase is incorrect."),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Import a bundle previously created by the export endpoint. All changes are applied in
+/// a single transaction, and conflicts with existing users or libraries are skipped unless
+/// an OVERWRITE resolution is provided for them. Use `dry_run` to preview the changes.
+async fn import_bundle(
+	State(ctx): State<AppState>,
+	Json(input): Json<ImportBundle>,
+) -> ApiResult<Json<ImportPlan>> {
+	let plan = import_server_bundle(ctx.get_db(), input.bundle, input.options).await?;
+
+	if !plan.dry_run {
+		tracing::info!(
+			changes = plan.changes.len(),
+			conflicts = plan.conflicts().len(),
+			"Imported server bundle"
+		);
+	}
+
+	Ok(Json(plan))
+}
+
+#[derive(Serialize, Type, ToSchema)]
+pub struct TelemetryPreview {
+	/// Whether telemetry is currently enabled. This will be false whenever the kill switch
+	/// is set, regardless of the configured opt-in.
+	enabled: bool,
+	/// The report which would be sent, were telemetry enabled
+	report: TelemetryReport,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/server/telemetry/preview",
+	tag = "server",
+	responses(
+		(status = 200, description = "Successfully generated telemetry preview", body = TelemetryPreview),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Preview the anonymous usage report for the server, exactly as it would be sent. This
+/// is available regardless of whether telemetry has been opted into.
+async fn preview_telemetry(
+	State(ctx): State<AppState>,
+) -> ApiResult<Json<TelemetryPreview>> {
+	let report = collect_telemetry_report(ctx.get_db(), &ctx.config).await?;
+
+	Ok(Json(TelemetryPreview {
+		enabled: is_telemetry_enabled(&ctx.config),
+		report,
+	}))
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct UpdateMaintenanceMode {
+	/// Whether maintenance mode should be active
+	enabled: bool,
+	/// The message to show clients while maintenance mode is active
+	message: Option<String>,
+	/// The datetime stamp (RFC 3339) after which maintenance mode is automatically exited
+	expires_at: Option<String>,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/server/maintenance",
+	tag = "server",
+	request_body = UpdateMaintenanceMode,
+	responses(
+		(status = 200, description = "Successfully updated maintenance mode", body = MaintenanceStatus),
+		(status = 400, description = "The expiry is invalid or in the past."),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Enter or exit (read-only) maintenance mode. While active, all mutating endpoints respond
+/// with a 503 and a `MAINTENANCE_MODE` code, while reads continue to work. Exiting
+/// maintenance mode resumes any jobs which were held while it was active.
+async fn update_maintenance_mode(
+	State(ctx): State<AppState>,
+	Json(input): Json<UpdateMaintenanceMode>,
+) -> ApiResult<Json<MaintenanceStatus>> {
+	if !input.enabled {
+		exit_maintenance_mode(&ctx).await?;
+		return Ok(Json(MaintenanceStatus {
+			active: false,
+			mode: None,
+		}));
+	}
+
+	let expires_at = input
+		.expires_at
+		.map(|expires_at| {
+			DateTime::parse_from_rfc3339(&expires_at).map_err(|e| {
+				ApiError::BadRequest(format!("Invalid expiry provided: {}", e))
+			})
+		})
+		.transpose()?;
+	if matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+		return Err(ApiError::BadRequest(String::from(
+			"The expiry for maintenance mode must be in the future",
+		)));
+	}
+
+	let message = input.message.filter(|message| !message.trim().is_empty());
+	let mode = enter_maintenance_mode(&ctx, message, expires_at).await?;
+
+	Ok(Json(MaintenanceStatus {
+		active: true,
+		mode: Some(mode),
+	}))
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct RunDatabaseMaintenance {
+	/// The stages to run, in order. Defaults to an integrity check, ANALYZE and optimize
+	stages: Option<Vec<DatabaseMaintenanceStage>>,
+	/// Whether to start the job even if the system is under pressure
+	#[serde(default)]
+	force: bool,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/server/db-maintenance",
+	tag = "server",
+	request_body = RunDatabaseMaintenance,
+	responses(
+		(status = 200, description = "Successfully queued database maintenance", body = JobQueueStatus),
+		(status = 400, description = "No stages were selected."),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Queue a job which checks the integrity of the database and optimizes it. The result
+/// of each stage, including the full integrity check output on failure, is recorded in
+/// the logs of the job. Responds with the job's position in the queue and an estimate of
+/// when it will start.
+async fn run_database_maintenance(
+	State(ctx): State<AppState>,
+	Json(input): Json<RunDatabaseMaintenance>,
+) -> ApiResult<Json<JobQueueStatus>> {
+	let stages = input
+		.stages
+		.unwrap_or_else(DatabaseMaintenanceStage::defaults);
+
+	let job_id = ctx
+		.enqueue_job(DatabaseMaintenanceJob::new(stages).with_force(input.force))
+		.await?;
+
+	Ok(Json(get_job_queue_status(&ctx, job_id).await?))
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct SupportBundleParams {
+	/// The number of most recent jobs (with their logs) to include
+	job_limit: Option<i64>,
+	/// Whether to redact the user-identifiable segments of paths, e.g. home directories
+	#[serde(default)]
+	redact_paths: bool,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/server/support-bundle",
+	tag = "server",
+	params(
+		("job_limit" = Option<i64>, Query, description = "The number of recent jobs to include"),
+		("redact_paths" = Option<bool>, Query, description = "Whether to redact the user-identifiable segments of paths")
+	),
+	responses(
+		(status = 200, description = "Successfully generated the support bundle (a zip archive)"),
+		(status = 401, description = "No user is logged in (unauthorized)."),
+		(status = 403, description = "User does not have permission to access this resource."),
+		(status = 500, description = "Internal server error."),
+	)
+)]
+/// Download a support bundle for diagnosing problems, e.g. with the job system. The bundle
+/// is a zip archive containing the server config, the most recent jobs with their logs and
+/// output, the live job queue, the migration status and the tail of the server log.
+async fn get_support_bundle(
+	State(ctx): State<AppState>,
+	QsQuery(params): QsQuery<SupportBundleParams>,
+) -> ApiResult<([(header::HeaderName, String); 1], BufferResponse)> {
+	let (task_tx, task_rx) = oneshot::channel();
+	ctx.dispatch_task(InternalCoreTask::GetJobQueue(task_tx))?;
+	// The bundle is still useful without the queue, e.g. if the job manager is stuck
+	let queue = match tokio::time::timeout(SUPPORT_BUNDLE_QUEUE_TIMEOUT, task_rx).await {
+		Ok(Ok(Ok(queue))) => Some(queue),
+		error => {
+			tracing::warn!(?error, "Failed to get the job queue for a support bundle");
+			None
+		},
+	};
+
+	let options = SupportBundleOptions {
+		job_limit: params
+			.job_limit
+			.unwrap_or(DEFAULT_SUPPORT_BUNDLE_JOB_LIMIT)
+			.max(0),
+		redact_paths: params.redact_paths,
+	};
+	let archive = build_support_bundle(ctx.get_db(), &ctx.config, queue, options).await?;
+
+	let file_name = format!("stump-support-{}.zip", Utc::now().format("%Y%m%d%H%M%S"));
+	Ok((
+		[(
+			header::CONTENT_DISPOSITION,
+			format!("attachment; filename=\"{}\"", file_name),
+		)],
+		BufferResponse::new(ContentType::ZIP, archive),
+	))
+}
@@ -0,0 +1,258 @@
+use std::path;
+
+use axum::{
+	extract::State,
+	routing::{get, post},
+	Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use stump_core::{
+	db::{
+		entity::{Library, LibraryOptions, LibraryScanMode, User},
+		run_in_transaction,
+	},
+	filesystem::scanner::LibraryScanJob,
+	prisma::{library, library_options, user, user_preferences},
+	setup::{complete_setup, verify_setup_token},
+};
+use utoipa::ToSchema;
+
+use crate::{
+	config::state::AppState,
+	errors::{ApiError, ApiResult},
+};
+
+pub(crate) fn mount() -> Router<AppState> {
+	Router::new().nest(
+		"/setup",
+		Router::new()
+			.route("/status", get(get_setup_status))
+			.route("/complete", post(complete_setup_handler)),
+	)
+}
+
+/// Errors with a 410 once setup has been completed, since the setup routes are single-use.
+fn enforce_setup_pending(ctx: &AppState) -> ApiResult<()> {
+	if ctx.is_setup_required() {
+		Ok(())
+	} else {
+		Err(ApiError::Gone(String::from(
+			"Setup has already been completed",
+		)))
+	}
+}
+
+#[derive(Serialize, Type, ToSchema)]
+pub struct SetupStatus {
+	/// Whether the server is awaiting first-run setup
+	pub is_setup_required: bool,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/setup/status",
+	tag = "setup",
+	responses(
+		(status = 200, description = "Setup is pending", body = SetupStatus),
+		(status = 410, description = "Setup has already been completed")
+	)
+)]
+/// Get whether the server is awaiting first-run setup.
+async fn get_setup_status(State(ctx): State<AppState>) -> ApiResult<Json<SetupStatus>> {
+	enforce_setup_pending(&ctx)?;
+
+	Ok(Json(SetupStatus {
+		is_setup_required: true,
+	}))
+}
+
+#[derive(Deserialize, Debug, Type, ToSchema)]
+pub struct SetupLibrary {
+	/// The name of the library to create
+	pub name: String,
+	/// The path to the library on the filesystem
+	pub path: String,
+	/// How the library should be scanned after creation. Default is `BATCHED`.
+	pub scan_mode: Option<LibraryScanMode>,
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct CompleteSetup {
+	/// The one-time setup token, written to the server log on startup
+	pub token: String,
+	/// The username of the server owner account
+	pub username: String,
+	/// The password of the server owner account
+	pub password: String,
+	/// The first library to create, if any
+	pub library: Option<SetupLibrary>,
+}
+
+#[derive(Serialize, Type, ToSchema)]
+pub struct SetupResult {
+	/// The created server owner account
+	pub user: User,
+	/// The created library, if one was requested
+	pub library: Option<Library>,
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/setup/complete",
+	tag = "setup",
+	request_body = CompleteSetup,
+	responses(
+		(status = 200, description = "Successfully completed setup", body = SetupResult),
+		(status = 400, description = "Bad request"),
+		(status = 401, description = "Invalid setup token"),
+		(status = 410, description = "Setup has already been completed"),
+		(status = 500, description = "Internal server error")
+	)
+)]
+/// Completes first-run setup, creating the server owner account and (optionally) the first
+/// library in a single transaction. Requires the one-time token written to the server log.
+async fn complete_setup_handler(
+	State(ctx): State<AppState>,
+	Json(input): Json<CompleteSetup>,
+) -> ApiResult<Json<SetupResult>> {
+	enforce_setup_pending(&ctx)?;
+
+	if !verify_setup_token(&ctx, &input.token) {
+		return Err(ApiError::Unauthorized);
+	}
+
+	if let Some(library) = input.library.as_ref() {
+		if !path::Path::new(&library.path).exists() {
+			return Err(ApiError::BadRequest(format!(
+				"The library directory does not exist: {}",
+				library.path
+			)));
+		}
+	}
+
+	let db = ctx.get_db();
+	let hashed_password = bcrypt::hash(&input.password, ctx.config.password_hash_cost)?;
+	let username = input.username;
+	let library_input = input.library;
+
+	let (created_user_id, created_library) =
+		run_in_transaction(db, |client| async move {
+			// Another request may have completed setup since the token was checked
+			if client.user().find_first(vec![]).exec().await?.is_some() {
+				return Err(ApiError::Gone(String::from(
+					"Setup has already been completed",
+				)));
+			}
+
+			let created_user = client
+				.user()
+				.create(
+					username,
+					hashed_password,
+					vec![user::is_server_owner::set(true)],
+				)
+				.exec()
+				.await?;
+
+			let _user_preferences = client
+				.user_preferences()
+				.create(vec![user_preferences::user::connect(user::id::equals(
+					created_user.id.clone(),
+				))])
+				.exec()
+				.await?;
+
+			let Some(library_input) = library_input else {
+				return Ok((created_user.id, None));
+			};
+
+			let library_options_arg = LibraryOptions::default();
+			let library_options = client
+				.library_options()
+				.create(vec![
+					library_options::convert_rar_to_zip::set(
+						library_options_arg.convert_rar_to_zip,
+					),
+					library_options::hard_delete_conversions::set(
+						library_options_arg.hard_delete_conversions,
+					),
+					library_options::library_pattern::set(
+						library_options_arg.library_pattern.to_string(),
+					),
+					library_options::extract_metadata::set(
+						library_options_arg.extract_metadata,
+					),
+					library_options::generate_file_hashes::set(
+						library_options_arg.generate_file_hashes,
+					),
+				])
+				.exec()
+				.await?;
+
+			let library = client
+				.library()
+				.create(
+					library_input.name,
+					library_input.path,
+					library_options::id::equals(library_options.id.clone()),
+					vec![],
+				)
+				.exec()
+				.await?;
+
+			let library_options = client
+				.library_options()
+				.update(
+					library_options::id::equals(library_options.id),
+					vec![
+						library_options::library::connect(library::id::equals(
+							library.id.clone(),
+						)),
+						library_options::library_id::set(Some(library.id.clone())),
+					],
+				)
+				.exec()
+				.await?;
+
+			Ok((
+				created_user.id,
+				Some((
+					Library::from((library, library_options)),
+					library_input.scan_mode.unwrap_or_default(),
+				)),
+			))
+		})
+		.await?;
+
+	complete_setup(&ctx);
+
+	let library = match created_library {
+		Some((library, scan_mode)) => {
+			if scan_mode != LibraryScanMode::None {
+				ctx.enqueue_job(LibraryScanJob::new(library.path.clone(), scan_mode))
+					.await?;
+			}
+			Some(library)
+		},
+		None => None,
+	};
+
+	let user = db
+		.user()
+		.find_unique(user::id::equals(created_user_id))
+		.with(user::user_preferences::fetch())
+		.with(user::age_restriction::fetch())
+		.exec()
+		.await?
+		.ok_or_else(|| {
+			ApiError::InternalServerError(String::from(
+				"Failed to fetch the server owner after setup",
+			))
+		})?;
+
+	Ok(Json(SetupResult {
+		user: user.into(),
+		library,
+	}))
+}
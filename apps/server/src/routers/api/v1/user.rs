@@ -3,11 +3,14 @@ use std::{fs::File, io::Write};
 use axum::{
 	extract::{DefaultBodyLimit, Multipart, Path, State},
 	middleware::from_extractor_with_state,
-	routing::{delete, get, put},
+	routing::{delete, get, post, put},
 	Json, Router,
 };
 use axum_extra::extract::Query;
-use prisma_client_rust::{chrono::Utc, Direction};
+use prisma_client_rust::{
+	chrono::{Duration, Utc},
+	Direction,
+};
 use serde::Deserialize;
 use specta::Type;
 use stump_core::{
@@ -15,6 +18,8 @@ use stump_core::{
 	db::{
 		entity::{AgeRestriction, LoginActivity, User, UserPermission, UserPreferences},
 		query::pagination::{Pageable, Pagination, PaginationQuery},
+		reading_stats::{get_reading_stats, ReadingStats},
+		run_in_transaction,
 	},
 	filesystem::{
 		get_unknown_image, read_entire_file, ContentType, FileParts, PathUtils,
@@ -30,18 +35,22 @@ use utoipa::ToSchema;
 
 use crate::{
 	config::{session::SESSION_USER_KEY, state::AppState},
-	errors::{ApiError, ApiResult},
+	errors::{ApiError, ApiResult, CodedErrorBody},
 	filter::UserQueryRelation,
 	middleware::auth::Auth,
 	utils::{
-		get_session_server_owner_user, get_session_user, get_user_and_enforce_permission,
-		http::ImageResponse, validate_image_upload,
+		enforce_elevated_session, get_session_server_owner_user, get_session_user,
+		get_user_and_enforce_permission, http::ImageResponse, string_to_date,
+		validate_image_upload,
 	},
 };
 
+use super::series::apply_series_age_restriction;
+
 pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 	Router::new()
 		.route("/users", get(get_users).post(create_user))
+		.route("/users/bulk-update", post(bulk_update_users))
 		.route(
 			"/users/login-activity",
 			get(get_user_login_activity).delete(delete_user_login_activity),
@@ -50,7 +59,8 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 			"/users/me",
 			Router::new()
 				.route("/", put(update_current_user))
-				.route("/preferences", put(update_current_user_preferences)),
+				.route("/preferences", put(update_current_user_preferences))
+				.route("/reading-stats", get(get_current_user_reading_stats)),
 		)
 		.nest(
 			"/users/:id",
@@ -542,6 +552,68 @@ async fn update_current_user(
 	Ok(Json(updated_user))
 }
 
+/// The range covered by the reading statistics when no start date is given
+const DEFAULT_READING_STATS_DAYS: i64 = 30;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReadingStatsQuery {
+	/// The start of the range (RFC 3339). Defaults to 30 days before the end of the range.
+	from: Option<String>,
+	/// The end of the range (RFC 3339). Defaults to now.
+	to: Option<String>,
+}
+
+#[utoipa::path(
+	get,
+	path = "/api/v1/users/me/reading-stats",
+	tag = "user",
+	params(
+		("query" = ReadingStatsQuery, Query, description = "The range to compute statistics for"),
+	),
+	responses(
+		(status = 200, description = "Successfully computed reading statistics", body = ReadingStats),
+		(status = 400, description = "Bad request"),
+		(status = 401, description = "Unauthorized"),
+		(status = 500, description = "Internal server error"),
+	)
+)]
+/// Get the reading statistics of the session user within a date range: books finished, pages
+/// read and the longest reading streak, along with a few series they have yet to start.
+async fn get_current_user_reading_stats(
+	session: Session,
+	State(ctx): State<AppState>,
+	Query(query): Query<ReadingStatsQuery>,
+) -> ApiResult<Json<ReadingStats>> {
+	let user = get_session_user(&session)?;
+
+	let to = query
+		.to
+		.map(string_to_date)
+		.transpose()?
+		.unwrap_or_else(Utc::now);
+	let from = query
+		.from
+		.map(string_to_date)
+		.transpose()?
+		.unwrap_or_else(|| to - Duration::days(DEFAULT_READING_STATS_DAYS));
+	if from > to {
+		return Err(ApiError::BadRequest(String::from(
+			"The start of the range must be before its end",
+		)));
+	}
+
+	let series_filters = user
+		.age_restriction
+		.as_ref()
+		.map(|ar| vec![apply_series_age_restriction(ar.age, ar.restrict_on_unset)])
+		.unwrap_or_default();
+
+	let stats =
+		get_reading_stats(ctx.get_db(), &user.id, from, to, series_filters).await?;
+
+	Ok(Json(stats))
+}
+
 #[derive(Debug, Clone, Deserialize, Type, ToSchema)]
 pub struct UpdateUserPreferences {
 	pub id: String,
@@ -617,12 +689,12 @@ pub struct DeleteUser {
 	responses(
 		(status = 200, description = "Successfully deleted user", body = User),
 		(status = 401, description = "Unauthorized"),
-		(status = 403, description = "Forbidden"),
+		(status = 403, description = "Forbidden, or the session must be re-authenticated", body = CodedErrorBody),
 		(status = 404, description = "User not found"),
 		(status = 500, description = "Internal server error"),
 	)
 )]
-/// Deletes a user by ID.
+/// Deletes a user by ID. A hard delete requires a recently re-authenticated session.
 async fn delete_user_by_id(
 	Path(id): Path<String>,
 	State(ctx): State<AppState>,
@@ -639,6 +711,9 @@ async fn delete_user_by_id(
 	}
 
 	let hard_delete = input.hard_delete.unwrap_or(false);
+	if hard_delete {
+		enforce_elevated_session(&session, db).await?;
+	}
 
 	let deleted_user = if hard_delete {
 		db.user().delete(user::id::equals(id.clone())).exec().await
@@ -788,6 +863,203 @@ async fn update_user_handler(
 	Ok(Json(updated_user))
 }
 
+#[derive(Deserialize, Type, ToSchema)]
+pub struct BulkUserChanges {
+	/// Permissions to grant to every user
+	#[serde(default)]
+	pub add_permissions: Vec<UserPermission>,
+	/// Permissions to revoke from every user. Revoking takes precedence over granting.
+	#[serde(default)]
+	pub remove_permissions: Vec<UserPermission>,
+	/// The age restriction to set for every user
+	pub age_restriction: Option<AgeRestriction>,
+	/// Whether to remove the age restriction of every user. Ignored if an age restriction
+	/// is provided.
+	#[serde(default)]
+	pub clear_age_restriction: bool,
+}
+
+#[derive(Deserialize, Type, ToSchema)]
+pub struct BulkUpdateUsers {
+	pub user_ids: Vec<String>,
+	pub changes: BulkUserChanges,
+}
+
+/// Returns the permissions after granting and revoking the given permissions, preserving the
+/// order of the existing permissions.
+fn apply_permission_changes(
+	existing: Vec<UserPermission>,
+	add: &[UserPermission],
+	remove: &[UserPermission],
+) -> Vec<UserPermission> {
+	let mut permissions = existing;
+	for permission in add {
+		if !permissions.contains(permission) {
+			permissions.push(*permission);
+		}
+	}
+	permissions.retain(|permission| !remove.contains(permission));
+	permissions
+}
+
+#[utoipa::path(
+	post,
+	path = "/api/v1/users/bulk-update",
+	tag = "user",
+	request_body = BulkUpdateUsers,
+	responses(
+		(status = 200, description = "Successfully updated users", body = [User]),
+		(status = 400, description = "Bad request"),
+		(status = 401, description = "Unauthorized"),
+		(status = 403, description = "Forbidden"),
+		(status = 404, description = "One or more users not found"),
+		(status = 500, description = "Internal server error"),
+	)
+)]
+/// Applies the same permission and age restriction changes to many users at once. Either
+/// every user is updated or none are, and the updated users are returned. Server owners
+/// can't be bulk updated, since their permissions are implicit.
+async fn bulk_update_users(
+	session: Session,
+	State(ctx): State<AppState>,
+	Json(input): Json<BulkUpdateUsers>,
+) -> ApiResult<Json<Vec<User>>> {
+	let by_user = get_user_and_enforce_permission(&session, UserPermission::ManageUsers)?;
+	let client = ctx.get_db();
+
+	if input.user_ids.is_empty() {
+		return Err(ApiError::BadRequest(String::from("No users were provided")));
+	}
+
+	// Users who manage other users may only grant permissions they have themselves
+	if let Some(permission) = input
+		.changes
+		.add_permissions
+		.iter()
+		.find(|permission| !by_user.has_permission(**permission))
+	{
+		return Err(ApiError::Forbidden(format!(
+			"You can't grant a permission you don't have: {}",
+			permission.to_string()
+		)));
+	}
+
+	let user_ids = input.user_ids;
+	let changes = input.changes;
+	let (added_permissions, removed_permissions) = (
+		changes.add_permissions.clone(),
+		changes.remove_permissions.clone(),
+	);
+	let updated_users: Vec<User> = run_in_transaction(client, |tx| async move {
+		let users = tx
+			.user()
+			.find_many(vec![
+				user::id::in_vec(user_ids.clone()),
+				user::deleted_at::equals(None),
+			])
+			.exec()
+			.await?;
+
+		let missing = user_ids
+			.iter()
+			.filter(|id| !users.iter().any(|user| &user.id == *id))
+			.cloned()
+			.collect::<Vec<String>>();
+		if !missing.is_empty() {
+			return Err(ApiError::NotFound(format!(
+				"Users not found: {}",
+				missing.join(", ")
+			)));
+		}
+		if let Some(owner) = users.iter().find(|user| user.is_server_owner) {
+			return Err(ApiError::BadRequest(format!(
+				"The server owner ({}) can't be bulk updated",
+				owner.username
+			)));
+		}
+
+		let mut updated_users = vec![];
+		for user in users {
+			let user_id = user.id.clone();
+			let permissions = apply_permission_changes(
+				User::from(user).permissions,
+				&changes.add_permissions,
+				&changes.remove_permissions,
+			);
+
+			if let Some(age_restriction) = changes.age_restriction.as_ref() {
+				tx.age_restriction()
+					.upsert(
+						age_restriction::user_id::equals(user_id.clone()),
+						(
+							age_restriction.age,
+							user::id::equals(user_id.clone()),
+							vec![age_restriction::restrict_on_unset::set(
+								age_restriction.restrict_on_unset,
+							)],
+						),
+						vec![
+							age_restriction::age::set(age_restriction.age),
+							age_restriction::restrict_on_unset::set(
+								age_restriction.restrict_on_unset,
+							),
+						],
+					)
+					.exec()
+					.await?;
+			} else if changes.clear_age_restriction {
+				tx.age_restriction()
+					.delete_many(vec![age_restriction::user_id::equals(user_id.clone())])
+					.exec()
+					.await?;
+			}
+
+			let updated_user = tx
+				.user()
+				.update(
+					user::id::equals(user_id),
+					vec![user::permissions::set(Some(
+						permissions
+							.into_iter()
+							.map(|p| p.to_string())
+							.collect::<Vec<String>>()
+							.join(","),
+					))],
+				)
+				.with(user::user_preferences::fetch())
+				.with(user::age_restriction::fetch())
+				.exec()
+				.await?;
+			updated_users.push(User::from(updated_user));
+		}
+
+		Ok(updated_users)
+	})
+	.await?;
+
+	// As with single updates, the sessions of the updated users are deleted so that the
+	// changes take effect immediately
+	let updated_user_ids = updated_users
+		.iter()
+		.map(|user| user.id.clone())
+		.collect::<Vec<String>>();
+	client
+		.session()
+		.delete_many(vec![session::user_id::in_vec(updated_user_ids.clone())])
+		.exec()
+		.await?;
+
+	tracing::info!(
+		by_user_id = by_user.id.as_str(),
+		user_ids = ?updated_user_ids,
+		?added_permissions,
+		?removed_permissions,
+		"Bulk updated users"
+	);
+
+	Ok(Json(updated_users))
+}
+
 #[utoipa::path(
 	delete,
 	path = "/api/v1/users/:id/sessions",
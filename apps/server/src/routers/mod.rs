@@ -1,6 +1,10 @@
-use axum::Router;
+use axum::{middleware::from_extractor_with_state, Router};
 
-use crate::config::state::AppState;
+use crate::{
+	config::state::AppState,
+	errors::ApiError,
+	middleware::{maintenance::MaintenanceGuard, setup::SetupGuard},
+};
 
 mod api;
 mod opds;
@@ -21,5 +25,23 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 		.merge(ws::mount())
 		.merge(sse::mount())
 		.merge(api::mount(app_state.clone()))
-		.merge(opds::mount(app_state))
+		.merge(opds::mount(app_state.clone()))
+		.layer(from_extractor_with_state::<MaintenanceGuard, AppState>(
+			app_state.clone(),
+		))
+		.layer(from_extractor_with_state::<SetupGuard, AppState>(app_state))
+}
+
+/// Mounts the routes for a server running in degraded mode. Only health, version, and
+/// migration status endpoints are available, everything else responds with a 503.
+pub(crate) fn mount_degraded(app_state: AppState) -> Router<AppState> {
+	Router::new()
+		.merge(api::mount_degraded(app_state))
+		.fallback(degraded_fallback)
+}
+
+async fn degraded_fallback() -> ApiError {
+	ApiError::ServiceUnavailable(String::from(
+		"Stump is running in degraded mode because the database could not be migrated. Please check the server logs and /api/v1/server/migrations",
+	))
 }
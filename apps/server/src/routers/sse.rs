@@ -7,8 +7,12 @@ use axum::{
 	Router,
 };
 use futures_util::{stream::Stream, StreamExt};
+use tower_sessions::Session;
 
-use crate::{config::state::AppState, utils::shutdown_signal};
+use crate::{
+	config::state::AppState,
+	utils::{can_receive_event, get_session_user, shutdown_signal},
+};
 
 // TODO: do I need auth middleware here? I think so.
 pub(crate) fn mount() -> Router<AppState> {
@@ -18,12 +22,18 @@ pub(crate) fn mount() -> Router<AppState> {
 
 async fn sse_handler(
 	State(ctx): State<AppState>,
+	session: Session,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+	// Without a session, only events which don't require a permission are sent
+	let user = get_session_user(&session).ok();
 	let mut rx = ctx.get_client_receiver();
 
 	let stream = async_stream::stream! {
 		loop {
 			if let Ok(msg) = rx.recv().await {
+				if !can_receive_event(user.as_ref(), &msg) {
+					continue;
+				}
 				yield Ok(Event::default().json_data(&msg).unwrap());
 			} else {
 				continue;
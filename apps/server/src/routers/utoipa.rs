@@ -1,19 +1,36 @@
 use axum::middleware::from_extractor_with_state;
 use axum::Router;
+use stump_core::db::bundle::{
+	BundleImportOptions, BundleItemKind, BundledLibrary, BundledNotifier,
+	BundledReadProgress, BundledScanSchedule, BundledUser, ConflictResolution,
+	EncryptedSecrets, ImportPlan, PlannedAction, PlannedChange, ServerBundle,
+};
 use stump_core::db::entity::*;
 // TODO: investigate how to get this working for swagger...
 use stump_core::db::filter::{SmartFilterSchema as SmartFilter, *};
+use stump_core::db::maintenance::DatabaseMaintenanceStage;
+use stump_core::db::migration::{
+	AppliedMigration, MigrationRisk, MigrationState, MigrationStatus, PendingMigration,
+};
 use stump_core::db::query::{ordering::*, pagination::*};
+use stump_core::db::reading_stats::ReadingStats;
 use stump_core::filesystem::{
 	DirectoryListing, DirectoryListingFile, DirectoryListingInput,
 };
-use stump_core::job::{JobDetail, JobStatus};
+use stump_core::job::{
+	JobDetail, JobLimits, JobManagerConfig, JobQueueSnapshot, JobQueueStatus, JobStatus,
+	JobWaitTimes, QueueCompletionEstimate, QueuedJob, UpdateJobLimits, WaitExplanation,
+	WaitReason,
+};
+use stump_core::maintenance::MaintenanceMode;
+use stump_core::telemetry::{JobDurationPercentiles, JobTypeDurations, TelemetryReport};
+use stump_core::warm_up::{LibraryAvailability, LibraryAvailabilityStatus, WarmUpStatus};
 
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::config::state::AppState;
-use crate::errors::ApiError;
+use crate::errors::{ApiError, CodedErrorBody};
 use crate::filter::{
 	FilterableLibraryQuery, FilterableMediaQuery, FilterableSeriesQuery, LibraryFilter,
 	MediaFilter, SeriesFilter, SeriesQueryRelation,
@@ -23,8 +40,18 @@ use crate::middleware::auth::Auth;
 use super::api::{
 	self,
 	v1::{
-		auth::LoginOrRegisterArgs, library::*, media::*, notifier::*, series::*,
-		smart_list::*, user::*, ClaimResponse, StumpVersion,
+		auth::{LoginOrRegisterArgs, ReverifyArgs, SessionElevationStatus},
+		book_club::*,
+		job::{UpdatePausedJobType, UpdateQueuePaused},
+		library::*,
+		media::*,
+		notifier::*,
+		series::*,
+		server::*,
+		setup::*,
+		smart_list::*,
+		user::*,
+		ClaimResponse, MaintenanceStatus, StumpVersion,
 	},
 };
 
@@ -37,24 +64,49 @@ use super::api::{
     paths(
         api::v1::claim,
         api::v1::ping,
+        api::v1::get_maintenance_status,
         api::v1::version,
+        api::v1::check_for_updates,
         api::v1::auth::viewer,
         api::v1::auth::login,
         api::v1::auth::logout,
+        api::v1::auth::reverify,
         api::v1::auth::register,
+        api::v1::book_club::get_book_clubs,
+        api::v1::book_club::create_book_club,
+        api::v1::book_club::get_book_club,
+        api::v1::book_club::update_book_club,
+        api::v1::book_club::get_book_club_members,
+        api::v1::book_club::respond_to_book_club_invitation,
+        api::v1::book_club::create_book_club_member_handler,
+        api::v1::book_club::get_book_club_member,
+        api::v1::book_club::update_book_club_member,
+        api::v1::book_club::create_book_club_schedule,
+        api::v1::book_club::get_book_club_schedule,
+        api::v1::book_club::get_book_club_current_book,
         // TODO: epub here
         api::v1::filesystem::list_directory,
         api::v1::job::get_jobs,
+        api::v1::job::get_job_queue,
+        api::v1::job::get_job_wait_times,
         api::v1::job::delete_jobs,
+        api::v1::job::get_job_by_id,
         api::v1::job::delete_job_by_id,
         api::v1::job::cancel_job_by_id,
+        api::v1::job::kill_job_by_id,
         api::v1::job::get_scheduler_config,
         api::v1::job::update_scheduler_config,
+        api::v1::job::get_paused_job_types,
+        api::v1::job::get_job_manager_config,
+        api::v1::job::update_job_limits,
+        api::v1::job::update_paused_job_type,
+        api::v1::job::update_queue_paused,
         api::v1::library::get_libraries,
         api::v1::library::get_libraries_stats,
         api::v1::library::get_library_by_id,
         api::v1::library::get_library_series,
         api::v1::library::get_library_thumbnail_handler,
+        api::v1::library::patch_library_thumbnail,
         api::v1::library::delete_library_thumbnails,
         api::v1::library::generate_library_thumbnails,
         api::v1::library::scan_library,
@@ -66,16 +118,22 @@ use super::api::{
         api::v1::media::get_duplicate_media,
         api::v1::media::get_in_progress_media,
         api::v1::media::get_recently_added_media,
+        api::v1::media::get_media_by_path,
         api::v1::media::get_media_by_id,
         api::v1::media::get_media_file,
         api::v1::media::convert_media,
         api::v1::media::get_media_page,
         api::v1::media::get_media_thumbnail_handler,
+        api::v1::media::patch_media_thumbnail,
+        api::v1::media::replace_media_thumbnail,
         api::v1::media::update_media_progress,
         api::v1::media::get_media_progress,
         api::v1::media::delete_media_progress,
         api::v1::media::get_is_media_completed,
         api::v1::media::put_media_complete_status,
+        api::v1::log::get_logs,
+        api::v1::log::get_logfile_info,
+        api::v1::log::clear_logs,
         api::v1::metadata::get_metadata_overview,
         api::v1::metadata::get_genres_handler,
         api::v1::metadata::get_writers_handler,
@@ -102,8 +160,20 @@ use super::api::{
         api::v1::series::get_series_by_id,
         api::v1::series::get_recently_added_series_handler,
         api::v1::series::get_series_thumbnail_handler,
+        api::v1::series::patch_series_thumbnail,
+        api::v1::series::replace_series_thumbnail,
         api::v1::series::get_series_media,
         api::v1::series::get_series_is_complete,
+        api::v1::server::get_migrations,
+        api::v1::server::export_bundle,
+        api::v1::server::import_bundle,
+        api::v1::server::preview_telemetry,
+        api::v1::server::update_maintenance_mode,
+        api::v1::server::run_database_maintenance,
+        api::v1::server::get_support_bundle,
+        api::v1::server::get_warm_up_status,
+        api::v1::setup::get_setup_status,
+        api::v1::setup::complete_setup_handler,
         api::v1::smart_list::get_smart_lists,
         api::v1::smart_list::create_smart_list,
         api::v1::smart_list::get_smart_list_by_id,
@@ -127,9 +197,16 @@ use super::api::{
         api::v1::user::get_user_by_id,
         api::v1::user::get_user_login_activity_by_id,
         api::v1::user::update_user_handler,
+        api::v1::user::bulk_update_users,
+        api::v1::user::get_current_user_reading_stats,
         api::v1::user::get_user_preferences,
         api::v1::user::update_user_preferences,
-        api::v1::user::update_user_lock_status
+        api::v1::user::update_user_lock_status,
+        api::v1::user::update_current_user,
+        api::v1::user::update_current_user_preferences,
+        api::v1::user::delete_user_sessions,
+        api::v1::user::get_user_avatar,
+        api::v1::user::upload_user_avatar
     ),
     components(
         schemas(
@@ -147,7 +224,25 @@ use super::api::{
             CreateOrUpdateSmartListView, SmartListItemGrouping, SmartFilter, FilterJoin, EntityVisibility,
             SmartListViewConfig, SmartListTableColumnSelection, SmartListTableSortingState,
             MediaSmartFilter, MediaMetadataSmartFilter, SeriesSmartFilter, SeriesMetadataSmartFilter,
-            LibrarySmartFilter, Notifier, CreateOrUpdateNotifier, PatchNotifier
+            LibrarySmartFilter, Notifier, CreateOrUpdateNotifier, PatchNotifier, MigrationStatus,
+            AppliedMigration, PendingMigration, MigrationRisk, MigrationState, ServerBundle,
+            BundledUser, BundledLibrary, BundledNotifier, BundledScanSchedule, BundledReadProgress,
+            EncryptedSecrets, BundleImportOptions, ConflictResolution, ImportPlan, PlannedChange,
+            PlannedAction, BundleItemKind, ImportBundle, AgeRestriction, NotifierType,
+            TelemetryPreview, TelemetryReport, JobDurationPercentiles, JobTypeDurations, MaintenanceMode,
+            MaintenanceStatus, UpdateMaintenanceMode, CodedErrorBody,
+            ReverifyArgs, SessionElevationStatus,
+            RunDatabaseMaintenance, DatabaseMaintenanceStage, UpdatePausedJobType,
+            UpdateQueuePaused, JobManagerConfig, JobLimits, UpdateJobLimits, JobQueueSnapshot, JobQueueStatus, QueuedJob, WaitExplanation, WaitReason,
+            QueueCompletionEstimate,
+            JobWaitTimes, BookClub,
+            BookClubMember, BookClubMemberRole, BookClubMemberRoleSpec, BookClubSchedule,
+            BookClubBook, BookClubInvitation, CreateBookClub, UpdateBookClub,
+            CreateBookClubMember, BookClubInvitationAnswer, UpdateBookClubMember,
+            CreateBookClubSchedule, CreateBookClubScheduleBook, LogMetadata, SetupStatus,
+            SetupLibrary, CompleteSetup, SetupResult, WarmUpStatus,
+            LibraryAvailability, LibraryAvailabilityStatus, BulkUpdateUsers, BulkUserChanges,
+            ReadingStats, ReadingStatsQuery
         )
     ),
     tags(
@@ -160,6 +255,8 @@ use super::api::{
         (name = "library", description = "Library API"),
         (name = "media", description = "Media API"),
         (name = "series", description = "Series API"),
+        (name = "server", description = "Server API"),
+        (name = "setup", description = "Setup API"),
         (name = "tag", description = "Tag API"),
         (name = "reading-list", description = "Reading List API"),
         (name = "user", description = "User API"),
@@ -177,3 +274,97 @@ pub(crate) fn mount(app_state: AppState) -> Router<AppState> {
 pub(crate) fn swagger_ui() -> SwaggerUi {
 	SwaggerUi::new("/swagger-ui").url("/api-doc/openapi.json", ApiDoc::openapi())
 }
+
+#[cfg(test)]
+mod tests {
+	use utoipa::openapi::PathItemType;
+
+	use super::*;
+
+	/// The sources of every router module with handlers documented by `#[utoipa::path]`
+	const DOCUMENTED_ROUTERS: &[(&str, &str)] = &[
+		("auth", include_str!("api/v1/auth.rs")),
+		("book_club", include_str!("api/v1/book_club.rs")),
+		("filesystem", include_str!("api/v1/filesystem.rs")),
+		("job", include_str!("api/v1/job.rs")),
+		("library", include_str!("api/v1/library.rs")),
+		("log", include_str!("api/v1/log.rs")),
+		("media", include_str!("api/v1/media.rs")),
+		("metadata", include_str!("api/v1/metadata.rs")),
+		("mod", include_str!("api/v1/mod.rs")),
+		("notifier", include_str!("api/v1/notifier.rs")),
+		("reading_list", include_str!("api/v1/reading_list.rs")),
+		("series", include_str!("api/v1/series.rs")),
+		("server", include_str!("api/v1/server.rs")),
+		("setup", include_str!("api/v1/setup.rs")),
+		("smart_list", include_str!("api/v1/smart_list.rs")),
+		("tag", include_str!("api/v1/tag.rs")),
+		("user", include_str!("api/v1/user.rs")),
+	];
+
+	/// Returns the (method, path) of every `#[utoipa::path]` in the source
+	fn documented_operations(source: &str) -> Vec<(String, String)> {
+		let mut lines = source.lines().map(str::trim);
+		let mut operations = vec![];
+
+		while lines.any(|line| line == "#[utoipa::path(") {
+			let method = lines.next().unwrap_or_default().trim_end_matches(',');
+			let path = lines
+				.find_map(|line| line.strip_prefix("path = \""))
+				.and_then(|line| line.split('"').next())
+				.unwrap_or_default();
+			operations.push((method.to_string(), path.to_string()));
+		}
+
+		operations
+	}
+
+	fn path_item_type(method: &str) -> Option<PathItemType> {
+		match method {
+			"get" => Some(PathItemType::Get),
+			"post" => Some(PathItemType::Post),
+			"put" => Some(PathItemType::Put),
+			"patch" => Some(PathItemType::Patch),
+			"delete" => Some(PathItemType::Delete),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn test_documented_handlers_are_registered() {
+		let doc = ApiDoc::openapi();
+
+		let missing = DOCUMENTED_ROUTERS
+			.iter()
+			.flat_map(|(module, source)| {
+				documented_operations(source)
+					.into_iter()
+					.map(move |(method, path)| (*module, method, path))
+			})
+			.filter(|(_, method, path)| {
+				let registered = doc.paths.paths.get(path).zip(path_item_type(method));
+				!registered
+					.is_some_and(|(item, method)| item.operations.contains_key(&method))
+			})
+			.collect::<Vec<_>>();
+
+		assert!(
+			missing.is_empty(),
+			"Handlers are documented but missing from ApiDoc: {:?}",
+			missing
+		);
+	}
+
+	#[test]
+	fn test_doc_is_deterministic() {
+		let first = ApiDoc::openapi().to_json().unwrap();
+		let second = ApiDoc::openapi().to_json().unwrap();
+		assert_eq!(first, second);
+
+		let doc = ApiDoc::openapi();
+		let paths = doc.paths.paths.keys().collect::<Vec<_>>();
+		let mut sorted_paths = paths.clone();
+		sorted_paths.sort();
+		assert_eq!(paths, sorted_paths);
+	}
+}
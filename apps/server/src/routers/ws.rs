@@ -10,9 +10,13 @@ use axum::{
 	Router,
 };
 use futures_util::{sink::SinkExt, stream::StreamExt};
-use stump_core::Ctx;
+use stump_core::{db::entity::User, Ctx};
+use tower_sessions::Session;
 
-use crate::config::state::AppState;
+use crate::{
+	config::state::AppState,
+	utils::{can_receive_event, get_session_user},
+};
 
 // TODO: do I need auth middleware here? I think so, but I think the ws:// is
 // throwing if off and making it not think there is a session when there is.
@@ -24,16 +28,23 @@ pub(crate) fn mount() -> Router<AppState> {
 async fn ws_handler(
 	ws: WebSocketUpgrade,
 	State(ctx): State<AppState>,
+	session: Session,
 ) -> impl IntoResponse {
-	ws.on_upgrade(|socket| handle_socket(socket, ctx))
+	// Without a session, only events which don't require a permission are sent
+	let user = get_session_user(&session).ok();
+	ws.on_upgrade(|socket| handle_socket(socket, ctx, user))
 }
 
-async fn handle_socket(socket: WebSocket, ctx: Arc<Ctx>) {
+async fn handle_socket(socket: WebSocket, ctx: Arc<Ctx>, user: Option<User>) {
 	let (mut sender, mut _receiver) = socket.split();
 
 	let mut rx = ctx.get_client_receiver();
 
 	while let Ok(core_event) = rx.recv().await {
+		if !can_receive_event(user.as_ref(), &core_event) {
+			continue;
+		}
+
 		if let Ok(payload) = serde_json::to_string(&core_event) {
 			// TODO: Pipe errors give me a twitchy eye
 			let _ = sender.send(Message::Text(payload)).await;
@@ -0,0 +1,60 @@
+use std::{sync::Arc, time::Duration};
+
+use hyper::header::USER_AGENT;
+use stump_core::{
+	telemetry::{collect_telemetry_report, is_telemetry_enabled},
+	CoreError, Ctx,
+};
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::utils::outbound_client;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TelemetryError {
+	#[error("Failed to collect the telemetry report: {0}")]
+	CollectError(#[from] CoreError),
+	#[error("Failed to send the telemetry report: {0}")]
+	RequestError(#[from] reqwest::Error),
+}
+
+/// How often a telemetry report is sent, when telemetry is enabled
+pub(crate) const TELEMETRY_SEND_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Periodically sends the telemetry report to the configured telemetry URL, through the
+/// shared outbound client. Nothing is collected or sent unless telemetry is enabled (see
+/// [is_telemetry_enabled]) and a URL is configured, which is checked before every send.
+pub(crate) async fn continuously_send_telemetry(ctx: Arc<Ctx>, period: Duration) {
+	// The first report is sent after a full period, rather than on every startup
+	let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+	interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+	loop {
+		interval.tick().await;
+		if let Err(error) = send_telemetry(&ctx).await {
+			tracing::error!(?error, "Failed to send telemetry report");
+		}
+	}
+}
+
+async fn send_telemetry(ctx: &Ctx) -> Result<(), TelemetryError> {
+	if !is_telemetry_enabled(&ctx.config) {
+		tracing::trace!("Telemetry is disabled, skipping report");
+		return Ok(());
+	}
+
+	let Some(telemetry_url) = ctx.config.telemetry_url.as_deref() else {
+		tracing::trace!("No telemetry URL is configured, skipping report");
+		return Ok(());
+	};
+
+	let report = collect_telemetry_report(&ctx.db, &ctx.config).await?;
+	outbound_client(&ctx.config)?
+		.post(telemetry_url)
+		.header(USER_AGENT, "stumpapp/stump")
+		.json(&report)
+		.send()
+		.await?
+		.error_for_status()?;
+	tracing::debug!(?report, "Sent telemetry report");
+
+	Ok(())
+}
@@ -1,11 +1,25 @@
-use stump_core::db::entity::{User, UserPermission};
+use std::{
+	collections::hash_map::DefaultHasher,
+	hash::{Hash, Hasher},
+};
+
+use prisma_client_rust::chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use stump_core::{
+	db::entity::{User, UserPermission},
+	event::CoreEvent,
+	prisma::{user, PrismaClient},
+};
 use tower_sessions::Session;
 
 use crate::{
-	config::session::SESSION_USER_KEY,
+	config::session::{SESSION_ELEVATION_KEY, SESSION_USER_KEY},
 	errors::{ApiError, ApiResult, AuthError},
 };
 
+/// How long (in minutes) a session remains elevated after the user re-enters their password
+pub const SESSION_ELEVATION_TTL_MINUTES: i64 = 10;
+
 #[derive(Debug)]
 pub struct DecodedCredentials {
 	pub username: String,
@@ -62,6 +76,17 @@ fn user_has_permission(user: &User, permission: UserPermission) -> bool {
 			.any(|p| p == &permission || p.associated().contains(&permission))
 }
 
+/// Returns whether the event may be delivered to the (possibly anonymous) user. Events which
+/// don't require a permission are delivered to everyone.
+pub fn can_receive_event(user: Option<&User>, event: &CoreEvent) -> bool {
+	match event.required_permission() {
+		Some(permission) => {
+			user.is_some_and(|user| user_has_permission(user, permission))
+		},
+		None => true,
+	}
+}
+
 /// Enforce that the user has the given permission. If the user does not have the permission, an
 /// `ApiError::Forbidden` is returned.
 fn enforce_permission(user: &User, permission: UserPermission) -> ApiResult<()> {
@@ -130,6 +155,72 @@ pub fn get_user_and_enforce_permission(
 	Ok(user)
 }
 
+/// The elevation stamp stored in a session once the user has re-entered their password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionElevation {
+	/// The unix timestamp (in seconds) of when the elevation expires
+	pub expires_at: i64,
+	/// A fingerprint of the user's password hash when the session was elevated. Changing
+	/// the password changes the hash, which revokes the elevation of every session.
+	pub password_fingerprint: u64,
+}
+
+impl SessionElevation {
+	pub fn new(hashed_password: &str) -> Self {
+		let expires_at = Utc::now() + Duration::minutes(SESSION_ELEVATION_TTL_MINUTES);
+		Self {
+			expires_at: expires_at.timestamp(),
+			password_fingerprint: password_fingerprint(hashed_password),
+		}
+	}
+
+	/// Returns whether the elevation is still valid at `now` (a unix timestamp, in seconds)
+	/// for a user with the given password hash.
+	pub fn is_valid(&self, hashed_password: &str, now: i64) -> bool {
+		now < self.expires_at
+			&& self.password_fingerprint == password_fingerprint(hashed_password)
+	}
+}
+
+fn password_fingerprint(hashed_password: &str) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	hashed_password.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// Enforce that the session was recently elevated, i.e. that the user re-entered their
+/// password within the last [SESSION_ELEVATION_TTL_MINUTES] minutes and hasn't changed it
+/// since. If not, an `ApiError::ElevationRequired` is returned. The user is returned if the
+/// session is elevated.
+pub async fn enforce_elevated_session(
+	session: &Session,
+	client: &PrismaClient,
+) -> ApiResult<User> {
+	let user = get_session_user(session)?;
+
+	let Some(elevation) = session.get::<SessionElevation>(SESSION_ELEVATION_KEY)? else {
+		return Err(ApiError::ElevationRequired(String::from(
+			"You must re-enter your password to perform this action",
+		)));
+	};
+
+	let hashed_password = client
+		.user()
+		.find_unique(user::id::equals(user.id.clone()))
+		.exec()
+		.await?
+		.map(|user| user.hashed_password)
+		.ok_or(ApiError::Unauthorized)?;
+
+	if elevation.is_valid(&hashed_password, Utc::now().timestamp()) {
+		Ok(user)
+	} else {
+		Err(ApiError::ElevationRequired(String::from(
+			"Your re-authentication has expired. Please re-enter your password",
+		)))
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -154,4 +245,50 @@ mod tests {
 
 		assert!(user_has_all_permissions(&user, &expected_can_do));
 	}
+
+	#[test]
+	fn test_can_receive_event() {
+		let notifier_event = CoreEvent::NotifierDeleted { id: 1 };
+		let job_event = CoreEvent::JobComplete {
+			job_id: String::from("job"),
+			sequence: 1,
+		};
+
+		let manager = User {
+			permissions: vec![UserPermission::ManageNotifier],
+			..Default::default()
+		};
+		assert!(can_receive_event(Some(&manager), &notifier_event));
+
+		let reader = User {
+			permissions: vec![UserPermission::ReadNotifier],
+			..Default::default()
+		};
+		assert!(!can_receive_event(Some(&reader), &notifier_event));
+		assert!(can_receive_event(Some(&reader), &job_event));
+
+		let owner = User {
+			is_server_owner: true,
+			..Default::default()
+		};
+		assert!(can_receive_event(Some(&owner), &notifier_event));
+
+		assert!(!can_receive_event(None, &notifier_event));
+		assert!(can_receive_event(None, &job_event));
+	}
+
+	#[test]
+	fn test_session_elevation_validity() {
+		let elevation = SessionElevation::new("$2b$12$original");
+		let now = Utc::now().timestamp();
+
+		assert!(elevation.is_valid("$2b$12$original", now));
+		// Stale once the TTL has passed
+		assert!(!elevation.is_valid(
+			"$2b$12$original",
+			now + SESSION_ELEVATION_TTL_MINUTES * 60 + 1
+		));
+		// Revoked once the password changes
+		assert!(!elevation.is_valid("$2b$12$changed", now));
+	}
 }
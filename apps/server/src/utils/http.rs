@@ -28,17 +28,31 @@ pub(crate) fn unexpected_error<E: std::error::Error>(err: E) -> impl IntoRespons
 pub struct ImageResponse {
 	pub content_type: ContentType,
 	pub data: Vec<u8>,
+	/// Whether the image will never change, and so may be cached indefinitely
+	pub immutable: bool,
 }
 
 impl ImageResponse {
 	pub fn new(content_type: ContentType, data: Vec<u8>) -> Self {
-		Self { content_type, data }
+		Self {
+			content_type,
+			data,
+			immutable: false,
+		}
+	}
+
+	/// Marks the image as immutable, so that clients may cache it for as long as they like
+	pub fn immutable(self) -> Self {
+		Self {
+			immutable: true,
+			..self
+		}
 	}
 }
 
 impl From<(ContentType, Vec<u8>)> for ImageResponse {
 	fn from((content_type, data): (ContentType, Vec<u8>)) -> Self {
-		Self { content_type, data }
+		Self::new(content_type, data)
 	}
 }
 
@@ -55,11 +69,16 @@ impl IntoResponse for ImageResponse {
 				},
 			),
 		);
-		base_response.headers_mut().insert(
-			header::CACHE_CONTROL,
+		let cache_control = if self.immutable {
+			// 1 year
+			HeaderValue::from_static("private,max-age=31536000,immutable")
+		} else {
 			// 10 minutes
-			HeaderValue::from_static("private,max-age=600"),
-		);
+			HeaderValue::from_static("private,max-age=600")
+		};
+		base_response
+			.headers_mut()
+			.insert(header::CACHE_CONTROL, cache_control);
 
 		base_response
 	}
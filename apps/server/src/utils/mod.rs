@@ -1,10 +1,12 @@
 mod auth;
 mod date;
 pub mod http;
+mod outbound;
 mod signal;
 mod upload;
 
 pub(crate) use auth::*;
 pub(crate) use date::*;
+pub(crate) use outbound::*;
 pub(crate) use signal::*;
 pub(crate) use upload::*;
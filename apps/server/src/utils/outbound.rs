@@ -0,0 +1,45 @@
+use std::sync::OnceLock;
+
+use reqwest::{Client, Proxy};
+use stump_core::config::StumpConfig;
+
+static OUTBOUND_CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Returns the HTTP client shared by everything which makes requests to external services
+/// (e.g. update checks and telemetry), so that the configured outbound proxy is always
+/// respected. The client is built on first use.
+pub(crate) fn outbound_client(config: &StumpConfig) -> reqwest::Result<Client> {
+	if let Some(client) = OUTBOUND_CLIENT.get() {
+		return Ok(client.clone());
+	}
+
+	let client = build_outbound_client(config)?;
+	Ok(OUTBOUND_CLIENT.get_or_init(|| client).clone())
+}
+
+fn build_outbound_client(config: &StumpConfig) -> reqwest::Result<Client> {
+	let mut builder = Client::builder();
+	if let Some(proxy) = &config.outbound_proxy {
+		builder = builder.proxy(Proxy::all(proxy)?);
+	}
+	builder.build()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_invalid_proxy_is_rejected() {
+		let config = StumpConfig {
+			outbound_proxy: Some(String::from("not a proxy")),
+			..StumpConfig::debug()
+		};
+		assert!(build_outbound_client(&config).is_err());
+	}
+
+	#[test]
+	fn test_client_without_proxy() {
+		assert!(build_outbound_client(&StumpConfig::debug()).is_ok());
+	}
+}
@@ -1,5 +1,9 @@
 use chrono::prelude::{DateTime, Utc};
-use std::{process::Command, time::SystemTime};
+use std::{fs, path::Path, process::Command, time::SystemTime};
+
+/// The annotation a migration.sql file must contain for the migration to be treated
+/// as destructive (i.e. it drops or rewrites data that can't be recovered).
+const DESTRUCTIVE_ANNOTATION: &str = "-- stump:destructive";
 
 fn main() {
 	let system_time = SystemTime::now();
@@ -21,4 +25,41 @@ fn main() {
 	};
 
 	println!("cargo:rustc-env=GIT_REV={}", rev);
+
+	write_bundled_migrations();
+}
+
+/// Writes the list of migrations bundled with this build, alongside whether each is
+/// annotated as destructive, so the core can determine which are still pending.
+fn write_bundled_migrations() {
+	let manifest_dir =
+		std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is not set!");
+	let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is not set!");
+
+	let mut migrations = fs::read_dir(Path::new(&manifest_dir).join("prisma/migrations"))
+		.expect("Failed to read prisma migrations directory!")
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.filter(|path| path.is_dir())
+		.filter_map(|path| {
+			let name = path.file_name()?.to_str()?.to_string();
+			let sql = fs::read_to_string(path.join("migration.sql")).ok()?;
+			Some((name, sql.contains(DESTRUCTIVE_ANNOTATION)))
+		})
+		.collect::<Vec<(String, bool)>>();
+	migrations.sort();
+
+	let entries = migrations
+		.into_iter()
+		.map(|(name, destructive)| format!("\t(\"{}\", {}),\n", name, destructive))
+		.collect::<String>();
+
+	fs::write(
+		Path::new(&out_dir).join("bundled_migrations.rs"),
+		format!(
+			"/// The migrations bundled with this build, as (name, is_destructive) pairs\npub(crate) const BUNDLED_MIGRATIONS: &[(&str, bool)] = &[\n{}];\n",
+			entries
+		),
+	)
+	.expect("Failed to write bundled migrations!");
 }
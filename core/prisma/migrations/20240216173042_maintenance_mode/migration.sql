@@ -0,0 +1,4 @@
+-- AlterTable
+ALTER TABLE "server_config" ADD COLUMN "maintenance_enabled_at" DATETIME;
+ALTER TABLE "server_config" ADD COLUMN "maintenance_expires_at" DATETIME;
+ALTER TABLE "server_config" ADD COLUMN "maintenance_message" TEXT;
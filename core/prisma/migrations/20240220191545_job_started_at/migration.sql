@@ -0,0 +1,2 @@
+-- AlterTable
+ALTER TABLE "jobs" ADD COLUMN "started_at" DATETIME;
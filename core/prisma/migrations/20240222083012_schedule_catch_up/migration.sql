@@ -0,0 +1,4 @@
+-- AlterTable
+ALTER TABLE "job_schedule_configs" ADD COLUMN "last_run_at" DATETIME;
+ALTER TABLE "job_schedule_configs" ADD COLUMN "catch_up_policy" TEXT NOT NULL DEFAULT 'RUN_ONCE';
+ALTER TABLE "job_schedule_configs" ADD COLUMN "missed_runs" INTEGER NOT NULL DEFAULT 0;
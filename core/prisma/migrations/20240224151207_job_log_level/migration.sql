@@ -0,0 +1,3 @@
+-- AlterTable
+ALTER TABLE "jobs" ADD COLUMN "log_level" TEXT NOT NULL DEFAULT 'INFO';
+ALTER TABLE "job_schedule_configs" ADD COLUMN "log_level" TEXT;
@@ -0,0 +1,2 @@
+-- AlterTable
+ALTER TABLE "libraries" ADD COLUMN "archived" BOOLEAN NOT NULL DEFAULT false;
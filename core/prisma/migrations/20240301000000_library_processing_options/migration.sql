@@ -0,0 +1,3 @@
+-- AlterTable
+ALTER TABLE "library_options" ADD COLUMN "extract_metadata" BOOLEAN NOT NULL DEFAULT true;
+ALTER TABLE "library_options" ADD COLUMN "generate_file_hashes" BOOLEAN NOT NULL DEFAULT true;
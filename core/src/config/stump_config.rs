@@ -4,7 +4,10 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
-use crate::error::{CoreError, CoreResult};
+use crate::{
+	db::entity::LogLevel,
+	error::{CoreError, CoreResult},
+};
 
 pub mod env_keys {
 	pub const CONFIG_DIR_KEY: &str = "STUMP_CONFIG_DIR";
@@ -20,6 +23,23 @@ pub mod env_keys {
 	pub const HASH_COST_KEY: &str = "HASH_COST";
 	pub const SESSION_TTL_KEY: &str = "SESSION_TTL";
 	pub const SESSION_EXPIRY_INTERVAL_KEY: &str = "SESSION_EXPIRY_CLEANUP_INTERVAL";
+	pub const APPLY_DESTRUCTIVE_MIGRATIONS_KEY: &str =
+		"STUMP_APPLY_DESTRUCTIVE_MIGRATIONS";
+	pub const ENABLE_TELEMETRY_KEY: &str = "STUMP_ENABLE_TELEMETRY";
+	pub const DISABLE_TELEMETRY_KEY: &str = "STUMP_DISABLE_TELEMETRY";
+	pub const HOLD_JOBS_DURING_MAINTENANCE_KEY: &str =
+		"STUMP_HOLD_JOBS_DURING_MAINTENANCE";
+	pub const JOB_YIELD_BUDGET_KEY: &str = "STUMP_JOB_YIELD_BUDGET_MS";
+	pub const PAGE_RESIZE_WIDTHS_KEY: &str = "STUMP_PAGE_RESIZE_WIDTHS";
+	pub const JOB_MIN_FREE_DISK_KEY: &str = "STUMP_JOB_MIN_FREE_DISK_MB";
+	pub const JOB_MAX_RSS_KEY: &str = "STUMP_JOB_MAX_RSS_MB";
+	pub const JOB_MAX_LOAD_AVERAGE_KEY: &str = "STUMP_JOB_MAX_LOAD_AVERAGE";
+	pub const TEMP_DIR_MAX_KEY: &str = "STUMP_TEMP_DIR_MAX_MB";
+	pub const JOB_LOG_LEVEL_KEY: &str = "STUMP_JOB_LOG_LEVEL";
+	pub const JOB_DISK_PAUSE_THRESHOLD_KEY: &str = "STUMP_JOB_DISK_PAUSE_THRESHOLD_MB";
+	pub const LIBRARY_WARM_UP_TIMEOUT_KEY: &str = "STUMP_LIBRARY_WARM_UP_TIMEOUT_SECS";
+	pub const OUTBOUND_PROXY_KEY: &str = "STUMP_OUTBOUND_PROXY";
+	pub const TELEMETRY_URL_KEY: &str = "STUMP_TELEMETRY_URL";
 }
 use env_keys::*;
 
@@ -27,6 +47,12 @@ pub mod defaults {
 	pub const DEFAULT_PASSWORD_HASH_COST: u32 = 12;
 	pub const DEFAULT_SESSION_TTL: i64 = 3600 * 24 * 3; // 3 days
 	pub const DEFAULT_SESSION_EXPIRY_CLEANUP_INTERVAL: u64 = 60 * 60 * 24; // 24 hours
+	pub const DEFAULT_JOB_YIELD_BUDGET_MS: u64 = 50;
+	pub const DEFAULT_PAGE_RESIZE_WIDTHS: [u32; 4] = [480, 720, 1080, 1440];
+	pub const DEFAULT_JOB_MIN_FREE_DISK_MB: u64 = 512;
+	pub const DEFAULT_TEMP_DIR_MAX_MB: u64 = 4096;
+	pub const DEFAULT_JOB_DISK_PAUSE_THRESHOLD_MB: u64 = 1024;
+	pub const DEFAULT_LIBRARY_WARM_UP_TIMEOUT_SECS: u64 = 120;
 }
 use defaults::*;
 
@@ -82,6 +108,34 @@ pub struct StumpConfig {
 	pub session_ttl: i64,
 	/// The interval at which automatic deleted session cleanup is performed.
 	pub expired_session_cleanup_interval: u64,
+	/// Whether migrations annotated as destructive may be applied automatically on startup.
+	pub apply_destructive_migrations: bool,
+	/// Whether anonymous usage telemetry has been opted into (default: false).
+	pub enable_telemetry: bool,
+	/// Whether jobs enqueued during maintenance mode are held until it ends, rather than rejected (default: false).
+	pub hold_jobs_during_maintenance: bool,
+	/// The time in milliseconds a job may run before yielding back to the async runtime (default: 50).
+	pub job_yield_budget_ms: u64,
+	/// The widths (in pixels) which pages may be resized to. Requested widths are clamped to these.
+	pub page_resize_widths: Vec<u32>,
+	/// The free disk space (in MB) below which new jobs are deferred, or 0 to disable (default: 512).
+	pub job_min_free_disk_mb: u64,
+	/// The memory usage (in MB) of the server above which new jobs are deferred, or 0 to disable (default: 0).
+	pub job_max_rss_mb: u64,
+	/// The 1 minute load average above which new jobs are deferred, or 0 to disable (default: 0).
+	pub job_max_load_average: f64,
+	/// The maximum total size (in MB) of the temporary files directory, or 0 to disable the limit (default: 4096).
+	pub temp_dir_max_mb: u64,
+	/// The level of logs persisted for jobs which don't specify one, one of ERROR, WARN, INFO or DEBUG (default: INFO).
+	pub job_log_level: LogLevel,
+	/// The free disk space (in MB) below which disk-writing job types are paused until space recovers, or 0 to disable (default: 1024).
+	pub job_disk_pause_threshold_mb: u64,
+	/// How long (in seconds) to wait on startup for library directories to become available before flagging them as unavailable, or 0 to disable (default: 120).
+	pub library_warm_up_timeout_secs: u64,
+	/// An optional proxy (e.g. http://proxy.local:3128) through which all outbound requests are sent.
+	pub outbound_proxy: Option<String>,
+	/// The URL to which telemetry reports are sent, if telemetry is enabled. Nothing is sent without one.
+	pub telemetry_url: Option<String>,
 }
 
 impl StumpConfig {
@@ -101,6 +155,20 @@ impl StumpConfig {
 			password_hash_cost: DEFAULT_PASSWORD_HASH_COST,
 			session_ttl: DEFAULT_SESSION_TTL,
 			expired_session_cleanup_interval: DEFAULT_SESSION_EXPIRY_CLEANUP_INTERVAL,
+			apply_destructive_migrations: false,
+			enable_telemetry: false,
+			hold_jobs_during_maintenance: false,
+			job_yield_budget_ms: DEFAULT_JOB_YIELD_BUDGET_MS,
+			page_resize_widths: DEFAULT_PAGE_RESIZE_WIDTHS.to_vec(),
+			job_min_free_disk_mb: DEFAULT_JOB_MIN_FREE_DISK_MB,
+			job_max_rss_mb: 0,
+			job_max_load_average: 0.0,
+			temp_dir_max_mb: DEFAULT_TEMP_DIR_MAX_MB,
+			job_log_level: LogLevel::Info,
+			job_disk_pause_threshold_mb: DEFAULT_JOB_DISK_PAUSE_THRESHOLD_MB,
+			library_warm_up_timeout_secs: DEFAULT_LIBRARY_WARM_UP_TIMEOUT_SECS,
+			outbound_proxy: None,
+			telemetry_url: None,
 		}
 	}
 
@@ -121,6 +189,20 @@ impl StumpConfig {
 			password_hash_cost: DEFAULT_PASSWORD_HASH_COST,
 			session_ttl: DEFAULT_SESSION_TTL,
 			expired_session_cleanup_interval: DEFAULT_SESSION_EXPIRY_CLEANUP_INTERVAL,
+			apply_destructive_migrations: false,
+			enable_telemetry: false,
+			hold_jobs_during_maintenance: false,
+			job_yield_budget_ms: DEFAULT_JOB_YIELD_BUDGET_MS,
+			page_resize_widths: DEFAULT_PAGE_RESIZE_WIDTHS.to_vec(),
+			job_min_free_disk_mb: DEFAULT_JOB_MIN_FREE_DISK_MB,
+			job_max_rss_mb: 0,
+			job_max_load_average: 0.0,
+			temp_dir_max_mb: DEFAULT_TEMP_DIR_MAX_MB,
+			job_log_level: LogLevel::Info,
+			job_disk_pause_threshold_mb: DEFAULT_JOB_DISK_PAUSE_THRESHOLD_MB,
+			library_warm_up_timeout_secs: DEFAULT_LIBRARY_WARM_UP_TIMEOUT_SECS,
+			outbound_proxy: None,
+			telemetry_url: None,
 		}
 	}
 
@@ -236,6 +318,136 @@ impl StumpConfig {
 			}
 		}
 
+		if let Ok(apply_destructive) = env::var(APPLY_DESTRUCTIVE_MIGRATIONS_KEY) {
+			match apply_destructive.parse() {
+				Ok(val) => env_configs.apply_destructive_migrations = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_APPLY_DESTRUCTIVE_MIGRATIONS"
+				),
+			}
+		}
+
+		if let Ok(enable_telemetry) = env::var(ENABLE_TELEMETRY_KEY) {
+			match enable_telemetry.parse() {
+				Ok(val) => env_configs.enable_telemetry = Some(val),
+				Err(e) => {
+					tracing::error!(?e, "Failed to parse provided STUMP_ENABLE_TELEMETRY")
+				},
+			}
+		}
+
+		if let Ok(hold_jobs_during_maintenance) =
+			env::var(HOLD_JOBS_DURING_MAINTENANCE_KEY)
+		{
+			match hold_jobs_during_maintenance.parse() {
+				Ok(val) => env_configs.hold_jobs_during_maintenance = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_HOLD_JOBS_DURING_MAINTENANCE"
+				),
+			}
+		}
+
+		if let Ok(job_yield_budget_ms) = env::var(JOB_YIELD_BUDGET_KEY) {
+			match job_yield_budget_ms.parse() {
+				Ok(val) => env_configs.job_yield_budget_ms = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_JOB_YIELD_BUDGET_MS"
+				),
+			}
+		}
+
+		if let Ok(page_resize_widths) = env::var(PAGE_RESIZE_WIDTHS_KEY) {
+			match page_resize_widths
+				.split(',')
+				.map(|val| val.trim().parse::<u32>())
+				.collect::<Result<Vec<u32>, _>>()
+			{
+				Ok(val) => env_configs.page_resize_widths = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_PAGE_RESIZE_WIDTHS"
+				),
+			}
+		}
+
+		if let Ok(job_min_free_disk_mb) = env::var(JOB_MIN_FREE_DISK_KEY) {
+			match job_min_free_disk_mb.parse() {
+				Ok(val) => env_configs.job_min_free_disk_mb = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_JOB_MIN_FREE_DISK_MB"
+				),
+			}
+		}
+
+		if let Ok(job_max_rss_mb) = env::var(JOB_MAX_RSS_KEY) {
+			match job_max_rss_mb.parse() {
+				Ok(val) => env_configs.job_max_rss_mb = Some(val),
+				Err(e) => {
+					tracing::error!(?e, "Failed to parse provided STUMP_JOB_MAX_RSS_MB")
+				},
+			}
+		}
+
+		if let Ok(job_max_load_average) = env::var(JOB_MAX_LOAD_AVERAGE_KEY) {
+			match job_max_load_average.parse() {
+				Ok(val) => env_configs.job_max_load_average = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_JOB_MAX_LOAD_AVERAGE"
+				),
+			}
+		}
+
+		if let Ok(temp_dir_max_mb) = env::var(TEMP_DIR_MAX_KEY) {
+			match temp_dir_max_mb.parse() {
+				Ok(val) => env_configs.temp_dir_max_mb = Some(val),
+				Err(e) => {
+					tracing::error!(?e, "Failed to parse provided STUMP_TEMP_DIR_MAX_MB")
+				},
+			}
+		}
+
+		if let Ok(job_log_level) = env::var(JOB_LOG_LEVEL_KEY) {
+			match job_log_level.parse() {
+				Ok(val) => env_configs.job_log_level = Some(val),
+				Err(e) => {
+					tracing::error!(?e, "Failed to parse provided STUMP_JOB_LOG_LEVEL")
+				},
+			}
+		}
+
+		if let Ok(job_disk_pause_threshold_mb) = env::var(JOB_DISK_PAUSE_THRESHOLD_KEY) {
+			match job_disk_pause_threshold_mb.parse() {
+				Ok(val) => env_configs.job_disk_pause_threshold_mb = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_JOB_DISK_PAUSE_THRESHOLD_MB"
+				),
+			}
+		}
+
+		if let Ok(library_warm_up_timeout_secs) = env::var(LIBRARY_WARM_UP_TIMEOUT_KEY) {
+			match library_warm_up_timeout_secs.parse() {
+				Ok(val) => env_configs.library_warm_up_timeout_secs = Some(val),
+				Err(e) => tracing::error!(
+					?e,
+					"Failed to parse provided STUMP_LIBRARY_WARM_UP_TIMEOUT_SECS"
+				),
+			}
+		}
+
+		if let Ok(outbound_proxy) = env::var(OUTBOUND_PROXY_KEY) {
+			env_configs.outbound_proxy = Some(outbound_proxy);
+		}
+
+		if let Ok(telemetry_url) = env::var(TELEMETRY_URL_KEY) {
+			env_configs.telemetry_url = Some(telemetry_url);
+		}
+
 		env_configs.apply_to_config(&mut self);
 		Ok(self)
 	}
@@ -299,6 +511,63 @@ impl StumpConfig {
 		Ok(())
 	}
 
+	/// Checks the configuration for values which are likely to cause problems at runtime,
+	/// returning a description of each problem found. An empty list means the configuration
+	/// is valid.
+	pub fn validate(&self) -> Vec<String> {
+		let mut issues = vec![];
+
+		if !["debug", "release"].contains(&self.profile.as_str()) {
+			issues.push(format!(
+				"Invalid profile '{}', expected 'debug' or 'release'",
+				self.profile
+			));
+		}
+
+		if self.port == 0 {
+			issues.push(String::from("Port must be greater than 0"));
+		}
+
+		// See bcrypt::MIN_COST and bcrypt::MAX_COST
+		if !(4..=31).contains(&self.password_hash_cost) {
+			issues.push(format!(
+				"Password hash cost must be between 4 and 31, got {}",
+				self.password_hash_cost
+			));
+		}
+
+		if self.session_ttl <= 0 {
+			issues.push(String::from("Session TTL must be greater than 0"));
+		}
+
+		if self.expired_session_cleanup_interval == 0 {
+			issues.push(String::from(
+				"Expired session cleanup interval must be greater than 0",
+			));
+		}
+
+		if let Some(db_path) = &self.db_path {
+			if !PathBuf::from(db_path).is_dir() {
+				issues.push(format!("Database path {} is not a directory", db_path));
+			}
+		}
+
+		if let Some(pdfium_path) = &self.pdfium_path {
+			if !PathBuf::from(pdfium_path).exists() {
+				issues.push(format!("PDFium path {} does not exist", pdfium_path));
+			}
+		}
+
+		if !PathBuf::from(&self.client_dir).is_dir() {
+			issues.push(format!(
+				"Client directory {} does not exist",
+				self.client_dir
+			));
+		}
+
+		issues
+	}
+
 	/// Returns True if the configuration profile is "debug" and False otherwise.
 	pub fn is_debug(&self) -> bool {
 		self.profile.as_str() == "debug"
@@ -314,6 +583,11 @@ impl StumpConfig {
 		PathBuf::from(&self.config_dir).join("cache")
 	}
 
+	/// Returns a `PathBuf` to the Stump temporary files directory.
+	pub fn get_temp_dir(&self) -> PathBuf {
+		PathBuf::from(&self.config_dir).join("temp")
+	}
+
 	/// Returns a `PathBuf` to the Stump thumbnails directory.
 	pub fn get_thumbnails_dir(&self) -> PathBuf {
 		PathBuf::from(&self.config_dir).join("thumbnails")
@@ -344,6 +618,20 @@ pub struct PartialStumpConfig {
 	pub password_hash_cost: Option<u32>,
 	pub session_ttl: Option<i64>,
 	pub expired_session_cleanup_interval: Option<u64>,
+	pub apply_destructive_migrations: Option<bool>,
+	pub enable_telemetry: Option<bool>,
+	pub hold_jobs_during_maintenance: Option<bool>,
+	pub job_yield_budget_ms: Option<u64>,
+	pub page_resize_widths: Option<Vec<u32>>,
+	pub job_min_free_disk_mb: Option<u64>,
+	pub job_max_rss_mb: Option<u64>,
+	pub job_max_load_average: Option<f64>,
+	pub temp_dir_max_mb: Option<u64>,
+	pub job_log_level: Option<LogLevel>,
+	pub job_disk_pause_threshold_mb: Option<u64>,
+	pub library_warm_up_timeout_secs: Option<u64>,
+	pub outbound_proxy: Option<String>,
+	pub telemetry_url: Option<String>,
 }
 
 impl PartialStumpConfig {
@@ -361,6 +649,20 @@ impl PartialStumpConfig {
 			password_hash_cost: None,
 			session_ttl: None,
 			expired_session_cleanup_interval: None,
+			apply_destructive_migrations: None,
+			enable_telemetry: None,
+			hold_jobs_during_maintenance: None,
+			job_yield_budget_ms: None,
+			page_resize_widths: None,
+			job_min_free_disk_mb: None,
+			job_max_rss_mb: None,
+			job_max_load_average: None,
+			temp_dir_max_mb: None,
+			job_log_level: None,
+			job_disk_pause_threshold_mb: None,
+			library_warm_up_timeout_secs: None,
+			outbound_proxy: None,
+			telemetry_url: None,
 		}
 	}
 
@@ -418,6 +720,62 @@ impl PartialStumpConfig {
 		if let Some(cleanup_interval) = self.expired_session_cleanup_interval {
 			config.expired_session_cleanup_interval = cleanup_interval;
 		}
+		// Apply Destructive Migrations - Merge if not None
+		if let Some(apply_destructive) = self.apply_destructive_migrations {
+			config.apply_destructive_migrations = apply_destructive;
+		}
+		// Enable Telemetry - Merge if not None
+		if let Some(enable_telemetry) = self.enable_telemetry {
+			config.enable_telemetry = enable_telemetry;
+		}
+		// Hold Jobs During Maintenance - Merge if not None
+		if let Some(hold_jobs_during_maintenance) = self.hold_jobs_during_maintenance {
+			config.hold_jobs_during_maintenance = hold_jobs_during_maintenance;
+		}
+		// Job Yield Budget - Merge if not None
+		if let Some(job_yield_budget_ms) = self.job_yield_budget_ms {
+			config.job_yield_budget_ms = job_yield_budget_ms;
+		}
+		// Page Resize Widths - Merge if not None
+		if let Some(page_resize_widths) = self.page_resize_widths {
+			config.page_resize_widths = page_resize_widths;
+		}
+		// Job Min Free Disk - Merge if not None
+		if let Some(job_min_free_disk_mb) = self.job_min_free_disk_mb {
+			config.job_min_free_disk_mb = job_min_free_disk_mb;
+		}
+		// Job Max RSS - Merge if not None
+		if let Some(job_max_rss_mb) = self.job_max_rss_mb {
+			config.job_max_rss_mb = job_max_rss_mb;
+		}
+		// Job Max Load Average - Merge if not None
+		if let Some(job_max_load_average) = self.job_max_load_average {
+			config.job_max_load_average = job_max_load_average;
+		}
+		// Temp Dir Max MB - Merge if not None
+		if let Some(temp_dir_max_mb) = self.temp_dir_max_mb {
+			config.temp_dir_max_mb = temp_dir_max_mb;
+		}
+		// Job Log Level - Merge if not None
+		if let Some(job_log_level) = self.job_log_level {
+			config.job_log_level = job_log_level;
+		}
+		// Job Disk Pause Threshold - Merge if not None
+		if let Some(job_disk_pause_threshold_mb) = self.job_disk_pause_threshold_mb {
+			config.job_disk_pause_threshold_mb = job_disk_pause_threshold_mb;
+		}
+		// Library Warm Up Timeout - Merge if not None
+		if let Some(library_warm_up_timeout_secs) = self.library_warm_up_timeout_secs {
+			config.library_warm_up_timeout_secs = library_warm_up_timeout_secs;
+		}
+		// Outbound Proxy - Merge if not None
+		if let Some(outbound_proxy) = self.outbound_proxy {
+			config.outbound_proxy = Some(outbound_proxy);
+		}
+		// Telemetry URL - Merge if not None
+		if let Some(telemetry_url) = self.telemetry_url {
+			config.telemetry_url = Some(telemetry_url);
+		}
 	}
 }
 
@@ -451,6 +809,20 @@ mod tests {
 			password_hash_cost: Some(24),
 			session_ttl: Some(3600 * 24),
 			expired_session_cleanup_interval: Some(60 * 60 * 8),
+			apply_destructive_migrations: Some(true),
+			enable_telemetry: Some(true),
+			hold_jobs_during_maintenance: Some(true),
+			job_yield_budget_ms: Some(100),
+			page_resize_widths: Some(vec![800, 1600]),
+			job_min_free_disk_mb: Some(1024),
+			job_max_rss_mb: Some(2048),
+			job_max_load_average: Some(4.0),
+			temp_dir_max_mb: Some(2048),
+			job_log_level: Some(LogLevel::Debug),
+			job_disk_pause_threshold_mb: Some(2048),
+			library_warm_up_timeout_secs: Some(300),
+			outbound_proxy: Some("http://proxy.local:3128".to_string()),
+			telemetry_url: Some("https://telemetry.local/report".to_string()),
 		};
 
 		// Apply the partial configuration
@@ -476,6 +848,20 @@ mod tests {
 				password_hash_cost: 24,
 				session_ttl: 3600 * 24,
 				expired_session_cleanup_interval: 60 * 60 * 8,
+				apply_destructive_migrations: true,
+				enable_telemetry: true,
+				hold_jobs_during_maintenance: true,
+				job_yield_budget_ms: 100,
+				page_resize_widths: vec![800, 1600],
+				job_min_free_disk_mb: 1024,
+				job_max_rss_mb: 2048,
+				job_max_load_average: 4.0,
+				temp_dir_max_mb: 2048,
+				job_log_level: LogLevel::Debug,
+				job_disk_pause_threshold_mb: 2048,
+				library_warm_up_timeout_secs: 300,
+				outbound_proxy: Some("http://proxy.local:3128".to_string()),
+				telemetry_url: Some("https://telemetry.local/report".to_string()),
 			}
 		);
 	}
@@ -493,6 +879,20 @@ mod tests {
 		env::set_var(HASH_COST_KEY, "24");
 		env::set_var(SESSION_TTL_KEY, (3600 * 24).to_string());
 		env::set_var(SESSION_EXPIRY_INTERVAL_KEY, (60 * 60 * 8).to_string());
+		env::set_var(APPLY_DESTRUCTIVE_MIGRATIONS_KEY, "true");
+		env::set_var(ENABLE_TELEMETRY_KEY, "true");
+		env::set_var(HOLD_JOBS_DURING_MAINTENANCE_KEY, "true");
+		env::set_var(JOB_YIELD_BUDGET_KEY, "100");
+		env::set_var(PAGE_RESIZE_WIDTHS_KEY, "800,1600");
+		env::set_var(JOB_MIN_FREE_DISK_KEY, "1024");
+		env::set_var(JOB_MAX_RSS_KEY, "2048");
+		env::set_var(JOB_MAX_LOAD_AVERAGE_KEY, "4.0");
+		env::set_var(TEMP_DIR_MAX_KEY, "2048");
+		env::set_var(JOB_LOG_LEVEL_KEY, "DEBUG");
+		env::set_var(JOB_DISK_PAUSE_THRESHOLD_KEY, "2048");
+		env::set_var(LIBRARY_WARM_UP_TIMEOUT_KEY, "300");
+		env::set_var(OUTBOUND_PROXY_KEY, "http://proxy.local:3128");
+		env::set_var(TELEMETRY_URL_KEY, "https://telemetry.local/report");
 
 		// Create a new StumpConfig and load values from the environment.
 		let config = StumpConfig::new("not_a_dir".to_string())
@@ -515,6 +915,20 @@ mod tests {
 				password_hash_cost: 24,
 				session_ttl: 3600 * 24,
 				expired_session_cleanup_interval: 60 * 60 * 8,
+				apply_destructive_migrations: true,
+				enable_telemetry: true,
+				hold_jobs_during_maintenance: true,
+				job_yield_budget_ms: 100,
+				page_resize_widths: vec![800, 1600],
+				job_min_free_disk_mb: 1024,
+				job_max_rss_mb: 2048,
+				job_max_load_average: 4.0,
+				temp_dir_max_mb: 2048,
+				job_log_level: LogLevel::Debug,
+				job_disk_pause_threshold_mb: 2048,
+				library_warm_up_timeout_secs: 300,
+				outbound_proxy: Some("http://proxy.local:3128".to_string()),
+				telemetry_url: Some("https://telemetry.local/report".to_string()),
 			}
 		);
 	}
@@ -547,6 +961,20 @@ mod tests {
 				password_hash_cost: DEFAULT_PASSWORD_HASH_COST,
 				session_ttl: DEFAULT_SESSION_TTL,
 				expired_session_cleanup_interval: DEFAULT_SESSION_EXPIRY_CLEANUP_INTERVAL,
+				apply_destructive_migrations: false,
+				enable_telemetry: false,
+				hold_jobs_during_maintenance: false,
+				job_yield_budget_ms: DEFAULT_JOB_YIELD_BUDGET_MS,
+				page_resize_widths: DEFAULT_PAGE_RESIZE_WIDTHS.to_vec(),
+				job_min_free_disk_mb: DEFAULT_JOB_MIN_FREE_DISK_MB,
+				job_max_rss_mb: 0,
+				job_max_load_average: 0.0,
+				temp_dir_max_mb: DEFAULT_TEMP_DIR_MAX_MB,
+				job_log_level: LogLevel::Info,
+				job_disk_pause_threshold_mb: DEFAULT_JOB_DISK_PAUSE_THRESHOLD_MB,
+				library_warm_up_timeout_secs: DEFAULT_LIBRARY_WARM_UP_TIMEOUT_SECS,
+				outbound_proxy: None,
+				telemetry_url: None,
 			}
 		);
 
@@ -578,6 +1006,20 @@ mod tests {
 			password_hash_cost: None,
 			session_ttl: None,
 			expired_session_cleanup_interval: None,
+			apply_destructive_migrations: None,
+			enable_telemetry: None,
+			hold_jobs_during_maintenance: None,
+			job_yield_budget_ms: None,
+			page_resize_widths: None,
+			job_min_free_disk_mb: None,
+			job_max_rss_mb: None,
+			job_max_load_average: None,
+			temp_dir_max_mb: None,
+			job_log_level: None,
+			job_disk_pause_threshold_mb: None,
+			library_warm_up_timeout_secs: None,
+			outbound_proxy: None,
+			telemetry_url: None,
 		};
 		partial_config.apply_to_config(&mut config);
 
@@ -608,6 +1050,20 @@ mod tests {
 				expired_session_cleanup_interval: Some(
 					DEFAULT_SESSION_EXPIRY_CLEANUP_INTERVAL
 				),
+				apply_destructive_migrations: Some(false),
+				enable_telemetry: Some(false),
+				hold_jobs_during_maintenance: Some(false),
+				job_yield_budget_ms: Some(DEFAULT_JOB_YIELD_BUDGET_MS),
+				page_resize_widths: Some(DEFAULT_PAGE_RESIZE_WIDTHS.to_vec()),
+				job_min_free_disk_mb: Some(DEFAULT_JOB_MIN_FREE_DISK_MB),
+				job_max_rss_mb: Some(0),
+				job_max_load_average: Some(0.0),
+				temp_dir_max_mb: Some(DEFAULT_TEMP_DIR_MAX_MB),
+				job_log_level: Some(LogLevel::Info),
+				job_disk_pause_threshold_mb: Some(DEFAULT_JOB_DISK_PAUSE_THRESHOLD_MB),
+				library_warm_up_timeout_secs: Some(DEFAULT_LIBRARY_WARM_UP_TIMEOUT_SECS),
+				outbound_proxy: None,
+				telemetry_url: None,
 			}
 		);
 
@@ -617,6 +1073,21 @@ mod tests {
 			.expect("Failed to delete temporary directory");
 	}
 
+	#[test]
+	fn test_validating_config() {
+		let tempdir = tempfile::tempdir().expect("Failed to create temporary directory");
+		let config_dir = tempdir.path().to_string_lossy().to_string();
+		let mut config = StumpConfig::new(config_dir.clone());
+		config.client_dir = config_dir;
+		assert!(config.validate().is_empty());
+
+		config.profile = String::from("staging");
+		config.port = 0;
+		config.password_hash_cost = 1;
+		config.db_path = Some(String::from("/does/not/exist"));
+		assert_eq!(config.validate().len(), 4);
+	}
+
 	fn get_mock_config_file() -> String {
 		let mock_config_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
 			.join("integration-tests/data/mock-stump.toml");
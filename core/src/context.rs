@@ -9,8 +9,11 @@ use crate::{
 	config::StumpConfig,
 	db::{self, entity::Log},
 	event::{CoreEvent, InternalCoreTask},
-	job::JobExecutorTrait,
+	job::{JobError, JobExecutorTrait},
+	maintenance::{MaintenanceMode, MaintenanceState},
 	prisma,
+	setup::SetupState,
+	warm_up::WarmUpState,
 };
 
 type InternalSender = UnboundedSender<InternalCoreTask>;
@@ -25,6 +28,9 @@ pub struct Ctx {
 	pub db: Arc<prisma::PrismaClient>,
 	pub internal_sender: Arc<InternalSender>,
 	pub response_channel: Arc<ClientChannel>,
+	pub maintenance: MaintenanceState,
+	pub setup: SetupState,
+	pub warm_up: WarmUpState,
 }
 
 impl Clone for Ctx {
@@ -56,6 +62,9 @@ impl Ctx {
 			db: Arc::new(db::create_client(&config).await),
 			internal_sender: Arc::new(internal_sender),
 			response_channel: Arc::new(channel::<CoreEvent>(1024)),
+			maintenance: MaintenanceState::default(),
+			setup: SetupState::default(),
+			warm_up: WarmUpState::default(),
 		}
 	}
 
@@ -69,9 +78,49 @@ impl Ctx {
 			db: Arc::new(db::create_test_client().await),
 			internal_sender: Arc::new(unbounded_channel::<InternalCoreTask>().0),
 			response_channel: Arc::new(channel::<CoreEvent>(1024)),
+			maintenance: MaintenanceState::default(),
+			setup: SetupState::default(),
+			warm_up: WarmUpState::default(),
 		}
 	}
 
+	/// Creates a [Ctx] instance for unit tests **only**, wrapping the given prisma client
+	/// (typically from [prisma::PrismaClient::_mock]) and config.
+	#[cfg(test)]
+	pub(crate) fn mock_with_client(
+		client: prisma::PrismaClient,
+		config: StumpConfig,
+	) -> Ctx {
+		Ctx {
+			config: Arc::new(config),
+			db: Arc::new(client),
+			internal_sender: Arc::new(unbounded_channel::<InternalCoreTask>().0),
+			response_channel: Arc::new(channel::<CoreEvent>(1024)),
+			maintenance: MaintenanceState::default(),
+			setup: SetupState::default(),
+			warm_up: WarmUpState::default(),
+		}
+	}
+
+	/// Creates a client for a new SQLite database in a temporary directory, with the
+	/// schema pushed to it. The directory is removed when dropped, so it must be kept
+	/// alive for as long as the client is used.
+	#[cfg(test)]
+	pub(crate) async fn mock_db() -> (prisma::PrismaClient, tempfile::TempDir) {
+		let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+		let client = crate::db::create_client_with_url(&format!(
+			"file:{}",
+			dir.path().join("test.db").to_string_lossy()
+		))
+		.await;
+		client
+			._db_push()
+			.await
+			.expect("Failed to push the schema to the test database");
+
+		(client, dir)
+	}
+
 	/// Wraps the [Ctx] in an [Arc], allowing it to be shared across threads. This
 	/// is just a simple utility function.
 	///
@@ -109,6 +158,9 @@ impl Ctx {
 			db: self.db.clone(),
 			internal_sender: self.internal_sender.clone(),
 			response_channel: self.response_channel.clone(),
+			maintenance: self.maintenance.clone(),
+			setup: self.setup.clone(),
+			warm_up: self.warm_up.clone(),
 		}
 	}
 
@@ -134,6 +186,7 @@ impl Ctx {
 	///    let event = CoreEvent::JobFailed {
 	///        job_id: "Gandalf quote".to_string(),
 	///        message: "When in doubt, follow your nose".to_string(),
+	///        sequence: 1,
 	///    };
 	///
 	///    let ctx_cpy = ctx.clone();
@@ -142,7 +195,7 @@ impl Ctx {
 	///        let received_event = receiver.recv().await;
 	///        assert_eq!(received_event.is_ok(), true);
 	///        match received_event.unwrap() {
-	///            CoreEvent::JobFailed { job_id, message } => {
+	///            CoreEvent::JobFailed { job_id, message, .. } => {
 	///                assert_eq!(job_id, "Gandalf quote");
 	///                assert_eq!(message, "When in doubt, follow your nose");
 	///            }
@@ -188,8 +241,63 @@ impl Ctx {
 		self.internal_sender.send(task)
 	}
 
-	/// Sends an EnqueueJob task to the event manager.
-	pub fn dispatch_job(
+	/// Returns the active maintenance mode, if any. A mode whose expiry has passed is
+	/// considered inactive, even if it has yet to be cleared.
+	pub fn maintenance_mode(&self) -> Option<MaintenanceMode> {
+		self.maintenance
+			.read()
+			.ok()
+			.and_then(|state| state.clone())
+			.filter(|mode| !mode.is_expired(chrono::Utc::now()))
+	}
+
+	/// Returns true if the server is awaiting first-run setup, i.e. it had no users on startup
+	/// and setup has yet to be completed.
+	pub fn is_setup_required(&self) -> bool {
+		self.setup
+			.read()
+			.map(|state| state.is_some())
+			.unwrap_or(false)
+	}
+
+	/// Returns true if jobs should be held in the queue rather than started, which is the
+	/// case while maintenance mode is active and configured to hold jobs.
+	pub fn is_holding_jobs(&self) -> bool {
+		self.config.hold_jobs_during_maintenance && self.maintenance_mode().is_some()
+	}
+
+	/// Validates the job and, if it is valid, sends an EnqueueJob task to the event manager.
+	/// Invalid jobs are rejected with an error instead of taking up a slot in the queue, as
+	/// are all jobs during maintenance mode unless it is configured to hold them. Returns
+	/// the ID of the enqueued job.
+	pub async fn enqueue_job(
+		&self,
+		job: Box<dyn JobExecutorTrait>,
+	) -> Result<String, JobError> {
+		job.validate(self).await?;
+		let job_id = job
+			.detail()
+			.as_ref()
+			.map(|detail| detail.id.clone())
+			.ok_or_else(|| JobError::InvalidJob(String::from("Job is missing an ID")))?;
+
+		if let Some(mode) = self.maintenance_mode() {
+			if !self.config.hold_jobs_during_maintenance {
+				return Err(JobError::MaintenanceMode(
+					mode.message
+						.unwrap_or_else(|| String::from("Stump is in maintenance mode")),
+				));
+			}
+		}
+
+		self.dispatch_job(job)
+			.map_err(|e| JobError::Unknown(format!("Failed to enqueue job: {}", e)))?;
+
+		Ok(job_id)
+	}
+
+	/// Sends an EnqueueJob task to the event manager, without validating the job
+	fn dispatch_job(
 		&self,
 		job: Box<dyn JobExecutorTrait>,
 	) -> Result<(), SendError<InternalCoreTask>> {
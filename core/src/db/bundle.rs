@@ -0,0 +1,1236 @@
+//! Server-wide export and import of configuration-type data, e.g. users, libraries,
+//! notifiers and schedules, for the purposes of disaster recovery. Media is never
+//! included, since it is rebuilt by scanning the bundled libraries.
+
+use std::{
+	collections::{HashMap, HashSet},
+	num::NonZeroU32,
+	str::FromStr,
+};
+
+use data_encoding::{BASE64, HEXLOWER};
+use prisma_client_rust::{
+	chrono::{DateTime, Utc},
+	RelationNotFetchedError,
+};
+use ring::{
+	aead, pbkdf2,
+	rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::{
+	db::entity::{AgeRestriction, LibraryOptions, NotifierType},
+	prisma::{
+		age_restriction, job_schedule_config, library, library_options, media,
+		read_progress, server_config, user, user_preferences, PrismaClient,
+	},
+};
+
+/// The current version of the [ServerBundle] format. Bundles with any other version are
+/// rejected on import.
+pub const SERVER_BUNDLE_VERSION: u32 = 1;
+
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+	#[error("Unsupported bundle version {0}, expected {}", SERVER_BUNDLE_VERSION)]
+	UnsupportedVersion(u32),
+	#[error("The bundle contains encrypted secrets, but no passphrase was provided")]
+	MissingPassphrase,
+	#[error("Failed to decrypt the bundle secrets, is the passphrase correct?")]
+	InvalidPassphrase,
+	#[error("Failed to encrypt the bundle secrets")]
+	EncryptionFailed,
+	#[error("The bundle is malformed: {0}")]
+	Malformed(String),
+	#[error("A query error occurred {0}")]
+	QueryError(#[from] prisma_client_rust::QueryError),
+	#[error("Failed to load relation: {0}")]
+	RelationNotLoaded(#[from] RelationNotFetchedError),
+	#[error("An object failed to (de)serialize: {0}")]
+	SerdeFailure(#[from] serde_json::Error),
+}
+
+pub type BundleResult<T> = Result<T, BundleError>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct ServerBundle {
+	/// The version of the bundle format, see [SERVER_BUNDLE_VERSION]
+	pub version: u32,
+	/// The datetime stamp of when the bundle was exported
+	pub exported_at: String,
+	pub users: Vec<BundledUser>,
+	pub libraries: Vec<BundledLibrary>,
+	pub notifiers: Vec<BundledNotifier>,
+	/// The scheduled scan configuration, if one was set
+	pub scan_schedule: Option<BundledScanSchedule>,
+	/// The read progress of every user. Only present when explicitly requested
+	pub read_progress: Option<Vec<BundledReadProgress>>,
+	/// The password hashes and notifier configs, encrypted with a passphrase. Secrets are
+	/// omitted entirely when no passphrase is provided during export
+	pub secrets: Option<EncryptedSecrets>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct BundledUser {
+	pub username: String,
+	pub is_server_owner: bool,
+	pub is_locked: bool,
+	pub max_sessions_allowed: Option<i32>,
+	/// A comma separated list of permissions, e.g. "file:upload,file:download"
+	pub permissions: Option<String>,
+	pub age_restriction: Option<AgeRestriction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct BundledLibrary {
+	pub name: String,
+	pub description: Option<String>,
+	pub emoji: Option<String>,
+	pub path: String,
+	pub library_options: LibraryOptions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct BundledNotifier {
+	#[serde(rename = "type")]
+	pub _type: NotifierType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct BundledScanSchedule {
+	pub interval_secs: i32,
+	/// The paths of the libraries excluded from scheduled scans
+	pub excluded_library_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct BundledReadProgress {
+	pub username: String,
+	/// The path of the media on disk, which is used to match it on import
+	pub media_path: String,
+	pub page: i32,
+	pub percentage_completed: Option<f64>,
+	pub epubcfi: Option<String>,
+	pub is_completed: bool,
+	pub completed_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct EncryptedSecrets {
+	/// The hex encoded salt used to derive the key from the passphrase
+	pub salt: String,
+	/// The hex encoded nonce used for encryption
+	pub nonce: String,
+	/// The base64 encoded, AES-256-GCM encrypted secrets
+	pub ciphertext: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BundleSecrets {
+	/// The password hashes of the bundled users, keyed by username
+	password_hashes: HashMap<String, String>,
+	/// The configs of the bundled notifiers, in the same order as [ServerBundle::notifiers]
+	notifier_configs: Vec<String>,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> BundleResult<aead::LessSafeKey> {
+	let mut key_bytes = [0u8; 32];
+	pbkdf2::derive(
+		pbkdf2::PBKDF2_HMAC_SHA256,
+		NonZeroU32::new(PBKDF2_ITERATIONS).expect("PBKDF2 iterations must be non-zero"),
+		salt,
+		passphrase.as_bytes(),
+		&mut key_bytes,
+	);
+	let key = aead::UnboundKey::new(&aead::AES_256_GCM, &key_bytes)
+		.map_err(|_| BundleError::EncryptionFailed)?;
+	Ok(aead::LessSafeKey::new(key))
+}
+
+impl BundleSecrets {
+	fn encrypt(&self, passphrase: &str) -> BundleResult<EncryptedSecrets> {
+		let rng = SystemRandom::new();
+		let mut salt = [0u8; SALT_LEN];
+		let mut nonce = [0u8; aead::NONCE_LEN];
+		rng.fill(&mut salt)
+			.and_then(|_| rng.fill(&mut nonce))
+			.map_err(|_| BundleError::EncryptionFailed)?;
+
+		let key = derive_key(passphrase, &salt)?;
+		let mut in_out = serde_json::to_vec(self)?;
+		key.seal_in_place_append_tag(
+			aead::Nonce::assume_unique_for_key(nonce),
+			aead::Aad::empty(),
+			&mut in_out,
+		)
+		.map_err(|_| BundleError::EncryptionFailed)?;
+
+		Ok(EncryptedSecrets {
+			salt: HEXLOWER.encode(&salt),
+			nonce: HEXLOWER.encode(&nonce),
+			ciphertext: BASE64.encode(&in_out),
+		})
+	}
+}
+
+impl EncryptedSecrets {
+	fn decrypt(&self, passphrase: &str) -> BundleResult<BundleSecrets> {
+		let malformed =
+			|e: data_encoding::DecodeError| BundleError::Malformed(e.to_string());
+		let salt = HEXLOWER.decode(self.salt.as_bytes()).map_err(malformed)?;
+		let nonce: [u8; aead::NONCE_LEN] = HEXLOWER
+			.decode(self.nonce.as_bytes())
+			.map_err(malformed)?
+			.try_into()
+			.map_err(|_| BundleError::Malformed(String::from("Invalid nonce length")))?;
+		let mut in_out = BASE64
+			.decode(self.ciphertext.as_bytes())
+			.map_err(malformed)?;
+
+		let key = derive_key(passphrase, &salt)?;
+		let plaintext = key
+			.open_in_place(
+				aead::Nonce::assume_unique_for_key(nonce),
+				aead::Aad::empty(),
+				&mut in_out,
+			)
+			.map_err(|_| BundleError::InvalidPassphrase)?;
+
+		Ok(serde_json::from_slice(plaintext)?)
+	}
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct BundleExportOptions {
+	/// Whether to include the read progress of every user
+	pub include_progress: bool,
+	/// The passphrase used to encrypt secrets. If not provided, secrets are omitted
+	pub passphrase: Option<String>,
+}
+
+/// Exports the configuration-type data of the server into a [ServerBundle]
+pub async fn export_server_bundle(
+	client: &PrismaClient,
+	options: BundleExportOptions,
+) -> BundleResult<ServerBundle> {
+	let mut secrets = BundleSecrets::default();
+
+	let users = client
+		.user()
+		.find_many(vec![user::deleted_at::equals(None)])
+		.with(user::age_restriction::fetch())
+		.exec()
+		.await?
+		.into_iter()
+		.map(|user| -> BundleResult<BundledUser> {
+			let age_restriction =
+				user.age_restriction()?.map(|restriction| AgeRestriction {
+					age: restriction.age,
+					restrict_on_unset: restriction.restrict_on_unset,
+				});
+			secrets
+				.password_hashes
+				.insert(user.username.clone(), user.hashed_password);
+
+			Ok(BundledUser {
+				username: user.username,
+				is_server_owner: user.is_server_owner,
+				is_locked: user.is_locked,
+				max_sessions_allowed: user.max_sessions_allowed,
+				permissions: user.permissions,
+				age_restriction,
+			})
+		})
+		.collect::<BundleResult<Vec<BundledUser>>>()?;
+
+	let libraries = client
+		.library()
+		.find_many(vec![])
+		.with(library::library_options::fetch())
+		.exec()
+		.await?
+		.into_iter()
+		.map(|library| -> BundleResult<BundledLibrary> {
+			let library_options = LibraryOptions {
+				id: None,
+				library_id: None,
+				..LibraryOptions::from(library.library_options()?)
+			};
+
+			Ok(BundledLibrary {
+				name: library.name,
+				description: library.description,
+				emoji: library.emoji,
+				path: library.path,
+				library_options,
+			})
+		})
+		.collect::<BundleResult<Vec<BundledLibrary>>>()?;
+
+	let mut notifiers = vec![];
+	for notifier in client.notifier().find_many(vec![]).exec().await? {
+		match NotifierType::from_str(&notifier.r#type) {
+			Ok(_type) => {
+				notifiers.push(BundledNotifier { _type });
+				secrets.notifier_configs.push(
+					String::from_utf8(notifier.config)
+						.map_err(|e| BundleError::Malformed(e.to_string()))?,
+				);
+			},
+			Err(error) => {
+				tracing::warn!(?error, id = notifier.id, "Skipping invalid notifier");
+			},
+		}
+	}
+
+	let scan_schedule = client
+		.server_config()
+		.find_first(vec![])
+		.with(
+			server_config::job_schedule_config::fetch()
+				.with(job_schedule_config::excluded_libraries::fetch(vec![])),
+		)
+		.exec()
+		.await?
+		.and_then(|config| config.job_schedule_config.flatten())
+		.map(|schedule| -> BundleResult<BundledScanSchedule> {
+			Ok(BundledScanSchedule {
+				interval_secs: schedule.interval_secs,
+				excluded_library_paths: schedule
+					.excluded_libraries()?
+					.iter()
+					.map(|library| library.path.clone())
+					.collect(),
+			})
+		})
+		.transpose()?;
+
+	let read_progress = if options.include_progress {
+		let progress = client
+			.read_progress()
+			.find_many(vec![])
+			.with(read_progress::user::fetch())
+			.with(read_progress::media::fetch())
+			.exec()
+			.await?
+			.into_iter()
+			.map(|progress| -> BundleResult<BundledReadProgress> {
+				Ok(BundledReadProgress {
+					username: progress.user()?.username.clone(),
+					media_path: progress.media()?.path.clone(),
+					page: progress.page,
+					percentage_completed: progress.percentage_completed,
+					epubcfi: progress.epubcfi,
+					is_completed: progress.is_completed,
+					completed_at: progress.completed_at.map(|date| date.to_rfc3339()),
+				})
+			})
+			.collect::<BundleResult<Vec<BundledReadProgress>>>()?;
+		Some(progress)
+	} else {
+		None
+	};
+
+	let secrets = options
+		.passphrase
+		.map(|passphrase| secrets.encrypt(&passphrase))
+		.transpose()?;
+
+	Ok(ServerBundle {
+		version: SERVER_BUNDLE_VERSION,
+		exported_at: Utc::now().to_rfc3339(),
+		users,
+		libraries,
+		notifiers,
+		scan_schedule,
+		read_progress,
+		secrets,
+	})
+}
+
+#[derive(
+	Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema,
+)]
+pub enum ConflictResolution {
+	/// Leave the existing item untouched
+	#[default]
+	#[serde(rename = "SKIP")]
+	Skip,
+	/// Replace the existing item with the bundled one
+	#[serde(rename = "OVERWRITE")]
+	Overwrite,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Type, ToSchema)]
+pub struct BundleImportOptions {
+	/// Whether to only plan the import, without applying any changes
+	#[serde(default)]
+	pub dry_run: bool,
+	/// The passphrase used to encrypt the bundle secrets, if any
+	pub passphrase: Option<String>,
+	/// How to resolve conflicts with existing users, keyed by username. Defaults to SKIP
+	#[serde(default)]
+	pub user_resolutions: HashMap<String, ConflictResolution>,
+	/// How to resolve conflicts with existing libraries, keyed by path. Defaults to SKIP
+	#[serde(default)]
+	pub library_resolutions: HashMap<String, ConflictResolution>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub enum BundleItemKind {
+	#[serde(rename = "USER")]
+	User,
+	#[serde(rename = "LIBRARY")]
+	Library,
+	#[serde(rename = "NOTIFIER")]
+	Notifier,
+	#[serde(rename = "SCAN_SCHEDULE")]
+	ScanSchedule,
+	#[serde(rename = "READ_PROGRESS")]
+	ReadProgress,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub enum PlannedAction {
+	#[serde(rename = "CREATE")]
+	Create,
+	#[serde(rename = "UPDATE")]
+	Update,
+	#[serde(rename = "SKIP")]
+	Skip,
+}
+
+impl From<Option<ConflictResolution>> for PlannedAction {
+	fn from(resolution: Option<ConflictResolution>) -> Self {
+		match resolution.unwrap_or_default() {
+			ConflictResolution::Overwrite => PlannedAction::Update,
+			ConflictResolution::Skip => PlannedAction::Skip,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct PlannedChange {
+	pub kind: BundleItemKind,
+	/// The key which identifies the item, e.g. a username or library path
+	pub key: String,
+	pub action: PlannedAction,
+	/// Whether the item conflicts with existing data on the server
+	pub conflict: bool,
+	/// Additional details about the change, e.g. why it was skipped
+	pub note: Option<String>,
+}
+
+impl PlannedChange {
+	fn new(kind: BundleItemKind, key: String, action: PlannedAction) -> Self {
+		Self {
+			kind,
+			key,
+			action,
+			conflict: false,
+			note: None,
+		}
+	}
+
+	fn conflicting(mut self) -> Self {
+		self.conflict = true;
+		self
+	}
+
+	fn with_note(mut self, note: &str) -> Self {
+		self.note = Some(note.to_string());
+		self
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct ImportPlan {
+	/// Whether the plan was only computed, and not applied
+	pub dry_run: bool,
+	pub changes: Vec<PlannedChange>,
+}
+
+impl ImportPlan {
+	/// Returns the changes which conflict with existing data on the server
+	pub fn conflicts(&self) -> Vec<&PlannedChange> {
+		self.changes
+			.iter()
+			.filter(|change| change.conflict)
+			.collect()
+	}
+
+	fn action_for(&self, kind: BundleItemKind, key: &str) -> PlannedAction {
+		self.changes
+			.iter()
+			.find(|change| change.kind == kind && change.key == key)
+			.map(|change| change.action)
+			.unwrap_or(PlannedAction::Skip)
+	}
+}
+
+/// A snapshot of the existing data on the server which a bundle may conflict with
+#[derive(Debug, Default)]
+struct ExistingServerData {
+	usernames: HashSet<String>,
+	library_paths: HashSet<String>,
+	library_names: HashSet<String>,
+	/// The type and config of every existing notifier, used to avoid importing duplicates
+	notifiers: HashSet<(String, String)>,
+	has_server_owner: bool,
+	has_scan_schedule: bool,
+}
+
+impl ExistingServerData {
+	async fn fetch(client: &PrismaClient) -> BundleResult<Self> {
+		let users = client
+			.user()
+			.find_many(vec![user::deleted_at::equals(None)])
+			.exec()
+			.await?;
+		let libraries = client.library().find_many(vec![]).exec().await?;
+		let notifiers = client.notifier().find_many(vec![]).exec().await?;
+		let has_scan_schedule = client
+			.server_config()
+			.find_first(vec![])
+			.exec()
+			.await?
+			.map(|config| config.job_schedule_config_id.is_some())
+			.unwrap_or(false);
+
+		Ok(Self {
+			has_server_owner: users.iter().any(|user| user.is_server_owner),
+			usernames: users.into_iter().map(|user| user.username).collect(),
+			library_names: libraries.iter().map(|lib| lib.name.clone()).collect(),
+			library_paths: libraries.into_iter().map(|lib| lib.path).collect(),
+			notifiers: notifiers
+				.into_iter()
+				.map(|notifier| {
+					(
+						notifier.r#type,
+						String::from_utf8_lossy(&notifier.config).into_owned(),
+					)
+				})
+				.collect(),
+			has_scan_schedule,
+		})
+	}
+}
+
+fn plan_import(
+	bundle: &ServerBundle,
+	options: &BundleImportOptions,
+	secrets: &BundleSecrets,
+	existing: &ExistingServerData,
+) -> ImportPlan {
+	let mut changes = vec![];
+
+	for bundled_user in &bundle.users {
+		let key = bundled_user.username.clone();
+		let change = if existing.usernames.contains(&key) {
+			let action = options.user_resolutions.get(&key).copied().into();
+			PlannedChange::new(BundleItemKind::User, key, action)
+				.conflicting()
+				.with_note("A user with this username already exists")
+		} else if !secrets.password_hashes.contains_key(&key) {
+			PlannedChange::new(BundleItemKind::User, key, PlannedAction::Create)
+				.with_note("No password was bundled, so the account will be locked until its password is reset")
+		} else if bundled_user.is_server_owner && existing.has_server_owner {
+			PlannedChange::new(BundleItemKind::User, key, PlannedAction::Create)
+				.conflicting()
+				.with_note("The server already has an owner, so the account will be created without owner status")
+		} else {
+			PlannedChange::new(BundleItemKind::User, key, PlannedAction::Create)
+		};
+		changes.push(change);
+	}
+
+	for bundled_library in &bundle.libraries {
+		let key = bundled_library.path.clone();
+		let change = if existing.library_paths.contains(&key) {
+			let action = options.library_resolutions.get(&key).copied().into();
+			PlannedChange::new(BundleItemKind::Library, key, action)
+				.conflicting()
+				.with_note("A library with this path already exists")
+		} else if existing.library_names.contains(&bundled_library.name) {
+			PlannedChange::new(BundleItemKind::Library, key, PlannedAction::Skip)
+				.conflicting()
+				.with_note("A library with this name already exists at a different path")
+		} else {
+			PlannedChange::new(BundleItemKind::Library, key, PlannedAction::Create)
+		};
+		changes.push(change);
+	}
+
+	// Notifiers have no natural key, so they are compared by type and config. Planned
+	// notifiers are tracked too, so duplicates within the bundle are only created once
+	let mut known_notifiers = existing.notifiers.clone();
+	for (index, bundled_notifier) in bundle.notifiers.iter().enumerate() {
+		let _type = bundled_notifier._type.to_string();
+		let key = format!("{}#{}", _type, index);
+		let change = match secrets.notifier_configs.get(index) {
+			Some(config) if !known_notifiers.insert((_type, config.clone())) => {
+				PlannedChange::new(BundleItemKind::Notifier, key, PlannedAction::Skip)
+					.with_note("An identical notifier already exists")
+			},
+			Some(_) => {
+				PlannedChange::new(BundleItemKind::Notifier, key, PlannedAction::Create)
+			},
+			None => {
+				PlannedChange::new(BundleItemKind::Notifier, key, PlannedAction::Skip)
+					.with_note("The notifier configuration was not bundled")
+			},
+		};
+		changes.push(change);
+	}
+
+	if bundle.scan_schedule.is_some() {
+		let action = if existing.has_scan_schedule {
+			PlannedAction::Update
+		} else {
+			PlannedAction::Create
+		};
+		changes.push(PlannedChange::new(
+			BundleItemKind::ScanSchedule,
+			String::from("scan_schedule"),
+			action,
+		));
+	}
+
+	if let Some(read_progress) = &bundle.read_progress {
+		changes.push(
+			PlannedChange::new(
+				BundleItemKind::ReadProgress,
+				format!("{} record(s)", read_progress.len()),
+				PlannedAction::Update,
+			)
+			.with_note(
+				"Progress for users or media which don't exist on this server is ignored",
+			),
+		);
+	}
+
+	ImportPlan {
+		dry_run: options.dry_run,
+		changes,
+	}
+}
+
+/// Validates and imports a [ServerBundle], returning the plan which was (or, for a dry
+/// run, would be) applied. All changes are applied in a single transaction.
+pub async fn import_server_bundle(
+	client: &PrismaClient,
+	bundle: ServerBundle,
+	options: BundleImportOptions,
+) -> BundleResult<ImportPlan> {
+	if bundle.version != SERVER_BUNDLE_VERSION {
+		return Err(BundleError::UnsupportedVersion(bundle.version));
+	}
+
+	let secrets = match (&bundle.secrets, &options.passphrase) {
+		(Some(encrypted), Some(passphrase)) => encrypted.decrypt(passphrase)?,
+		(Some(_), None) => return Err(BundleError::MissingPassphrase),
+		(None, _) => BundleSecrets::default(),
+	};
+
+	let existing = ExistingServerData::fetch(client).await?;
+	let plan = plan_import(&bundle, &options, &secrets, &existing);
+
+	if plan.dry_run {
+		return Ok(plan);
+	}
+
+	let applied_plan = plan.clone();
+	client
+		._transaction()
+		.run(|client| async move {
+			apply_users(&client, &bundle, &plan, &secrets, &existing).await?;
+			apply_libraries(&client, &bundle, &plan).await?;
+			apply_notifiers(&client, &plan, &secrets).await?;
+			if let Some(schedule) = bundle.scan_schedule {
+				apply_scan_schedule(&client, schedule).await?;
+			}
+			if let Some(read_progress) = bundle.read_progress {
+				apply_read_progress(&client, read_progress).await?;
+			}
+			Ok::<_, BundleError>(())
+		})
+		.await?;
+
+	Ok(applied_plan)
+}
+
+async fn apply_users(
+	client: &PrismaClient,
+	bundle: &ServerBundle,
+	plan: &ImportPlan,
+	secrets: &BundleSecrets,
+	existing: &ExistingServerData,
+) -> BundleResult<()> {
+	for bundled_user in &bundle.users {
+		let username = bundled_user.username.clone();
+		let hashed_password = secrets.password_hashes.get(&username).cloned();
+
+		let user_id = match plan.action_for(BundleItemKind::User, &username) {
+			PlannedAction::Create => {
+				let created_user = client
+					.user()
+					.create(
+						username,
+						hashed_password.clone().unwrap_or_default(),
+						vec![
+							user::is_server_owner::set(
+								bundled_user.is_server_owner
+									&& !existing.has_server_owner,
+							),
+							user::is_locked::set(
+								bundled_user.is_locked || hashed_password.is_none(),
+							),
+							user::permissions::set(bundled_user.permissions.clone()),
+							user::max_sessions_allowed::set(
+								bundled_user.max_sessions_allowed,
+							),
+						],
+					)
+					.exec()
+					.await?;
+				client
+					.user_preferences()
+					.create(vec![
+						user_preferences::user::connect(user::id::equals(
+							created_user.id.clone(),
+						)),
+						user_preferences::user_id::set(Some(created_user.id.clone())),
+					])
+					.exec()
+					.await?;
+				created_user.id
+			},
+			PlannedAction::Update => {
+				let mut params = vec![
+					user::is_locked::set(bundled_user.is_locked),
+					user::permissions::set(bundled_user.permissions.clone()),
+					user::max_sessions_allowed::set(bundled_user.max_sessions_allowed),
+				];
+				if let Some(hashed_password) = hashed_password {
+					params.push(user::hashed_password::set(hashed_password));
+				}
+				client
+					.user()
+					.update(user::username::equals(username), params)
+					.exec()
+					.await?
+					.id
+			},
+			PlannedAction::Skip => continue,
+		};
+
+		if let Some(restriction) = &bundled_user.age_restriction {
+			client
+				.age_restriction()
+				.upsert(
+					age_restriction::user_id::equals(user_id.clone()),
+					(
+						restriction.age,
+						user::id::equals(user_id.clone()),
+						vec![age_restriction::restrict_on_unset::set(
+							restriction.restrict_on_unset,
+						)],
+					),
+					vec![
+						age_restriction::age::set(restriction.age),
+						age_restriction::restrict_on_unset::set(
+							restriction.restrict_on_unset,
+						),
+					],
+				)
+				.exec()
+				.await?;
+		} else {
+			client
+				.age_restriction()
+				.delete_many(vec![age_restriction::user_id::equals(user_id)])
+				.exec()
+				.await?;
+		}
+	}
+
+	Ok(())
+}
+
+fn library_options_params(options: &LibraryOptions) -> Vec<library_options::SetParam> {
+	vec![
+		library_options::convert_rar_to_zip::set(options.convert_rar_to_zip),
+		library_options::hard_delete_conversions::set(options.hard_delete_conversions),
+		library_options::library_pattern::set(options.library_pattern.to_string()),
+		library_options::extract_metadata::set(options.extract_metadata),
+		library_options::generate_file_hashes::set(options.generate_file_hashes),
+		library_options::thumbnail_config::set(
+			options
+				.thumbnail_config
+				.as_ref()
+				.map(|config| serde_json::to_vec(config).unwrap_or_default()),
+		),
+	]
+}
+
+async fn apply_libraries(
+	client: &PrismaClient,
+	bundle: &ServerBundle,
+	plan: &ImportPlan,
+) -> BundleResult<()> {
+	for bundled_library in &bundle.libraries {
+		match plan.action_for(BundleItemKind::Library, &bundled_library.path) {
+			PlannedAction::Create => {
+				// TODO: refactor once nested create is supported
+				// https://github.com/Brendonovich/prisma-client-rust/issues/44
+				let options = client
+					.library_options()
+					.create(library_options_params(&bundled_library.library_options))
+					.exec()
+					.await?;
+				let library = client
+					.library()
+					.create(
+						bundled_library.name.clone(),
+						bundled_library.path.clone(),
+						library_options::id::equals(options.id.clone()),
+						vec![
+							library::description::set(
+								bundled_library.description.clone(),
+							),
+							library::emoji::set(bundled_library.emoji.clone()),
+						],
+					)
+					.exec()
+					.await?;
+				client
+					.library_options()
+					.update(
+						library_options::id::equals(options.id),
+						vec![
+							library_options::library::connect(library::id::equals(
+								library.id.clone(),
+							)),
+							library_options::library_id::set(Some(library.id)),
+						],
+					)
+					.exec()
+					.await?;
+			},
+			PlannedAction::Update => {
+				let library = client
+					.library()
+					.update(
+						library::path::equals(bundled_library.path.clone()),
+						vec![
+							library::name::set(bundled_library.name.clone()),
+							library::description::set(
+								bundled_library.description.clone(),
+							),
+							library::emoji::set(bundled_library.emoji.clone()),
+						],
+					)
+					.exec()
+					.await?;
+				client
+					.library_options()
+					.update(
+						library_options::id::equals(library.library_options_id),
+						library_options_params(&bundled_library.library_options),
+					)
+					.exec()
+					.await?;
+			},
+			PlannedAction::Skip => continue,
+		}
+	}
+
+	Ok(())
+}
+
+async fn apply_notifiers(
+	client: &PrismaClient,
+	plan: &ImportPlan,
+	secrets: &BundleSecrets,
+) -> BundleResult<()> {
+	let to_create = plan
+		.changes
+		.iter()
+		.filter(|change| {
+			change.kind == BundleItemKind::Notifier
+				&& change.action == PlannedAction::Create
+		})
+		.filter_map(|change| {
+			let (_type, index) = change.key.split_once('#')?;
+			let config = secrets.notifier_configs.get(index.parse::<usize>().ok()?)?;
+			Some((_type.to_string(), config.clone().into_bytes()))
+		});
+
+	for (_type, config) in to_create {
+		client
+			.notifier()
+			.create(_type, config, vec![])
+			.exec()
+			.await?;
+	}
+
+	Ok(())
+}
+
+async fn apply_scan_schedule(
+	client: &PrismaClient,
+	schedule: BundledScanSchedule,
+) -> BundleResult<()> {
+	let server_config = client
+		.server_config()
+		.find_first(vec![])
+		.exec()
+		.await?
+		.ok_or(BundleError::Malformed(String::from(
+			"Server preferences are missing!",
+		)))?;
+	let excluded_libraries = client
+		.library()
+		.find_many(vec![library::path::in_vec(schedule.excluded_library_paths)])
+		.exec()
+		.await?
+		.into_iter()
+		.map(|library| library::id::equals(library.id))
+		.collect::<Vec<library::UniqueWhereParam>>();
+
+	if let Some(schedule_id) = server_config.job_schedule_config_id {
+		client
+			.job_schedule_config()
+			.update(
+				job_schedule_config::id::equals(schedule_id),
+				vec![
+					job_schedule_config::interval_secs::set(schedule.interval_secs),
+					job_schedule_config::excluded_libraries::set(excluded_libraries),
+				],
+			)
+			.exec()
+			.await?;
+	} else {
+		client
+			.job_schedule_config()
+			.create(vec![
+				job_schedule_config::server_config::connect(server_config::id::equals(
+					server_config.id,
+				)),
+				job_schedule_config::interval_secs::set(schedule.interval_secs),
+				job_schedule_config::excluded_libraries::connect(excluded_libraries),
+			])
+			.exec()
+			.await?;
+	}
+
+	Ok(())
+}
+
+async fn apply_read_progress(
+	client: &PrismaClient,
+	read_progress: Vec<BundledReadProgress>,
+) -> BundleResult<()> {
+	let user_ids = client
+		.user()
+		.find_many(vec![user::username::in_vec(
+			read_progress.iter().map(|p| p.username.clone()).collect(),
+		)])
+		.exec()
+		.await?
+		.into_iter()
+		.map(|user| (user.username, user.id))
+		.collect::<HashMap<String, String>>();
+	let media_ids = client
+		.media()
+		.find_many(vec![media::path::in_vec(
+			read_progress.iter().map(|p| p.media_path.clone()).collect(),
+		)])
+		.exec()
+		.await?
+		.into_iter()
+		.map(|media| (media.path, media.id))
+		.collect::<HashMap<String, String>>();
+
+	for progress in read_progress {
+		let (Some(user_id), Some(media_id)) = (
+			user_ids.get(&progress.username),
+			media_ids.get(&progress.media_path),
+		) else {
+			tracing::debug!(
+				?progress,
+				"Skipping read progress for unknown user or media"
+			);
+			continue;
+		};
+
+		let completed_at = progress
+			.completed_at
+			.as_ref()
+			.and_then(|date| DateTime::parse_from_rfc3339(date).ok());
+		let params = || {
+			vec![
+				read_progress::page::set(progress.page),
+				read_progress::percentage_completed::set(progress.percentage_completed),
+				read_progress::epubcfi::set(progress.epubcfi.clone()),
+				read_progress::is_completed::set(progress.is_completed),
+				read_progress::completed_at::set(completed_at),
+			]
+		};
+
+		client
+			.read_progress()
+			.upsert(
+				read_progress::user_id_media_id(user_id.clone(), media_id.clone()),
+				(
+					progress.page,
+					media::id::equals(media_id.clone()),
+					user::id::equals(user_id.clone()),
+					params(),
+				),
+				params(),
+			)
+			.exec()
+			.await?;
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Ctx;
+
+	fn bundled_user(username: &str, is_server_owner: bool) -> BundledUser {
+		BundledUser {
+			username: username.to_string(),
+			is_server_owner,
+			is_locked: false,
+			max_sessions_allowed: None,
+			permissions: None,
+			age_restriction: None,
+		}
+	}
+
+	fn bundled_library(name: &str, path: &str) -> BundledLibrary {
+		BundledLibrary {
+			name: name.to_string(),
+			description: None,
+			emoji: None,
+			path: path.to_string(),
+			library_options: LibraryOptions::default(),
+		}
+	}
+
+	fn empty_bundle() -> ServerBundle {
+		ServerBundle {
+			version: SERVER_BUNDLE_VERSION,
+			exported_at: Utc::now().to_rfc3339(),
+			users: vec![],
+			libraries: vec![],
+			notifiers: vec![],
+			scan_schedule: None,
+			read_progress: None,
+			secrets: None,
+		}
+	}
+
+	#[test]
+	fn test_secrets_round_trip() {
+		let mut secrets = BundleSecrets::default();
+		secrets
+			.password_hashes
+			.insert(String::from("oromei"), String::from("$2b$12$hash"));
+		secrets
+			.notifier_configs
+			.push(String::from(r#"{"webhook_url":"https://discord.com"}"#));
+
+		let encrypted = secrets.encrypt("correct horse").unwrap();
+
+		let decrypted = encrypted.decrypt("correct horse").unwrap();
+		assert_eq!(decrypted.password_hashes, secrets.password_hashes);
+		assert_eq!(decrypted.notifier_configs, secrets.notifier_configs);
+
+		assert!(matches!(
+			encrypted.decrypt("battery staple"),
+			Err(BundleError::InvalidPassphrase)
+		));
+	}
+
+	#[test]
+	fn test_plan_import_conflicts() {
+		let mut bundle = empty_bundle();
+		bundle.users = vec![
+			bundled_user("oromei", true),
+			bundled_user("existing", false),
+			bundled_user("overwritten", false),
+		];
+		bundle.libraries = vec![
+			bundled_library("Comics", "/data/comics"),
+			bundled_library("Books", "/data/books"),
+		];
+		bundle.notifiers = vec![BundledNotifier {
+			_type: NotifierType::Discord,
+		}];
+
+		let mut secrets = BundleSecrets::default();
+		secrets
+			.password_hashes
+			.insert(String::from("oromei"), String::from("hash"));
+
+		let existing = ExistingServerData {
+			usernames: HashSet::from([
+				String::from("existing"),
+				String::from("overwritten"),
+			]),
+			library_paths: HashSet::from([String::from("/data/comics")]),
+			library_names: HashSet::from([String::from("Comics")]),
+			notifiers: HashSet::new(),
+			has_server_owner: true,
+			has_scan_schedule: false,
+		};
+		let options = BundleImportOptions {
+			dry_run: true,
+			user_resolutions: HashMap::from([(
+				String::from("overwritten"),
+				ConflictResolution::Overwrite,
+			)]),
+			..Default::default()
+		};
+
+		let plan = plan_import(&bundle, &options, &secrets, &existing);
+
+		assert!(plan.dry_run);
+		assert_eq!(
+			plan.action_for(BundleItemKind::User, "oromei"),
+			PlannedAction::Create
+		);
+		assert_eq!(
+			plan.action_for(BundleItemKind::User, "existing"),
+			PlannedAction::Skip
+		);
+		assert_eq!(
+			plan.action_for(BundleItemKind::User, "overwritten"),
+			PlannedAction::Update
+		);
+		assert_eq!(
+			plan.action_for(BundleItemKind::Library, "/data/comics"),
+			PlannedAction::Skip
+		);
+		assert_eq!(
+			plan.action_for(BundleItemKind::Library, "/data/books"),
+			PlannedAction::Create
+		);
+		assert_eq!(
+			plan.action_for(BundleItemKind::Notifier, "DISCORD#0"),
+			PlannedAction::Skip
+		);
+		// The owner conflict, both existing users and the existing library
+		assert_eq!(plan.conflicts().len(), 4);
+	}
+
+	#[tokio::test]
+	async fn test_export_import_round_trip() {
+		let (source, _source_dir) = Ctx::mock_db().await;
+
+		source
+			.user()
+			.create(
+				String::from("oromei"),
+				String::from("$2b$12$hash"),
+				vec![user::is_server_owner::set(true)],
+			)
+			.exec()
+			.await
+			.unwrap();
+		let options = source
+			.library_options()
+			.create(vec![])
+			.exec()
+			.await
+			.unwrap();
+		source
+			.library()
+			.create(
+				String::from("Comics"),
+				String::from("/data/comics"),
+				library_options::id::equals(options.id),
+				vec![],
+			)
+			.exec()
+			.await
+			.unwrap();
+		source
+			.notifier()
+			.create(
+				NotifierType::Discord.to_string(),
+				br#"{"webhook_url":"https://discord.com"}"#.to_vec(),
+				vec![],
+			)
+			.exec()
+			.await
+			.unwrap();
+
+		let bundle = export_server_bundle(
+			&source,
+			BundleExportOptions {
+				include_progress: false,
+				passphrase: Some(String::from("correct horse")),
+			},
+		)
+		.await
+		.unwrap();
+		assert_eq!(bundle.users.len(), 1);
+		assert_eq!(bundle.libraries.len(), 1);
+		assert_eq!(bundle.notifiers.len(), 1);
+
+		let (target, _target_dir) = Ctx::mock_db().await;
+		let import_options = BundleImportOptions {
+			passphrase: Some(String::from("correct horse")),
+			..Default::default()
+		};
+
+		let plan = import_server_bundle(&target, bundle.clone(), import_options.clone())
+			.await
+			.unwrap();
+		assert!(plan
+			.changes
+			.iter()
+			.all(|change| change.action == PlannedAction::Create));
+
+		let imported_user = target
+			.user()
+			.find_unique(user::username::equals(String::from("oromei")))
+			.exec()
+			.await
+			.unwrap()
+			.expect("The bundled user should have been imported");
+		assert!(imported_user.is_server_owner);
+		assert_eq!(imported_user.hashed_password, "$2b$12$hash");
+
+		// Importing the same bundle again must not duplicate anything
+		let plan = import_server_bundle(&target, bundle, import_options)
+			.await
+			.unwrap();
+		assert!(plan
+			.changes
+			.iter()
+			.all(|change| change.action == PlannedAction::Skip));
+
+		assert_eq!(target.user().count(vec![]).exec().await.unwrap(), 1);
+		assert_eq!(target.library().count(vec![]).exec().await.unwrap(), 1);
+		let notifiers = target.notifier().find_many(vec![]).exec().await.unwrap();
+		assert_eq!(notifiers.len(), 1);
+		assert_eq!(
+			notifiers[0].config,
+			br#"{"webhook_url":"https://discord.com"}"#.to_vec()
+		);
+	}
+}
@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::trace;
 
 use crate::{config::StumpConfig, prisma};
@@ -39,6 +39,19 @@ pub async fn create_client(config: &StumpConfig) -> prisma::PrismaClient {
 	}
 }
 
+/// Returns the path of the database file [create_client] connects to for the given config
+pub fn database_path(config: &StumpConfig) -> PathBuf {
+	if let Some(path) = config.db_path.as_ref() {
+		Path::new(path).join("stump.db")
+	} else if config.profile == "release" {
+		config.get_config_dir().join("stump.db")
+	} else {
+		Path::new(env!("CARGO_MANIFEST_DIR"))
+			.join("prisma")
+			.join("dev.db")
+	}
+}
+
 pub async fn create_client_with_url(url: &str) -> prisma::PrismaClient {
 	prisma::new_client_with_url(url)
 		.await
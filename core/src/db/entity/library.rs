@@ -45,6 +45,10 @@ pub struct Library {
 	pub path: String,
 	/// The status of the library since last scan or access. ex: "READY" or "MISSING"
 	pub status: String,
+	/// Whether the library is archived (i.e. offline). Archived libraries are never scanned,
+	/// and their files are expected to be unavailable.
+	#[serde(default)]
+	pub archived: bool,
 	// The date in which the library was last updated. This is usually after a scan. ex: "2022-04-20 04:20:69"
 	pub updated_at: String,
 	/// The series that are in this library. Will be `None` only if the relation is not loaded.
@@ -105,7 +109,7 @@ impl fmt::Display for LibraryPattern {
 	}
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Type, ToSchema, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Type, ToSchema)]
 pub struct LibraryOptions {
 	// Note: this isn't really an Option, but I felt it was a little verbose
 	// to create an entirely new struct Create/UpdateLibraryOptions for just one
@@ -115,11 +119,37 @@ pub struct LibraryOptions {
 	pub hard_delete_conversions: bool,
 	pub library_pattern: LibraryPattern,
 	pub thumbnail_config: Option<ImageProcessorOptions>,
+	/// Whether metadata (e.g. ComicInfo.xml) should be extracted from books during scans
+	#[serde(default = "default_true")]
+	pub extract_metadata: bool,
+	/// Whether books should be hashed during scans. Hashes are only used to identify
+	/// books, so large libraries on slow storage may want to skip them.
+	#[serde(default = "default_true")]
+	pub generate_file_hashes: bool,
 	// TODO: don't make Option after pcr supports nested create
 	// https://github.com/Brendonovich/prisma-client-rust/issues/44
 	pub library_id: Option<String>,
 }
 
+impl Default for LibraryOptions {
+	fn default() -> Self {
+		Self {
+			id: None,
+			convert_rar_to_zip: false,
+			hard_delete_conversions: false,
+			library_pattern: LibraryPattern::default(),
+			thumbnail_config: None,
+			extract_metadata: true,
+			generate_file_hashes: true,
+			library_id: None,
+		}
+	}
+}
+
+fn default_true() -> bool {
+	true
+}
+
 impl LibraryOptions {
 	pub fn is_collection_based(&self) -> bool {
 		self.library_pattern == LibraryPattern::CollectionBased
@@ -182,6 +212,8 @@ impl From<prisma::library_options::Data> for LibraryOptions {
 			thumbnail_config: data.thumbnail_config.map(|config| {
 				ImageProcessorOptions::try_from(config).unwrap_or_default()
 			}),
+			extract_metadata: data.extract_metadata,
+			generate_file_hashes: data.generate_file_hashes,
 			library_id: data.library_id,
 		}
 	}
@@ -197,6 +229,8 @@ impl From<&prisma::library_options::Data> for LibraryOptions {
 			thumbnail_config: data.thumbnail_config.clone().map(|config| {
 				ImageProcessorOptions::try_from(config).unwrap_or_default()
 			}),
+			extract_metadata: data.extract_metadata,
+			generate_file_hashes: data.generate_file_hashes,
 			library_id: data.library_id.clone(),
 		}
 	}
@@ -226,6 +260,7 @@ impl From<prisma::library::Data> for Library {
 			emoji: data.emoji,
 			path: data.path,
 			status: data.status,
+			archived: data.archived,
 			updated_at: data.updated_at.to_rfc3339(),
 			series,
 			tags,
@@ -258,6 +293,7 @@ impl From<(prisma::library::Data, prisma::library_options::Data)> for Library {
 			emoji: library.emoji,
 			path: library.path,
 			status: library.status,
+			archived: library.archived,
 			updated_at: library.updated_at.to_rfc3339(),
 			series,
 			tags,
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -15,7 +15,9 @@ pub struct LogMetadata {
 	pub modified: String,
 }
 
-#[derive(Clone, Default, Serialize, Deserialize, Type, ToSchema)]
+#[derive(
+	Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema,
+)]
 pub enum LogLevel {
 	#[serde(rename = "ERROR")]
 	Error,
@@ -39,6 +41,49 @@ impl std::fmt::Display for LogLevel {
 	}
 }
 
+impl LogLevel {
+	/// Returns how verbose the level is, where ERROR is the least verbose
+	fn verbosity(&self) -> u8 {
+		match self {
+			LogLevel::Error => 0,
+			LogLevel::Warn => 1,
+			LogLevel::Info => 2,
+			LogLevel::Debug => 3,
+		}
+	}
+
+	/// Whether logs of the given level are captured at this level, e.g. WARN captures ERROR
+	/// and WARN logs, but not INFO or DEBUG logs.
+	pub fn includes(&self, level: LogLevel) -> bool {
+		level.verbosity() <= self.verbosity()
+	}
+}
+
+impl FromStr for LogLevel {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_uppercase().as_str() {
+			"ERROR" => Ok(LogLevel::Error),
+			"WARN" => Ok(LogLevel::Warn),
+			"INFO" => Ok(LogLevel::Info),
+			"DEBUG" => Ok(LogLevel::Debug),
+			_ => Err(format!("Invalid log level: {}", s)),
+		}
+	}
+}
+
+impl From<LogLevel> for tracing::Level {
+	fn from(level: LogLevel) -> Self {
+		match level {
+			LogLevel::Error => tracing::Level::ERROR,
+			LogLevel::Warn => tracing::Level::WARN,
+			LogLevel::Info => tracing::Level::INFO,
+			LogLevel::Debug => tracing::Level::DEBUG,
+		}
+	}
+}
+
 #[derive(Clone, Serialize, Deserialize, Default, Type, ToSchema)]
 pub struct Log {
 	pub id: String,
@@ -51,7 +96,9 @@ pub struct Log {
 impl From<CoreEvent> for Log {
 	fn from(event: CoreEvent) -> Self {
 		match event {
-			CoreEvent::JobFailed { job_id, message } => Self {
+			CoreEvent::JobFailed {
+				job_id, message, ..
+			} => Self {
 				level: LogLevel::Error,
 				message,
 				job_id: Some(job_id),
@@ -71,3 +118,24 @@ impl From<CoreEvent> for Log {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_log_level_includes() {
+		assert!(LogLevel::Warn.includes(LogLevel::Error));
+		assert!(LogLevel::Warn.includes(LogLevel::Warn));
+		assert!(!LogLevel::Warn.includes(LogLevel::Info));
+		assert!(LogLevel::Debug.includes(LogLevel::Info));
+		assert!(!LogLevel::Error.includes(LogLevel::Debug));
+	}
+
+	#[test]
+	fn test_log_level_from_str() {
+		assert_eq!(LogLevel::from_str("debug"), Ok(LogLevel::Debug));
+		assert_eq!(LogLevel::from_str("WARN"), Ok(LogLevel::Warn));
+		assert!(LogLevel::from_str("verbose").is_err());
+	}
+}
@@ -14,6 +14,16 @@ pub struct Notifier {
 	config: NotifierConfig,
 }
 
+impl Notifier {
+	pub fn id(&self) -> i32 {
+		self.id
+	}
+
+	pub fn notifier_type(&self) -> NotifierType {
+		self._type
+	}
+}
+
 #[derive(Serialize, Deserialize, ToSchema, Type)]
 #[serde(untagged)]
 pub enum NotifierConfig {
@@ -32,7 +42,7 @@ impl NotifierConfig {
 	}
 }
 
-#[derive(Serialize, Deserialize, ToSchema, Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, Type)]
 pub enum NotifierType {
 	#[serde(rename = "DISCORD")]
 	Discord,
@@ -1,10 +1,12 @@
+use std::{fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use utoipa::ToSchema;
 
 use crate::prisma::{job_schedule_config, server_config};
 
-use super::Library;
+use super::{Library, LogLevel};
 
 #[derive(Debug, Clone, Deserialize, Serialize, Type, ToSchema)]
 pub struct ServerConfig {
@@ -17,6 +19,64 @@ pub struct JobSchedulerConfig {
 	id: String,
 	interval_secs: i32,
 	excluded_libraries: Vec<Library>,
+	/// The datetime stamp of when the scheduled scans were last enqueued
+	last_run_at: Option<String>,
+	catch_up_policy: ScheduleCatchUpPolicy,
+	/// The number of runs missed while the server was last down
+	missed_runs: i32,
+	/// The level of logs captured for scheduled scans, or the configured default if not set
+	log_level: Option<LogLevel>,
+}
+
+/// What to do, on startup, about the scheduled runs missed while the server was down
+#[derive(
+	Serialize, Deserialize, Debug, Default, PartialEq, Eq, Copy, Clone, Type, ToSchema,
+)]
+pub enum ScheduleCatchUpPolicy {
+	/// Don't run the missed runs, and wait for the next scheduled run
+	#[serde(rename = "SKIP")]
+	Skip,
+	/// Run once to make up for any number of missed runs
+	#[default]
+	#[serde(rename = "RUN_ONCE")]
+	RunOnce,
+	/// Run once for every missed run
+	#[serde(rename = "RUN_ALL")]
+	RunAll,
+}
+
+impl ScheduleCatchUpPolicy {
+	/// Returns how many catch-up runs should be enqueued for the given number of missed runs
+	pub fn catch_up_runs(&self, missed_runs: u64) -> u64 {
+		match self {
+			ScheduleCatchUpPolicy::Skip => 0,
+			ScheduleCatchUpPolicy::RunOnce => missed_runs.min(1),
+			ScheduleCatchUpPolicy::RunAll => missed_runs,
+		}
+	}
+}
+
+impl fmt::Display for ScheduleCatchUpPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ScheduleCatchUpPolicy::Skip => write!(f, "SKIP"),
+			ScheduleCatchUpPolicy::RunOnce => write!(f, "RUN_ONCE"),
+			ScheduleCatchUpPolicy::RunAll => write!(f, "RUN_ALL"),
+		}
+	}
+}
+
+impl FromStr for ScheduleCatchUpPolicy {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_uppercase().as_str() {
+			"SKIP" => Ok(ScheduleCatchUpPolicy::Skip),
+			"RUN_ONCE" => Ok(ScheduleCatchUpPolicy::RunOnce),
+			"RUN_ALL" => Ok(ScheduleCatchUpPolicy::RunAll),
+			_ => Err(format!("Invalid schedule catch-up policy: {}", s)),
+		}
+	}
 }
 
 impl From<server_config::Data> for ServerConfig {
@@ -44,6 +104,24 @@ impl From<job_schedule_config::Data> for JobSchedulerConfig {
 				.into_iter()
 				.map(Library::from)
 				.collect(),
+			last_run_at: data.last_run_at.map(|dt| dt.to_rfc3339()),
+			catch_up_policy: ScheduleCatchUpPolicy::from_str(&data.catch_up_policy)
+				.unwrap_or_default(),
+			missed_runs: data.missed_runs,
+			log_level: data.log_level.and_then(|level| level.parse().ok()),
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_catch_up_runs() {
+		assert_eq!(ScheduleCatchUpPolicy::Skip.catch_up_runs(3), 0);
+		assert_eq!(ScheduleCatchUpPolicy::RunOnce.catch_up_runs(3), 1);
+		assert_eq!(ScheduleCatchUpPolicy::RunOnce.catch_up_runs(0), 0);
+		assert_eq!(ScheduleCatchUpPolicy::RunAll.catch_up_runs(3), 3);
+	}
+}
@@ -0,0 +1,69 @@
+use std::{
+	ffi::OsString,
+	fs::{File, OpenOptions},
+	path::PathBuf,
+};
+
+use fs2::FileExt;
+
+use crate::{config::StumpConfig, CoreError, CoreResult};
+
+use super::database_path;
+
+/// An exclusive lock on the database, held by the server for as long as it runs. Offline
+/// tools which write to the database (e.g. the CLI) take the same lock, so they can't run
+/// alongside the server. The lock is released when this is dropped, or when the process
+/// exits for any reason.
+pub struct DatabaseLock {
+	_file: File,
+}
+
+/// Returns the path of the lock file, which sits alongside the database. SQLite manages its
+/// own locks on the database file itself, so a separate file is locked instead.
+fn lock_path(config: &StumpConfig) -> PathBuf {
+	let mut path = OsString::from(database_path(config));
+	path.push(".lock");
+	PathBuf::from(path)
+}
+
+/// Takes the exclusive database lock without waiting for it, failing with
+/// [CoreError::DatabaseLocked] if another process holds it.
+pub fn lock_database(config: &StumpConfig) -> CoreResult<DatabaseLock> {
+	let path = lock_path(config);
+	let file = OpenOptions::new()
+		.create(true)
+		.truncate(false)
+		.write(true)
+		.open(&path)?;
+
+	match file.try_lock_exclusive() {
+		Ok(()) => Ok(DatabaseLock { _file: file }),
+		Err(error) if error.kind() == fs2::lock_contended_error().kind() => Err(
+			CoreError::DatabaseLocked(path.to_string_lossy().to_string()),
+		),
+		Err(error) => Err(error.into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_database_lock_is_exclusive() {
+		let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+		let config = StumpConfig {
+			db_path: Some(dir.path().to_string_lossy().to_string()),
+			..StumpConfig::debug()
+		};
+
+		let lock = lock_database(&config).expect("Failed to take the database lock");
+		assert!(matches!(
+			lock_database(&config),
+			Err(CoreError::DatabaseLocked(_))
+		));
+
+		drop(lock);
+		assert!(lock_database(&config).is_ok());
+	}
+}
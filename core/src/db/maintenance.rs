@@ -0,0 +1,236 @@
+use prisma_client_rust::raw;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::{
+	db::entity::LogLevel,
+	job::{Job, JobError, JobTrait, JobUpdate, WorkerCtx},
+	prisma::{log, PrismaClient},
+	Ctx,
+};
+
+pub const DATABASE_MAINTENANCE_JOB_NAME: &str = "database_maintenance";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub enum DatabaseMaintenanceStage {
+	/// A thorough check of the database for corruption (`PRAGMA integrity_check`)
+	#[serde(rename = "INTEGRITY_CHECK")]
+	IntegrityCheck,
+	/// A faster, less thorough alternative to the integrity check (`PRAGMA quick_check`)
+	#[serde(rename = "QUICK_CHECK")]
+	QuickCheck,
+	/// Gathers statistics used by the query planner (`ANALYZE`)
+	#[serde(rename = "ANALYZE")]
+	Analyze,
+	/// Lets SQLite apply any optimizations it deems worthwhile (`PRAGMA optimize`)
+	#[serde(rename = "OPTIMIZE")]
+	Optimize,
+	/// Rebuilds the database file to reclaim unused space (`VACUUM`). This can take a
+	/// long time for large databases, and temporarily needs up to twice the disk space.
+	#[serde(rename = "VACUUM")]
+	Vacuum,
+}
+
+impl DatabaseMaintenanceStage {
+	/// The stages run when none are explicitly selected. VACUUM is deliberately excluded,
+	/// since it is expensive and rarely necessary.
+	pub fn defaults() -> Vec<Self> {
+		vec![Self::IntegrityCheck, Self::Analyze, Self::Optimize]
+	}
+
+	fn statement(&self) -> &'static str {
+		match self {
+			Self::IntegrityCheck => "PRAGMA integrity_check;",
+			Self::QuickCheck => "PRAGMA quick_check;",
+			Self::Analyze => "ANALYZE;",
+			Self::Optimize => "PRAGMA optimize;",
+			Self::Vacuum => "VACUUM;",
+		}
+	}
+
+	fn is_check(&self) -> bool {
+		matches!(self, Self::IntegrityCheck | Self::QuickCheck)
+	}
+}
+
+impl std::fmt::Display for DatabaseMaintenanceStage {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::IntegrityCheck => write!(f, "INTEGRITY_CHECK"),
+			Self::QuickCheck => write!(f, "QUICK_CHECK"),
+			Self::Analyze => write!(f, "ANALYZE"),
+			Self::Optimize => write!(f, "OPTIMIZE"),
+			Self::Vacuum => write!(f, "VACUUM"),
+		}
+	}
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRow {
+	#[serde(alias = "quick_check")]
+	integrity_check: String,
+}
+
+#[derive(Debug)]
+struct StageResult {
+	success: bool,
+	/// The output of the stage. For the integrity checks, this is every reported problem.
+	output: Vec<String>,
+	ms_elapsed: u64,
+}
+
+/// Returns true if the output of an integrity (or quick) check indicates a healthy
+/// database. SQLite reports a single `ok` row when no problems are found.
+fn check_passed(output: &[String]) -> bool {
+	matches!(output, [row] if row == "ok")
+}
+
+async fn run_stage(
+	client: &PrismaClient,
+	stage: DatabaseMaintenanceStage,
+) -> StageResult {
+	let start = std::time::Instant::now();
+
+	let (success, output) = if stage.is_check() {
+		match client
+			._query_raw::<CheckRow>(raw!(stage.statement()))
+			.exec()
+			.await
+		{
+			Ok(rows) => {
+				let output = rows
+					.into_iter()
+					.map(|row| row.integrity_check)
+					.collect::<Vec<String>>();
+				(check_passed(&output), output)
+			},
+			Err(error) => (false, vec![error.to_string()]),
+		}
+	} else {
+		match client._execute_raw(raw!(stage.statement())).exec().await {
+			Ok(_) => (true, vec![]),
+			Err(error) => (false, vec![error.to_string()]),
+		}
+	};
+
+	StageResult {
+		success,
+		output,
+		ms_elapsed: start.elapsed().as_millis() as u64,
+	}
+}
+
+/// A job which checks the integrity of the database and optimizes it. Since the job
+/// manager only runs a single job at a time, no other job can write to the database
+/// while it runs.
+#[derive(Serialize, Deserialize)]
+pub struct DatabaseMaintenanceJob {
+	pub stages: Vec<DatabaseMaintenanceStage>,
+}
+
+impl DatabaseMaintenanceJob {
+	pub fn new(
+		stages: Vec<DatabaseMaintenanceStage>,
+	) -> Box<Job<DatabaseMaintenanceJob>> {
+		Job::new(Self { stages })
+	}
+}
+
+#[async_trait::async_trait]
+impl JobTrait for DatabaseMaintenanceJob {
+	fn name(&self) -> &'static str {
+		DATABASE_MAINTENANCE_JOB_NAME
+	}
+
+	fn description(&self) -> Option<Box<&str>> {
+		None
+	}
+
+	async fn validate(&self, _ctx: &Ctx) -> Result<(), JobError> {
+		if self.stages.is_empty() {
+			return Err(JobError::InvalidJob(String::from(
+				"At least one database maintenance stage must be selected",
+			)));
+		}
+
+		Ok(())
+	}
+
+	async fn run(&mut self, ctx: WorkerCtx) -> Result<u64, JobError> {
+		let client = ctx.core_ctx.get_db();
+		let task_count = self.stages.len() as u64;
+		ctx.emit_job_started(task_count, Some(String::from("Starting maintenance")));
+
+		let mut completed_task_count = 0;
+		for (index, stage) in self.stages.iter().enumerate() {
+			ctx.emit_progress(JobUpdate::tick(
+				ctx.job_id.clone(),
+				index as u64,
+				task_count,
+				Some(format!("Running {}", stage)),
+			));
+
+			let result = run_stage(client, *stage).await;
+			tracing::debug!(?result, "Database maintenance stage finished");
+
+			let (level, message) = if result.success {
+				(
+					LogLevel::Info,
+					format!("{} completed in {}ms", stage, result.ms_elapsed),
+				)
+			} else {
+				(
+					LogLevel::Error,
+					format!("{} failed:\n{}", stage, result.output.join("\n")),
+				)
+			};
+			let log_result = client
+				.log()
+				.create(
+					message.clone(),
+					vec![
+						log::job_id::set(Some(ctx.job_id.clone())),
+						log::level::set(level.to_string()),
+					],
+				)
+				.exec()
+				.await;
+			if let Err(error) = log_result {
+				tracing::error!(?error, "Failed to persist maintenance stage log");
+			}
+
+			if !result.success {
+				if stage.is_check() {
+					// Optimizing a corrupt database would only make recovery harder
+					tracing::error!(output = ?result.output, "Database corruption detected!");
+					return Err(JobError::Unknown(message));
+				}
+				tracing::warn!(message, "Database maintenance stage failed");
+				continue;
+			}
+
+			completed_task_count += 1;
+		}
+
+		Ok(completed_task_count)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_passed() {
+		assert!(check_passed(&[String::from("ok")]));
+		assert!(!check_passed(&[]));
+		assert!(!check_passed(&[String::from(
+			"*** in database main ***\nPage 4: never used"
+		)]));
+		assert!(!check_passed(&[
+			String::from("ok"),
+			String::from("row 12 missing from index media_path_idx"),
+		]));
+	}
+}
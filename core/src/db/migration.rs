@@ -1,10 +1,213 @@
-use crate::{error::CoreResult, prisma, CoreError};
+use prisma_client_rust::raw;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
 
-pub async fn run_migrations(client: &prisma::PrismaClient) -> CoreResult<()> {
+use crate::{
+	config::StumpConfig, db::common::CountQueryReturn, error::CoreResult, prisma,
+	CoreError,
+};
+
+// Generated by the build script from the contents of `prisma/migrations`. A migration is
+// considered destructive when its migration.sql contains a `-- stump:destructive` line.
+include!(concat!(env!("OUT_DIR"), "/bundled_migrations.rs"));
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub enum MigrationRisk {
+	/// The migration only adds to the schema (or rewrites tables without losing data)
+	#[serde(rename = "ADDITIVE")]
+	Additive,
+	/// The migration drops or rewrites data that cannot be recovered without a backup
+	#[serde(rename = "DESTRUCTIVE")]
+	Destructive,
+}
+
+impl From<bool> for MigrationRisk {
+	fn from(is_destructive: bool) -> Self {
+		if is_destructive {
+			MigrationRisk::Destructive
+		} else {
+			MigrationRisk::Additive
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub enum MigrationState {
+	#[serde(rename = "APPLIED")]
+	Applied,
+	#[serde(rename = "FAILED")]
+	Failed,
+	#[serde(rename = "ROLLED_BACK")]
+	RolledBack,
+}
+
+#[derive(Debug, Deserialize)]
+struct MigrationHistoryRow {
+	migration_name: String,
+	started_at: String,
+	finished_at: Option<String>,
+	rolled_back_at: Option<String>,
+	logs: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct AppliedMigration {
+	/// The name of the migration, e.g. 20240209201618_notifiers
+	pub name: String,
+	/// The state of the migration, e.g. APPLIED or FAILED
+	pub state: MigrationState,
+	/// The datetime stamp of when the migration was started
+	pub started_at: String,
+	/// The datetime stamp of when the migration finished, if it did
+	pub finished_at: Option<String>,
+	/// The logs recorded for the migration, which will contain the error if it failed
+	pub logs: Option<String>,
+}
+
+impl From<MigrationHistoryRow> for AppliedMigration {
+	fn from(row: MigrationHistoryRow) -> Self {
+		let state = if row.rolled_back_at.is_some() {
+			MigrationState::RolledBack
+		} else if row.finished_at.is_none() {
+			MigrationState::Failed
+		} else {
+			MigrationState::Applied
+		};
+
+		Self {
+			name: row.migration_name,
+			state,
+			started_at: row.started_at,
+			finished_at: row.finished_at,
+			logs: row.logs,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct PendingMigration {
+	/// The name of the migration, e.g. 20240209201618_notifiers
+	pub name: String,
+	/// Whether the migration is additive or destructive, based on its annotations
+	pub risk: MigrationRisk,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, ToSchema)]
+pub struct MigrationStatus {
+	/// Whether the database is fresh, i.e. it has no migration history table
+	pub is_fresh: bool,
+	/// The migration history recorded in the database, in the order it was applied
+	pub applied: Vec<AppliedMigration>,
+	/// The migrations bundled with this version of Stump which have not been applied
+	pub pending: Vec<PendingMigration>,
+}
+
+impl MigrationStatus {
+	/// Returns true if any migration in the history failed and was not rolled back
+	pub fn has_failed(&self) -> bool {
+		self.applied
+			.iter()
+			.any(|migration| migration.state == MigrationState::Failed)
+	}
+
+	/// Returns the pending migrations which are annotated as destructive
+	pub fn pending_destructive(&self) -> Vec<&PendingMigration> {
+		self.pending
+			.iter()
+			.filter(|migration| migration.risk == MigrationRisk::Destructive)
+			.collect()
+	}
+
+	/// Returns the pending destructive migrations which need explicit consent before being
+	/// applied. A fresh database has no migration history and no data to lose, so every
+	/// bundled migration is pending but none of them need consent.
+	pub fn destructive_requiring_consent(&self) -> Vec<&PendingMigration> {
+		if self.is_fresh {
+			return vec![];
+		}
+		self.pending_destructive()
+	}
+}
+
+/// Returns the migration history of the database alongside any bundled migrations
+/// which have yet to be applied.
+pub async fn get_migration_status(
+	client: &prisma::PrismaClient,
+) -> CoreResult<MigrationStatus> {
+	// The history table won't exist on a fresh database, nor when the schema was pushed
+	// directly (which is what debug builds do)
+	let has_history_table = client
+		._query_raw::<CountQueryReturn>(raw!(
+			"SELECT COUNT(*) as count FROM sqlite_master WHERE type='table' AND name='_prisma_migrations'"
+		))
+		.exec()
+		.await?
+		.first()
+		.map(|row| row.count > 0)
+		.unwrap_or(false);
+
+	let history = if has_history_table {
+		let rows: Vec<MigrationHistoryRow> = client
+			._query_raw(raw!(
+				"SELECT migration_name, started_at, finished_at, rolled_back_at, logs FROM _prisma_migrations ORDER BY started_at ASC"
+			))
+			.exec()
+			.await?;
+		Some(rows)
+	} else {
+		None
+	};
+
+	Ok(resolve_migration_status(history, BUNDLED_MIGRATIONS))
+}
+
+/// Resolves the status of the bundled migrations against the recorded history, where
+/// `None` means the database has no history table at all.
+fn resolve_migration_status(
+	history: Option<Vec<MigrationHistoryRow>>,
+	bundled: &[(&str, bool)],
+) -> MigrationStatus {
+	let is_fresh = history.is_none();
+	let applied = history
+		.unwrap_or_default()
+		.into_iter()
+		.map(AppliedMigration::from)
+		.collect::<Vec<AppliedMigration>>();
+
+	let pending = bundled
+		.iter()
+		.filter(|(name, _)| {
+			!applied.iter().any(|migration| {
+				migration.name == *name && migration.state == MigrationState::Applied
+			})
+		})
+		.map(|(name, is_destructive)| PendingMigration {
+			name: name.to_string(),
+			risk: MigrationRisk::from(*is_destructive),
+		})
+		.collect();
+
+	MigrationStatus {
+		is_fresh,
+		applied,
+		pending,
+	}
+}
+
+/// Runs any pending migrations. Before applying anything, a pre-flight check is performed
+/// which refuses to continue when a pending migration is annotated as destructive, unless
+/// [StumpConfig::apply_destructive_migrations] is set. The check is skipped for a fresh
+/// database, see [MigrationStatus::destructive_requiring_consent].
+pub async fn run_migrations(
+	client: &prisma::PrismaClient,
+	config: &StumpConfig,
+) -> CoreResult<()> {
 	tracing::info!("Running migrations...");
 
 	#[cfg(debug_assertions)]
 	{
+		let _ = config;
 		let mut builder = client._db_push();
 
 		if std::env::var("FORCE_RESET_DB")
@@ -24,10 +227,146 @@ pub async fn run_migrations(client: &prisma::PrismaClient) -> CoreResult<()> {
 	}
 
 	#[cfg(not(debug_assertions))]
-	client
-		._migrate_deploy()
-		.await
-		.map_err(|e| CoreError::MigrationError(e.to_string()))?;
+	{
+		let status = get_migration_status(client).await?;
+		tracing::info!(
+			applied = status.applied.len(),
+			pending = status.pending.len(),
+			"Determined migration status"
+		);
+
+		let pending_destructive = status.destructive_requiring_consent();
+		if !pending_destructive.is_empty() && !config.apply_destructive_migrations {
+			let names = pending_destructive
+				.iter()
+				.map(|migration| migration.name.as_str())
+				.collect::<Vec<_>>()
+				.join(", ");
+			return Err(CoreError::MigrationError(format!(
+				"Refusing to apply destructive migration(s) without explicit consent: {}. Back up your database and restart with --apply-destructive (or set {}=true)",
+				names,
+				crate::config::env_keys::APPLY_DESTRUCTIVE_MIGRATIONS_KEY
+			)));
+		}
+
+		client
+			._migrate_deploy()
+			.await
+			.map_err(|e| CoreError::MigrationError(e.to_string()))?;
+	}
 
 	Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Ctx;
+
+	fn history_row(name: &str, finished: bool) -> MigrationHistoryRow {
+		MigrationHistoryRow {
+			migration_name: name.to_string(),
+			started_at: "2024-02-09T20:16:18+00:00".to_string(),
+			finished_at: finished.then(|| "2024-02-09T20:16:19+00:00".to_string()),
+			rolled_back_at: None,
+			logs: None,
+		}
+	}
+
+	#[test]
+	fn test_resolve_pending_migrations() {
+		let bundled = [
+			("1_init", false),
+			("2_add_column", false),
+			("3_drop_table", true),
+		];
+		let history = vec![history_row("1_init", true)];
+
+		let status = resolve_migration_status(Some(history), &bundled);
+
+		assert!(!status.has_failed());
+		assert_eq!(status.applied.len(), 1);
+		assert_eq!(
+			status
+				.pending
+				.iter()
+				.map(|migration| migration.name.as_str())
+				.collect::<Vec<_>>(),
+			vec!["2_add_column", "3_drop_table"]
+		);
+		assert_eq!(
+			status
+				.pending_destructive()
+				.iter()
+				.map(|migration| migration.name.as_str())
+				.collect::<Vec<_>>(),
+			vec!["3_drop_table"]
+		);
+	}
+
+	#[test]
+	fn test_failed_migration_is_still_pending() {
+		let bundled = [("1_init", false), ("2_add_column", false)];
+		let history = vec![
+			history_row("1_init", true),
+			history_row("2_add_column", false),
+		];
+
+		let status = resolve_migration_status(Some(history), &bundled);
+
+		assert!(status.has_failed());
+		assert_eq!(status.applied[1].state, MigrationState::Failed);
+		assert_eq!(status.pending.len(), 1);
+		assert_eq!(status.pending[0].risk, MigrationRisk::Additive);
+	}
+
+	#[test]
+	fn test_fresh_database_needs_no_consent() {
+		let bundled = [("1_init", false), ("2_drop_table", true)];
+
+		let status = resolve_migration_status(None, &bundled);
+
+		assert!(status.is_fresh);
+		assert_eq!(status.pending.len(), 2);
+		assert_eq!(status.pending_destructive().len(), 1);
+		assert!(status.destructive_requiring_consent().is_empty());
+
+		// An empty history table is not a fresh database, e.g. the history was cleared
+		let status = resolve_migration_status(Some(vec![]), &bundled);
+		assert!(!status.is_fresh);
+		assert_eq!(status.destructive_requiring_consent().len(), 1);
+
+		let status =
+			resolve_migration_status(Some(vec![history_row("1_init", true)]), &bundled);
+		assert_eq!(
+			status
+				.destructive_requiring_consent()
+				.iter()
+				.map(|migration| migration.name.as_str())
+				.collect::<Vec<_>>(),
+			vec!["2_drop_table"]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_get_migration_status_checks_history_table() {
+		let (client, _dir) = Ctx::mock_db().await;
+
+		// Pushing the schema directly doesn't record any migration history
+		let status = get_migration_status(&client).await.unwrap();
+		assert!(status.is_fresh);
+		assert!(status.applied.is_empty());
+
+		client
+			._execute_raw(raw!(
+				"CREATE TABLE _prisma_migrations (id TEXT PRIMARY KEY NOT NULL, checksum TEXT NOT NULL, finished_at DATETIME, migration_name TEXT NOT NULL, logs TEXT, rolled_back_at DATETIME, started_at DATETIME NOT NULL DEFAULT current_timestamp, applied_steps_count INTEGER UNSIGNED NOT NULL DEFAULT 0)"
+			))
+			.exec()
+			.await
+			.unwrap();
+
+		let status = get_migration_status(&client).await.unwrap();
+		assert!(!status.is_fresh);
+		assert!(status.applied.is_empty());
+	}
+}
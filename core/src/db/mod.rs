@@ -1,15 +1,24 @@
+pub mod bundle;
 mod client;
 mod common;
 pub(crate) mod dao;
 pub mod entity;
 pub mod filter;
+mod lock;
+pub mod maintenance;
 pub mod migration;
 pub mod query;
+pub mod reading_stats;
+mod transaction;
 
 pub use dao::*;
 
-pub use client::{create_client, create_client_with_url, create_test_client};
+pub use client::{
+	create_client, create_client_with_url, create_test_client, database_path,
+};
 pub use common::{
 	CountQueryReturn, DBPragma, JournalMode, JournalModeQueryResult, PrismaCountTrait,
 };
 pub use entity::FileStatus;
+pub use lock::{lock_database, DatabaseLock};
+pub use transaction::{run_in_transaction, TransactionFailure};
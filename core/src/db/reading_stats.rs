@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+
+use prisma_client_rust::{
+	chrono::{DateTime, NaiveDate, Utc},
+	Direction,
+};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::{
+	db::entity::Series,
+	prisma::{library, media, read_progress, series, PrismaClient},
+	CoreResult,
+};
+
+/// The number of unstarted series suggested alongside the reading statistics
+const SUGGESTED_SERIES_COUNT: i64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct ReadingStats {
+	/// The start of the range the statistics cover
+	pub from: String,
+	/// The end of the range the statistics cover
+	pub to: String,
+	/// The number of books completed within the range
+	pub books_finished: u64,
+	/// The number of pages read within the range. Only the latest position in each book is
+	/// stored, so this counts the pages up to that position for every book read within
+	/// the range (or all of the pages, for completed books).
+	pub pages_read: u64,
+	/// The longest run of consecutive days with reading activity within the range
+	pub longest_streak_days: u32,
+	/// A few recently added series the user has yet to start
+	pub suggested_series: Vec<Series>,
+}
+
+/// Computes the reading statistics of a user between two dates. The suggested series are
+/// limited by the given filters, e.g. the user's age restriction.
+pub async fn get_reading_stats(
+	client: &PrismaClient,
+	user_id: &str,
+	from: DateTime<Utc>,
+	to: DateTime<Utc>,
+	series_filters: Vec<series::WhereParam>,
+) -> CoreResult<ReadingStats> {
+	let progresses = client
+		.read_progress()
+		.find_many(vec![
+			read_progress::user_id::equals(user_id.to_string()),
+			read_progress::updated_at::gte(from.into()),
+			read_progress::updated_at::lte(to.into()),
+		])
+		.with(read_progress::media::fetch())
+		.exec()
+		.await?;
+
+	let mut books_finished = 0;
+	let mut pages_read = 0;
+	let mut active_days = BTreeSet::new();
+	for progress in progresses.iter() {
+		let media_pages = progress
+			.media()
+			.map(|media| media.pages)
+			.unwrap_or_default();
+		if progress.is_completed {
+			books_finished += 1;
+			pages_read += media_pages.max(0) as u64;
+		} else {
+			pages_read += progress.page.clamp(0, media_pages.max(0)) as u64;
+		}
+
+		active_days.insert(progress.updated_at.date_naive());
+		if let Some(completed_at) = progress.completed_at {
+			if completed_at >= from && completed_at <= to {
+				active_days.insert(completed_at.date_naive());
+			}
+		}
+	}
+
+	let suggested_series = client
+		.series()
+		.find_many(
+			vec![
+				series::library::is(vec![library::archived::equals(false)]),
+				series::media::some(vec![]),
+				series::media::none(vec![media::read_progresses::some(vec![
+					read_progress::user_id::equals(user_id.to_string()),
+				])]),
+			]
+			.into_iter()
+			.chain(series_filters)
+			.collect(),
+		)
+		.order_by(series::created_at::order(Direction::Desc))
+		.take(SUGGESTED_SERIES_COUNT)
+		.exec()
+		.await?
+		.into_iter()
+		.map(Series::from)
+		.collect();
+
+	Ok(ReadingStats {
+		from: from.to_rfc3339(),
+		to: to.to_rfc3339(),
+		books_finished,
+		pages_read,
+		longest_streak_days: longest_streak(&active_days),
+		suggested_series,
+	})
+}
+
+/// Returns the length of the longest run of consecutive days
+fn longest_streak(days: &BTreeSet<NaiveDate>) -> u32 {
+	let mut longest = 0;
+	let mut current = 0;
+	let mut previous: Option<NaiveDate> = None;
+	for day in days {
+		current = match previous {
+			Some(previous) if previous.succ_opt() == Some(*day) => current + 1,
+			_ => 1,
+		};
+		longest = longest.max(current);
+		previous = Some(*day);
+	}
+	longest
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn day(day: u32) -> NaiveDate {
+		NaiveDate::from_ymd_opt(2024, 2, day).unwrap()
+	}
+
+	#[test]
+	fn test_longest_streak() {
+		assert_eq!(longest_streak(&BTreeSet::new()), 0);
+		assert_eq!(longest_streak(&BTreeSet::from([day(3)])), 1);
+		assert_eq!(
+			longest_streak(&BTreeSet::from([
+				day(1),
+				day(2),
+				day(5),
+				day(6),
+				day(7),
+				day(9)
+			])),
+			3
+		);
+		// Streaks carry over the end of the month
+		assert_eq!(
+			longest_streak(&BTreeSet::from([
+				day(28),
+				day(29),
+				NaiveDate::from_ymd_opt(2024, 3, 1).unwrap()
+			])),
+			3
+		);
+	}
+}
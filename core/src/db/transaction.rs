@@ -0,0 +1,141 @@
+use std::future::Future;
+
+use prisma_client_rust::{
+	prisma_errors::{
+		common::DatabaseOperationTimeout,
+		query_engine::{InteractiveTransactionError, TransactionWriteConflict},
+	},
+	QueryError,
+};
+
+use crate::prisma::PrismaClient;
+
+/// Runs `operation` within a transaction, which is committed if the operation succeeds and
+/// rolled back if it returns an error. This should be used whenever a create (or update)
+/// spans multiple dependent writes, e.g. a user and their preferences, so that a failure
+/// part way through can't leave orphaned records behind.
+///
+/// The error type is chosen by the caller, so long as query errors can be converted into it.
+/// [TransactionFailure] can be used to classify any failure of the transaction itself.
+pub async fn run_in_transaction<T, E, F, Fut>(
+	client: &PrismaClient,
+	operation: F,
+) -> Result<T, E>
+where
+	F: FnOnce(PrismaClient) -> Fut,
+	Fut: Future<Output = Result<T, E>>,
+	E: From<QueryError> + std::fmt::Debug,
+{
+	let result = client._transaction().run(operation).await;
+
+	if let Err(error) = &result {
+		tracing::debug!(?error, "Transaction was rolled back");
+	}
+
+	result
+}
+
+/// The ways in which a transaction can fail which are unrelated to the writes it performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionFailure {
+	/// The transaction conflicted with another write (or deadlocked). Retrying may succeed.
+	Conflict,
+	/// The transaction (or the wait for the database lock) timed out, or the transaction was
+	/// closed before it could be committed.
+	Timeout,
+}
+
+impl TransactionFailure {
+	/// Returns the [TransactionFailure] represented by the error, if any.
+	pub fn from_query_error(error: &QueryError) -> Option<Self> {
+		if error.is_prisma_error::<TransactionWriteConflict>() {
+			Some(TransactionFailure::Conflict)
+		} else if error.is_prisma_error::<InteractiveTransactionError>()
+			|| error.is_prisma_error::<DatabaseOperationTimeout>()
+		{
+			Some(TransactionFailure::Timeout)
+		} else {
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{prisma::tag, CoreError, Ctx};
+
+	#[tokio::test]
+	async fn test_failed_write_rolls_back_previous_writes() {
+		let (client, _dir) = Ctx::mock_db().await;
+
+		let result: Result<(), CoreError> =
+			run_in_transaction(&client, |tx| async move {
+				tx.tag()
+					.create(String::from("Action"), vec![])
+					.exec()
+					.await?;
+				// Tag names are unique, so the second write fails
+				tx.tag()
+					.create(String::from("Action"), vec![])
+					.exec()
+					.await?;
+				Ok(())
+			})
+			.await;
+		assert!(result.is_err());
+
+		let tag_count = client.tag().count(vec![]).exec().await.unwrap();
+		assert_eq!(tag_count, 0, "The first write should have been rolled back");
+	}
+
+	#[tokio::test]
+	async fn test_successful_writes_are_committed() {
+		let (client, _dir) = Ctx::mock_db().await;
+
+		let result: Result<(), CoreError> =
+			run_in_transaction(&client, |tx| async move {
+				tx.tag()
+					.create(String::from("Action"), vec![])
+					.exec()
+					.await?;
+				tx.tag()
+					.create(String::from("Adventure"), vec![])
+					.exec()
+					.await?;
+				Ok(())
+			})
+			.await;
+		assert!(result.is_ok());
+
+		let tags = client
+			.tag()
+			.find_many(vec![tag::name::in_vec(vec![
+				String::from("Action"),
+				String::from("Adventure"),
+			])])
+			.exec()
+			.await
+			.unwrap();
+		assert_eq!(tags.len(), 2);
+	}
+
+	#[tokio::test]
+	async fn test_operation_error_rolls_back_previous_writes() {
+		let (client, _dir) = Ctx::mock_db().await;
+
+		let result: Result<(), CoreError> =
+			run_in_transaction(&client, |tx| async move {
+				tx.tag()
+					.create(String::from("Action"), vec![])
+					.exec()
+					.await?;
+				Err(CoreError::InternalError(String::from("Injected failure")))
+			})
+			.await;
+		assert!(matches!(result, Err(CoreError::InternalError(_))));
+
+		let tag_count = client.tag().count(vec![]).exec().await.unwrap();
+		assert_eq!(tag_count, 0);
+	}
+}
@@ -21,6 +21,8 @@ pub enum CoreError {
 	RelationNotLoaded(#[from] RelationNotFetchedError),
 	#[error("Migration error: {0}")]
 	MigrationError(String),
+	#[error("The database is in use by another process (lock held on {0})")]
+	DatabaseLocked(String),
 	#[error("Requested resource could not be found: {0}")]
 	NotFound(String),
 	#[error("Requested file could not be found: {0}")]
@@ -34,10 +34,9 @@ impl EventManager {
 		ctx: Ctx,
 		mut request_reciever: mpsc::UnboundedReceiver<InternalCoreTask>,
 	) -> Arc<Self> {
-		let job_manager = JobManager::new(ctx.arced());
-		let this = Arc::new(Self {
-			job_manager: job_manager.arced(),
-		});
+		let job_manager = JobManager::new(ctx.arced()).arced();
+		job_manager.clone().spawn_disk_space_monitor();
+		let this = Arc::new(Self { job_manager });
 
 		let this_cpy = this.clone();
 		tokio::spawn(async move {
@@ -71,6 +70,16 @@ impl EventManager {
 					.send(result)
 					.expect("Fatal error: failed to send cancel job result");
 			},
+			InternalCoreTask::KillJob {
+				job_id,
+				return_sender,
+			} => {
+				let result = self.job_manager.clone().kill_job(job_id).await;
+
+				return_sender
+					.send(result)
+					.expect("Fatal error: failed to send kill job result");
+			},
 			InternalCoreTask::GetJobs(return_sender) => {
 				let job_report = self.clone().job_manager.clone().report().await;
 
@@ -78,6 +87,81 @@ impl EventManager {
 					.send(job_report)
 					.expect("Fatal error: failed to send job report");
 			},
+			InternalCoreTask::GetJobQueue(return_sender) => {
+				let snapshot = self.job_manager.clone().queue_snapshot().await;
+
+				return_sender
+					.send(snapshot)
+					.expect("Fatal error: failed to send job queue snapshot");
+			},
+			InternalCoreTask::GetJobQueueStatus {
+				job_id,
+				return_sender,
+			} => {
+				let status = self.job_manager.clone().job_queue_status(job_id).await;
+
+				return_sender
+					.send(status)
+					.expect("Fatal error: failed to send job queue status");
+			},
+			InternalCoreTask::SetJobTypePaused {
+				job_name,
+				paused,
+				return_sender,
+			} => {
+				let paused_types = self
+					.job_manager
+					.clone()
+					.set_job_type_paused(job_name, paused)
+					.await;
+
+				return_sender
+					.send(paused_types)
+					.expect("Fatal error: failed to send paused job types");
+			},
+			InternalCoreTask::GetPausedJobTypes(return_sender) => {
+				let paused_types = self.job_manager.get_paused_job_types().await;
+
+				return_sender
+					.send(paused_types)
+					.expect("Fatal error: failed to send paused job types");
+			},
+			InternalCoreTask::GetJobManagerConfig(return_sender) => {
+				let config = self.job_manager.config_snapshot().await;
+
+				return_sender
+					.send(config)
+					.expect("Fatal error: failed to send job manager config");
+			},
+			InternalCoreTask::UpdateJobLimits {
+				limits,
+				return_sender,
+			} => {
+				let config = self.job_manager.clone().update_limits(limits).await;
+
+				return_sender
+					.send(config)
+					.expect("Fatal error: failed to send job manager config");
+			},
+			InternalCoreTask::SetQueuePaused {
+				paused,
+				return_sender,
+			} => {
+				let is_paused = self.job_manager.set_queue_paused(paused);
+
+				return_sender
+					.send(is_paused)
+					.expect("Fatal error: failed to send queue paused state");
+			},
+			InternalCoreTask::ResumeHeldJobs => {
+				self.job_manager
+					.clone()
+					.resume_held_jobs()
+					.await
+					.unwrap_or_else(|e| {
+						error!("Failed to resume held jobs: {}", e);
+					});
+			},
 			InternalCoreTask::Shutdown { return_sender } => {
 				self.clone().job_manager.clone().shutdown().await;
 
@@ -4,15 +4,57 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 use tokio::sync::oneshot;
 
-use crate::job::{JobDetail, JobExecutorTrait, JobManagerResult, JobStatus, JobUpdate};
+use crate::{
+	db::entity::{NotifierType, UserPermission},
+	job::{
+		JobDetail, JobExecutorTrait, JobManagerConfig, JobManagerResult,
+		JobQueueSnapshot, JobQueueStatus, JobStatus, JobUpdate, UpdateJobLimits,
+	},
+};
 
 pub enum InternalCoreTask {
 	EnqueueJob(Box<dyn JobExecutorTrait>),
 	GetJobs(oneshot::Sender<JobManagerResult<Vec<JobDetail>>>),
+	GetJobQueue(oneshot::Sender<JobManagerResult<JobQueueSnapshot>>),
+	/// Responds with the status of a job, including its place in the queue if it has yet
+	/// to start, or `None` if the job doesn't exist
+	GetJobQueueStatus {
+		job_id: String,
+		return_sender: oneshot::Sender<JobManagerResult<Option<JobQueueStatus>>>,
+	},
 	CancelJob {
 		job_id: String,
 		return_sender: oneshot::Sender<JobManagerResult<()>>,
 	},
+	/// Kills a running job, which abandons its work immediately rather than finishing it
+	KillJob {
+		job_id: String,
+		return_sender: oneshot::Sender<JobManagerResult<()>>,
+	},
+	/// Pauses (or unpauses) a job type. Newly enqueued jobs of a paused type stay in the
+	/// queue, while jobs of other types continue to run. Responds with the paused types.
+	SetJobTypePaused {
+		job_name: String,
+		paused: bool,
+		return_sender: oneshot::Sender<Vec<String>>,
+	},
+	GetPausedJobTypes(oneshot::Sender<Vec<String>>),
+	/// Responds with the job system configuration currently in effect, including any
+	/// changes made at runtime
+	GetJobManagerConfig(oneshot::Sender<JobManagerConfig>),
+	/// Changes the job limits until the next restart. Responds with the job system
+	/// configuration now in effect.
+	UpdateJobLimits {
+		limits: UpdateJobLimits,
+		return_sender: oneshot::Sender<JobManagerConfig>,
+	},
+	/// Pauses (or resumes) the job queue, without affecting the running job
+	SetQueuePaused {
+		paused: bool,
+		return_sender: oneshot::Sender<bool>,
+	},
+	/// Starts any jobs which were held in the queue during maintenance mode
+	ResumeHeldJobs,
 	Shutdown {
 		return_sender: oneshot::Sender<()>,
 	},
@@ -27,10 +69,52 @@ pub enum ClientResponse {
 pub enum CoreEvent {
 	JobStarted(JobUpdate),
 	JobProgress(JobUpdate),
-	JobComplete(String),
+	/// A job finished. This is the job's terminal event, so its sequence is the highest of
+	/// any event emitted for the job.
+	JobComplete {
+		job_id: String,
+		sequence: u64,
+	},
+	/// A job failed. When emitted by the job's worker, this is the job's terminal event.
 	JobFailed {
 		job_id: String,
 		message: String,
+		#[serde(default)]
+		sequence: u64,
+	},
+	/// A job was killed, abandoning any partial progress. This is the job's terminal event.
+	JobKilled {
+		job_id: String,
+		sequence: u64,
+	},
+	/// A job was left in the queue, rather than started, because the system is under
+	/// pressure. It will be re-checked periodically.
+	JobDeferred {
+		job_id: String,
+		reason: String,
+	},
+	/// Free disk space dropped below the configured threshold, so the job types which write
+	/// to disk were paused until it recovers.
+	DiskSpaceLow {
+		free_mb: u64,
+		threshold_mb: u64,
+		paused_job_types: Vec<String>,
+	},
+	/// Free disk space recovered, so the job types paused because it was low were resumed.
+	DiskSpaceRecovered {
+		free_mb: u64,
+		resumed_job_types: Vec<String>,
+	},
+	/// A library directory wasn't available on startup, and is being retried until the
+	/// warm-up times out.
+	LibraryWaiting {
+		library_id: String,
+		path: String,
+	},
+	/// The startup warm-up finished. Libraries which never became available are flagged,
+	/// and skipped by scheduled scans.
+	WarmUpComplete {
+		unavailable_library_ids: Vec<String>,
 	},
 	CreateEntityFailed {
 		job_id: Option<String>,
@@ -58,9 +142,32 @@ pub enum CoreEvent {
 		id: String,
 	},
 	GeneratedThumbnailBatch(u64),
+	/// A notifier was created. The config is deliberately omitted, since it contains secrets.
+	NotifierCreated {
+		id: i32,
+		notifier_type: NotifierType,
+	},
+	/// A notifier was updated. The config is deliberately omitted, since it contains secrets.
+	NotifierUpdated {
+		id: i32,
+		notifier_type: NotifierType,
+	},
+	NotifierDeleted {
+		id: i32,
+	},
 }
 
 impl CoreEvent {
+	/// The permission a user must have to receive the event, if any
+	pub fn required_permission(&self) -> Option<UserPermission> {
+		match self {
+			CoreEvent::NotifierCreated { .. }
+			| CoreEvent::NotifierUpdated { .. }
+			| CoreEvent::NotifierDeleted { .. } => Some(UserPermission::ManageNotifier),
+			_ => None,
+		}
+	}
+
 	pub fn job_started(
 		job_id: String,
 		current_task: u64,
@@ -73,6 +180,7 @@ impl CoreEvent {
 			task_count,
 			message,
 			status: Some(JobStatus::Running),
+			sequence: 0,
 		})
 	}
 
@@ -88,6 +196,7 @@ impl CoreEvent {
 			task_count,
 			message,
 			status: Some(JobStatus::Running),
+			sequence: 0,
 		})
 	}
 }
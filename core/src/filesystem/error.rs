@@ -52,6 +52,8 @@ pub enum FileError {
 	UnknownError(String),
 	#[error("Failed to read directory")]
 	DirectoryReadError,
+	#[error("The temporary files directory is full ({used_mb}MB used of the {max_mb}MB limit)")]
+	TempDirFull { used_mb: u64, max_mb: u64 },
 }
 
 impl From<FileError> for CoreError {
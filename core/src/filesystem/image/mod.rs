@@ -1,4 +1,5 @@
 mod generic;
+mod page;
 mod process;
 mod thumbnail;
 mod thumbnail_job;
@@ -6,6 +7,7 @@ mod webp;
 
 pub use self::webp::WebpProcessor;
 pub use generic::GenericImageProcessor;
+pub use page::{get_resized_page, PageResizeOptions};
 pub use process::{
 	ImageFormat, ImageProcessor, ImageProcessorOptions, ImageResizeMode,
 	ImageResizeOptions,
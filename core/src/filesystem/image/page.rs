@@ -0,0 +1,251 @@
+use std::{
+	fs,
+	io::Cursor,
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, GenericImageView};
+use tracing::{error, trace};
+
+use crate::{
+	config::StumpConfig,
+	filesystem::{media, ContentType, FileError},
+};
+
+/// The name of the directory, within the cache directory, where resized pages are stored
+const PAGE_CACHE_DIR: &str = "pages";
+/// The maximum size (in bytes) of the resized page cache. Once exceeded, the least recently
+/// written pages are evicted.
+const PAGE_CACHE_MAX_BYTES: u64 = 512 * 1024 * 1024;
+/// The JPEG quality used when only a width is requested
+pub const DEFAULT_PAGE_QUALITY: u8 = 80;
+
+/// The (already validated) options for a resized page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageResizeOptions {
+	/// The width to resize the page to, or `None` to keep the original width
+	pub width: Option<u32>,
+	/// The JPEG quality to encode the page with, between 1 and 100
+	pub quality: u8,
+}
+
+impl PageResizeOptions {
+	/// Creates the options for a request, clamping the width to the closest allowed width and
+	/// the quality to a valid range. Returns `None` when the width cannot be satisfied,
+	/// i.e. when no resize widths are configured.
+	pub fn new(
+		requested_width: Option<u32>,
+		requested_quality: Option<u8>,
+		allowed_widths: &[u32],
+	) -> Option<Self> {
+		let width = match requested_width {
+			Some(width) => Some(clamp_width(width, allowed_widths)?),
+			None => None,
+		};
+
+		Some(Self {
+			width,
+			quality: requested_quality
+				.unwrap_or(DEFAULT_PAGE_QUALITY)
+				.clamp(1, 100),
+		})
+	}
+
+	/// The name of the cached file for the given media page with these options
+	fn cache_file_name(&self, media_id: &str, page: i32) -> String {
+		let width = self
+			.width
+			.map(|width| width.to_string())
+			.unwrap_or_else(|| String::from("original"));
+		format!("{}_{}_w{}_q{}.jpeg", media_id, page, width, self.quality)
+	}
+}
+
+/// Returns the smallest allowed width which is at least as wide as the requested width, or
+/// the largest allowed width if the request exceeds them all.
+fn clamp_width(requested: u32, allowed_widths: &[u32]) -> Option<u32> {
+	allowed_widths
+		.iter()
+		.filter(|width| **width >= requested)
+		.min()
+		.or_else(|| allowed_widths.iter().max())
+		.copied()
+}
+
+/// Gets a page of a media, resized and re-encoded as a JPEG according to the options.
+/// Resized pages are cached on disk, so subsequent requests for the same page with the
+/// same options are served from the cache.
+pub fn get_resized_page(
+	media_id: &str,
+	media_path: &str,
+	page: i32,
+	options: PageResizeOptions,
+	config: &StumpConfig,
+) -> Result<(ContentType, Vec<u8>), FileError> {
+	let cache_dir = config.get_cache_dir().join(PAGE_CACHE_DIR);
+	let cache_path = cache_dir.join(options.cache_file_name(media_id, page));
+
+	if cache_path.exists() {
+		trace!(?cache_path, "Serving resized page from cache");
+		return Ok((ContentType::JPEG, fs::read(cache_path)?));
+	}
+
+	let (_, original) = media::get_page(media_path, page, config)?;
+	let resized = resize_page(&original, options)?;
+
+	if let Err(error) = write_to_cache(&cache_dir, &cache_path, &resized) {
+		// The page can still be served, it just won't be cached
+		error!(?error, ?cache_path, "Failed to cache resized page");
+	}
+
+	Ok((ContentType::JPEG, resized))
+}
+
+fn resize_page(buffer: &[u8], options: PageResizeOptions) -> Result<Vec<u8>, FileError> {
+	let mut image = image::load_from_memory(buffer)?;
+
+	if let Some(width) = options.width {
+		let (current_width, current_height) = image.dimensions();
+		// Pages are never upscaled, since that only makes them larger
+		if width < current_width {
+			let height = (current_height as f64 * width as f64 / current_width as f64)
+				.round()
+				.max(1.0) as u32;
+			image = image.resize_exact(width, height, FilterType::Triangle);
+		}
+	}
+
+	let mut buffer = Cursor::new(vec![]);
+	JpegEncoder::new_with_quality(&mut buffer, options.quality)
+		.encode_image(&image.into_rgb8())?;
+
+	Ok(buffer.into_inner())
+}
+
+fn write_to_cache(
+	cache_dir: &Path,
+	cache_path: &Path,
+	bytes: &[u8],
+) -> Result<(), FileError> {
+	fs::create_dir_all(cache_dir)?;
+	fs::write(cache_path, bytes)?;
+	evict_cached_pages(cache_dir, PAGE_CACHE_MAX_BYTES)
+}
+
+/// Removes the least recently written pages from the cache until its total size is within
+/// the given limit.
+fn evict_cached_pages(cache_dir: &Path, max_bytes: u64) -> Result<(), FileError> {
+	let mut entries = fs::read_dir(cache_dir)?
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| {
+			let metadata = entry.metadata().ok()?;
+			let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+			metadata
+				.is_file()
+				.then(|| (entry.path(), metadata.len(), modified))
+		})
+		.collect::<Vec<(PathBuf, u64, SystemTime)>>();
+
+	let mut total_bytes = entries.iter().map(|(_, size, _)| size).sum::<u64>();
+	if total_bytes <= max_bytes {
+		return Ok(());
+	}
+
+	entries.sort_by_key(|(_, _, modified)| *modified);
+	for (path, size, _) in entries {
+		if total_bytes <= max_bytes {
+			break;
+		}
+		fs::remove_file(&path)?;
+		total_bytes -= size;
+		trace!(?path, "Evicted resized page from cache");
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use image::{DynamicImage, ImageOutputFormat};
+
+	#[test]
+	fn test_clamp_width() {
+		let allowed = [480, 720, 1080];
+		assert_eq!(clamp_width(100, &allowed), Some(480));
+		assert_eq!(clamp_width(720, &allowed), Some(720));
+		assert_eq!(clamp_width(721, &allowed), Some(1080));
+		assert_eq!(clamp_width(4000, &allowed), Some(1080));
+		assert_eq!(clamp_width(720, &[]), None);
+	}
+
+	#[test]
+	fn test_page_resize_options() {
+		let allowed = [480, 720];
+		assert_eq!(
+			PageResizeOptions::new(Some(500), None, &allowed),
+			Some(PageResizeOptions {
+				width: Some(720),
+				quality: DEFAULT_PAGE_QUALITY,
+			})
+		);
+		assert_eq!(
+			PageResizeOptions::new(None, Some(0), &allowed),
+			Some(PageResizeOptions {
+				width: None,
+				quality: 1,
+			})
+		);
+		assert_eq!(PageResizeOptions::new(Some(500), Some(50), &[]), None);
+	}
+
+	#[test]
+	fn test_resize_page() {
+		let mut original = Cursor::new(vec![]);
+		DynamicImage::new_rgb8(200, 300)
+			.write_to(&mut original, ImageOutputFormat::Png)
+			.unwrap();
+		let original = original.into_inner();
+
+		let resized = resize_page(
+			&original,
+			PageResizeOptions {
+				width: Some(100),
+				quality: 80,
+			},
+		)
+		.unwrap();
+		let resized = image::load_from_memory(&resized).unwrap();
+		assert_eq!(resized.dimensions(), (100, 150));
+
+		// Pages are never upscaled
+		let not_upscaled = resize_page(
+			&original,
+			PageResizeOptions {
+				width: Some(400),
+				quality: 80,
+			},
+		)
+		.unwrap();
+		let not_upscaled = image::load_from_memory(&not_upscaled).unwrap();
+		assert_eq!(not_upscaled.dimensions(), (200, 300));
+	}
+
+	#[test]
+	fn test_evict_cached_pages() {
+		let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+		for name in ["oldest", "middle", "newest"] {
+			fs::write(dir.path().join(name), [0u8; 10]).unwrap();
+			// Ensure each file has a distinct modified time
+			std::thread::sleep(std::time::Duration::from_millis(20));
+		}
+
+		evict_cached_pages(dir.path(), 20).unwrap();
+
+		assert!(!dir.path().join("oldest").exists());
+		assert!(dir.path().join("middle").exists());
+		assert!(dir.path().join("newest").exists());
+	}
+}
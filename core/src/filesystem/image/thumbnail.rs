@@ -105,12 +105,17 @@ pub fn generate_thumbnails_for_media(
 	options: ImageProcessorOptions,
 	config: &StumpConfig,
 	mut on_progress: impl FnMut(String) + Send + Sync + 'static,
+	is_cancelled: impl Fn() -> bool,
 ) -> Result<Vec<PathBuf>, FileError> {
 	trace!(media_count = media.len(), "Enter generate_thumbnails");
 
 	let mut generated_paths = Vec::with_capacity(media.len());
 
 	for (idx, chunk) in media.chunks(THUMBNAIL_CHUNK_SIZE).enumerate() {
+		if is_cancelled() {
+			debug!("Thumbnail generation was cancelled, skipping remaining chunks");
+			break;
+		}
 		trace!(chunk = idx + 1, "Processing chunk for thumbnail generation");
 		on_progress(
 			format!(
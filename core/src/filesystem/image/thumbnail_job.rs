@@ -161,12 +161,13 @@ impl JobTrait for ThumbnailJob {
 						"Generating thumbnails for library"
 					);
 
-					let generated_thumbnail_paths = generate_thumbnails_for_media(
+					let generated_thumbnail_paths = generate_thumbnails_blocking(
 						library_media,
 						self.options.to_owned(),
-						&core_ctx.config,
+						&ctx,
 						on_progress,
-					)?;
+					)
+					.await?;
 
 					Ok(generated_thumbnail_paths)
 				} else {
@@ -196,12 +197,13 @@ impl JobTrait for ThumbnailJob {
 						media_count = media_without_thumbnails.len(),
 						"Generating thumbnails for library"
 					);
-					let generated_thumbnail_paths = generate_thumbnails_for_media(
+					let generated_thumbnail_paths = generate_thumbnails_blocking(
 						media_without_thumbnails,
 						self.options.to_owned(),
-						&core_ctx.config,
+						&ctx,
 						on_progress,
-					)?;
+					)
+					.await?;
 					Ok(generated_thumbnail_paths)
 				}
 			},
@@ -262,12 +264,13 @@ impl JobTrait for ThumbnailJob {
 						"Generating thumbnails for series"
 					);
 
-					let generated_thumbnail_paths = generate_thumbnails_for_media(
+					let generated_thumbnail_paths = generate_thumbnails_blocking(
 						series_media,
 						self.options.to_owned(),
-						&core_ctx.config,
+						&ctx,
 						on_progress,
-					)?;
+					)
+					.await?;
 					Ok(generated_thumbnail_paths)
 				} else {
 					let media_without_thumbnails = series_media
@@ -296,12 +299,13 @@ impl JobTrait for ThumbnailJob {
 						media_count = media_without_thumbnails.len(),
 						"Generating thumbnails for library"
 					);
-					let generated_thumbnail_paths = generate_thumbnails_for_media(
+					let generated_thumbnail_paths = generate_thumbnails_blocking(
 						media_without_thumbnails,
 						self.options.to_owned(),
-						&core_ctx.config,
+						&ctx,
 						on_progress,
-					)?;
+					)
+					.await?;
 					Ok(generated_thumbnail_paths)
 				}
 			},
@@ -333,12 +337,13 @@ impl JobTrait for ThumbnailJob {
 					.find_many(vec![media::id::in_vec(media_group_ids.to_owned())])
 					.exec()
 					.await?;
-				let generated_thumbnail_paths = generate_thumbnails_for_media(
+				let generated_thumbnail_paths = generate_thumbnails_blocking(
 					media,
 					self.options.to_owned(),
-					&core_ctx.config,
+					&ctx,
 					on_progress,
-				)?;
+				)
+				.await?;
 				Ok(generated_thumbnail_paths)
 			},
 		};
@@ -354,6 +359,37 @@ impl JobTrait for ThumbnailJob {
 	}
 }
 
+/// Generates the thumbnails on a blocking thread. Image processing is CPU-bound and never
+/// yields, so running it directly on the async runtime would stall everything else (including
+/// the progress events emitted by `on_progress`) until the whole batch is done.
+async fn generate_thumbnails_blocking(
+	media: Vec<media::Data>,
+	options: ImageProcessorOptions,
+	ctx: &WorkerCtx,
+	on_progress: impl FnMut(String) + Send + Sync + 'static,
+) -> Result<Vec<PathBuf>, JobError> {
+	let config = ctx.core_ctx.config.clone();
+	let worker_ctx = ctx.clone();
+	let generated_paths = ctx
+		.spawn_blocking(move || {
+			generate_thumbnails_for_media(media, options, &config, on_progress, || {
+				worker_ctx.is_killed()
+			})
+		})
+		.await
+		.map_err(|join_error| {
+			if join_error.is_panic() {
+				// Let the worker report the panic as it would for any other job
+				std::panic::resume_unwind(join_error.into_panic())
+			}
+			JobError::Unknown(join_error.to_string())
+		})?
+		.map_err(JobError::from)?;
+	ctx.check_killed()?;
+
+	Ok(generated_paths)
+}
+
 impl ThumbnailJob {
 	pub fn new(
 		options: ImageProcessorOptions,
@@ -64,7 +64,7 @@ impl FileProcessor for EpubProcessor {
 
 	fn process(
 		path: &str,
-		_: FileProcessorOptions,
+		options: FileProcessorOptions,
 		_: &StumpConfig,
 	) -> Result<ProcessedFile, FileError> {
 		tracing::debug!(?path, "processing epub");
@@ -74,12 +74,17 @@ impl FileProcessor for EpubProcessor {
 
 		tracing::trace!(?epub_file.metadata, "Processing raw EPUB metadata");
 		let pages = epub_file.get_num_pages() as i32;
-		let metadata = MediaMetadata::from(epub_file.metadata);
+		let metadata = options
+			.extract_metadata
+			.then(|| MediaMetadata::from(epub_file.metadata));
 
 		Ok(ProcessedFile {
 			path: path_buf,
-			hash: EpubProcessor::hash(path),
-			metadata: Some(metadata),
+			hash: options
+				.generate_hash
+				.then(|| EpubProcessor::hash(path))
+				.flatten(),
+			metadata,
 			pages,
 		})
 	}
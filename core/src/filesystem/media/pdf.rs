@@ -13,7 +13,7 @@ use crate::{
 	db::entity::MediaMetadata,
 	filesystem::{
 		archive::create_zip_archive, error::FileError, hash, image::ImageFormat,
-		ContentType, FileParts, PathUtils,
+		ContentType, FileParts, PathUtils, TempDir,
 	},
 };
 
@@ -60,17 +60,24 @@ impl FileProcessor for PdfProcessor {
 
 	fn process(
 		path: &str,
-		_: FileProcessorOptions,
+		options: FileProcessorOptions,
 		_: &StumpConfig,
 	) -> Result<ProcessedFile, FileError> {
 		let file = FileOptions::cached().open(path)?;
 
 		let pages = file.pages().count() as i32;
-		let metadata = file.trailer.info_dict.map(MediaMetadata::from);
+		let metadata = file
+			.trailer
+			.info_dict
+			.filter(|_| options.extract_metadata)
+			.map(MediaMetadata::from);
 
 		Ok(ProcessedFile {
 			path: PathBuf::from(path),
-			hash: PdfProcessor::hash(path),
+			hash: options
+				.generate_hash
+				.then(|| PdfProcessor::hash(path))
+				.flatten(),
 			metadata,
 			pages,
 		})
@@ -205,15 +212,13 @@ impl FileConverter for PdfProcessor {
 		let parent = path_buf.parent().unwrap_or_else(|| Path::new("/"));
 		let FileParts {
 			file_name,
-			file_stem,
 			extension,
+			..
 		} = path_buf.as_path().file_parts();
 
-		let cache_dir = config.get_cache_dir();
-		let unpacked_path = cache_dir.join(file_stem);
-
-		// create folder for the zip
-		std::fs::create_dir_all(&unpacked_path)?;
+		// The folder for the zip, which is removed when the temporary directory is dropped
+		let temp_dir = TempDir::new("pdf", config)?;
+		let unpacked_path = temp_dir.path();
 
 		// write each image to the folder
 		for image_buf in converted_pages {
@@ -231,8 +236,7 @@ impl FileConverter for PdfProcessor {
 			}
 		}
 
-		let zip_path =
-			create_zip_archive(&unpacked_path, &file_name, &extension, parent)?;
+		let zip_path = create_zip_archive(unpacked_path, &file_name, &extension, parent)?;
 
 		// TODO: won't work in docker
 		if delete_source {
@@ -241,13 +245,6 @@ impl FileConverter for PdfProcessor {
 			}
 		}
 
-		// TODO: maybe check that this path isn't in a pre-defined list of important paths?
-		if let Err(err) = std::fs::remove_dir_all(&unpacked_path) {
-			tracing::error!(
-				error = ?err, ?cache_dir, ?unpacked_path, "Failed to delete unpacked contents in cache",
-			);
-		}
-
 		Ok(zip_path)
 	}
 }
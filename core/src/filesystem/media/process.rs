@@ -23,6 +23,10 @@ use super::{rar::RarProcessor, zip::ZipProcessor};
 pub struct FileProcessorOptions {
 	pub convert_rar_to_zip: bool,
 	pub delete_conversion_source: bool,
+	/// Whether to extract metadata (e.g. ComicInfo.xml) from the file
+	pub extract_metadata: bool,
+	/// Whether to generate a hash of the file
+	pub generate_hash: bool,
 }
 
 impl From<LibraryOptions> for FileProcessorOptions {
@@ -30,6 +34,8 @@ impl From<LibraryOptions> for FileProcessorOptions {
 		Self {
 			convert_rar_to_zip: options.convert_rar_to_zip,
 			delete_conversion_source: options.hard_delete_conversions,
+			extract_metadata: options.extract_metadata,
+			generate_hash: options.generate_file_hashes,
 		}
 	}
 }
@@ -39,6 +45,8 @@ impl From<&LibraryOptions> for FileProcessorOptions {
 		Self {
 			convert_rar_to_zip: options.convert_rar_to_zip,
 			delete_conversion_source: options.hard_delete_conversions,
+			extract_metadata: options.extract_metadata,
+			generate_hash: options.generate_file_hashes,
 		}
 	}
 }
@@ -177,3 +185,23 @@ pub fn get_content_types_for_pages(
 		_ => Err(FileError::UnsupportedFileType(path.to_string())),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_default_options_process_everything() {
+		let options = FileProcessorOptions::from(LibraryOptions::default());
+		assert!(options.extract_metadata);
+		assert!(options.generate_hash);
+
+		// Options saved before the flags existed (e.g. in a bundle) keep the old behavior
+		let options: LibraryOptions = serde_json::from_str(
+			r#"{"id":null,"convert_rar_to_zip":false,"hard_delete_conversions":false,"library_pattern":"SERIES_BASED","thumbnail_config":null,"library_id":null}"#,
+		)
+		.unwrap();
+		assert!(options.extract_metadata);
+		assert!(options.generate_file_hashes);
+	}
+}
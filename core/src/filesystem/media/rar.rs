@@ -4,7 +4,7 @@ use std::{
 	fs::File,
 	path::{Path, PathBuf},
 };
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, trace, warn};
 use unrar::{Archive, CursorBeforeHeader, List, OpenArchive, Process, UnrarResult};
 
 use crate::{
@@ -17,7 +17,7 @@ use crate::{
 		image::ImageFormat,
 		media::common::metadata_from_buf,
 		zip::ZipProcessor,
-		FileParts, PathUtils,
+		FileParts, PathUtils, TempDir,
 	},
 };
 
@@ -116,7 +116,10 @@ impl FileProcessor for RarProcessor {
 
 		debug!(path, "Processing RAR");
 
-		let hash: Option<String> = RarProcessor::hash(path);
+		let hash: Option<String> = options
+			.generate_hash
+			.then(|| RarProcessor::hash(path))
+			.flatten();
 
 		let mut archive = RarProcessor::open_for_processing(path)?;
 		let mut pages = 0;
@@ -124,7 +127,7 @@ impl FileProcessor for RarProcessor {
 
 		while let Ok(Some(header)) = archive.read_header() {
 			let entry = header.entry();
-			if entry.filename.as_os_str() == "ComicInfo.xml" {
+			if entry.filename.as_os_str() == "ComicInfo.xml" && options.extract_metadata {
 				let (data, rest) = header.read()?;
 				metadata_buf = Some(data);
 				archive = rest;
@@ -279,26 +282,26 @@ impl FileConverter for RarProcessor {
 		let parent = path_buf.parent().unwrap_or_else(|| Path::new("/"));
 		let FileParts {
 			extension,
-			file_stem,
 			file_name,
+			..
 		} = path_buf.as_path().file_parts();
 
-		let cache_dir = config.get_cache_dir();
-		let unpacked_path = cache_dir.join(file_stem);
+		// The unpacked contents are removed when the temporary directory is dropped
+		let temp_dir = TempDir::new("rar", config)?;
+		let unpacked_path = temp_dir.path();
 
-		trace!(?unpacked_path, "Extracting RAR to cache");
+		trace!(?unpacked_path, "Extracting RAR to temporary directory");
 
 		let mut archive = RarProcessor::open_for_processing(path)?;
 		while let Ok(Some(header)) = archive.read_header() {
 			archive = if header.entry().is_file() {
-				header.extract_to(&unpacked_path)?
+				header.extract_to(unpacked_path)?
 			} else {
 				header.skip()?
 			};
 		}
 
-		let zip_path =
-			create_zip_archive(&unpacked_path, &file_name, &extension, parent)?;
+		let zip_path = create_zip_archive(unpacked_path, &file_name, &extension, parent)?;
 
 		// TODO: won't work in docker
 		if delete_source {
@@ -307,13 +310,6 @@ impl FileConverter for RarProcessor {
 			}
 		}
 
-		// TODO: maybe check that this path isn't in a pre-defined list of important paths?
-		if let Err(err) = std::fs::remove_dir_all(&unpacked_path) {
-			error!(
-				error = ?err, ?cache_dir, ?unpacked_path, "Failed to delete unpacked RAR contents in cache",
-			);
-		}
-
 		Ok(zip_path)
 	}
 }
@@ -62,12 +62,15 @@ impl FileProcessor for ZipProcessor {
 
 	fn process(
 		path: &str,
-		_: FileProcessorOptions,
+		options: FileProcessorOptions,
 		_: &StumpConfig,
 	) -> Result<ProcessedFile, FileError> {
 		debug!(path, "Processing zip");
 
-		let hash = ZipProcessor::hash(path);
+		let hash = options
+			.generate_hash
+			.then(|| ZipProcessor::hash(path))
+			.flatten();
 		let zip_file = File::open(path)?;
 		let mut archive = zip::ZipArchive::new(zip_file)?;
 
@@ -77,7 +80,7 @@ impl FileProcessor for ZipProcessor {
 		for i in 0..archive.len() {
 			let mut file = archive.by_index(i)?;
 			let (content_type, buf) = get_zip_entry_content_type(&mut file)?;
-			if file.name() == "ComicInfo.xml" {
+			if file.name() == "ComicInfo.xml" && options.extract_metadata {
 				trace!("Found ComicInfo.xml");
 				// we have the first few bytes of the file in buf, so we need to read the rest and make it a string
 				let mut contents = buf.to_vec();
@@ -9,6 +9,7 @@ mod hash;
 pub mod image;
 pub mod media;
 pub mod scanner;
+mod temp;
 
 pub use common::*;
 pub use content_type::ContentType;
@@ -17,3 +18,4 @@ pub use directory_listing::{
 };
 pub use error::FileError;
 pub use media::*;
+pub use temp::{sweep_temp_dir, TempDir};
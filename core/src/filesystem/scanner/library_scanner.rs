@@ -66,6 +66,10 @@ impl LibraryScanner {
 
 		let counter = Arc::new(AtomicU64::new(0));
 		for series in library_series {
+			if self.worker_ctx.is_killed() {
+				tracing::debug!("Job was killed, abandoning library scan");
+				return Ok(counter.load(Ordering::SeqCst));
+			}
 			let progress_ctx = self.worker_ctx.clone();
 
 			let job_id = progress_ctx.job_id().to_string();
@@ -320,10 +324,11 @@ impl LibraryScanner {
 				force_regenerate: false,
 			};
 
-			let dispatch_result =
-				core_ctx.dispatch_job(ThumbnailJob::new(thumbnail_config, job_config));
-			if dispatch_result.is_err() {
-				tracing::error!("Failed to dispatch thumbnail job!");
+			let enqueue_result = core_ctx
+				.enqueue_job(ThumbnailJob::new(thumbnail_config, job_config))
+				.await;
+			if let Err(error) = enqueue_result {
+				tracing::error!(?error, "Failed to enqueue thumbnail job!");
 			}
 		} else {
 			tracing::debug!("No thumbnail config found, skipping thumbnail job dispatch");
@@ -4,5 +4,7 @@ mod series_scanner;
 mod utils;
 
 pub use library_scanner::LibraryScanner;
-pub use scanner_job::{LibraryScanJob, SeriesScanJob};
+pub use scanner_job::{
+	LibraryScanJob, SeriesScanJob, LIBRARY_SCAN_JOB_NAME, SERIES_SCAN_JOB_NAME,
+};
 pub use series_scanner::SeriesScanner;
@@ -1,9 +1,12 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
 use crate::{
 	db::entity::LibraryScanMode,
 	job::{Job, JobError, JobTrait, WorkerCtx},
-	CoreError,
+	prisma::{library, series},
+	CoreError, Ctx,
 };
 
 use super::{LibraryScanner, SeriesScanner};
@@ -27,6 +30,40 @@ impl JobTrait for LibraryScanJob {
 		Some(Box::new(self.library_path.as_str()))
 	}
 
+	async fn validate(&self, ctx: &Ctx) -> Result<(), JobError> {
+		if self.scan_mode == LibraryScanMode::None {
+			return Err(JobError::InvalidJob(String::from(
+				"Library scan mode is set to NONE",
+			)));
+		}
+
+		let library = ctx
+			.db
+			.library()
+			.find_unique(library::path::equals(self.library_path.clone()))
+			.exec()
+			.await?;
+
+		if library.is_none() {
+			return Err(JobError::InvalidJob(format!(
+				"No library exists with the path {}",
+				self.library_path
+			)));
+		} else if library.as_ref().is_some_and(|library| library.archived) {
+			return Err(JobError::LibraryArchived(format!(
+				"The library at {} is archived, so it can't be scanned",
+				self.library_path
+			)));
+		} else if !Path::new(&self.library_path).is_dir() {
+			return Err(JobError::InvalidJob(format!(
+				"The library directory does not exist: {}",
+				self.library_path
+			)));
+		}
+
+		Ok(())
+	}
+
 	async fn run(&mut self, ctx: WorkerCtx) -> Result<u64, JobError> {
 		let scanner = LibraryScanner::new(self.library_path.clone(), ctx);
 		let completed_task_count = match self.scan_mode {
@@ -65,6 +102,29 @@ impl JobTrait for SeriesScanJob {
 		Some(Box::new(self.series_path.as_str()))
 	}
 
+	async fn validate(&self, ctx: &Ctx) -> Result<(), JobError> {
+		let archived_library = ctx
+			.db
+			.library()
+			.find_first(vec![
+				library::archived::equals(true),
+				library::series::some(vec![series::path::equals(
+					self.series_path.clone(),
+				)]),
+			])
+			.exec()
+			.await?;
+
+		if let Some(library) = archived_library {
+			return Err(JobError::LibraryArchived(format!(
+				"The series at {} belongs to the archived library {}, so it can't be scanned",
+				self.series_path, library.name
+			)));
+		}
+
+		Ok(())
+	}
+
 	async fn run(&mut self, ctx: WorkerCtx) -> Result<u64, JobError> {
 		let scanner = SeriesScanner::new(ctx).with_path(self.series_path.clone());
 		let completed_task_count = scanner.scan().await?;
@@ -78,3 +138,84 @@ impl SeriesScanJob {
 		Job::new(Self { series_path })
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{config::StumpConfig, prisma::PrismaClient};
+
+	#[tokio::test]
+	async fn test_enqueue_scan_for_missing_library_is_rejected() {
+		let (client, mock) = PrismaClient::_mock();
+		let library_path = String::from("/does/not/exist");
+
+		mock.expect(
+			client
+				.library()
+				.find_unique(library::path::equals(library_path.clone())),
+			None,
+		)
+		.await;
+
+		let ctx = Ctx::mock_with_client(client, StumpConfig::debug());
+		let result = ctx
+			.enqueue_job(LibraryScanJob::new(library_path, LibraryScanMode::Default))
+			.await;
+
+		assert!(matches!(result, Err(JobError::InvalidJob(_))));
+	}
+
+	#[tokio::test]
+	async fn test_enqueue_scan_for_archived_library_is_rejected() {
+		let (client, mock) = PrismaClient::_mock();
+		let library_path = String::from("/mnt/external/comics");
+		let now = prisma_client_rust::chrono::Utc::now().into();
+
+		mock.expect(
+			client
+				.library()
+				.find_unique(library::path::equals(library_path.clone())),
+			Some(library::Data {
+				id: String::from("archived_library"),
+				name: String::from("External Comics"),
+				description: None,
+				path: library_path.clone(),
+				status: String::from("READY"),
+				updated_at: now,
+				created_at: now,
+				emoji: None,
+				archived: true,
+				series: None,
+				library_options: None,
+				library_options_id: String::from("archived_library_options"),
+				tags: None,
+				job_schedule_config: None,
+				job_schedule_config_id: None,
+				user_visits: None,
+			}),
+		)
+		.await;
+
+		let ctx = Ctx::mock_with_client(client, StumpConfig::debug());
+		let result = ctx
+			.enqueue_job(LibraryScanJob::new(library_path, LibraryScanMode::Default))
+			.await;
+
+		assert!(matches!(result, Err(JobError::LibraryArchived(_))));
+	}
+
+	#[tokio::test]
+	async fn test_enqueue_scan_with_none_mode_is_rejected() {
+		let (client, _mock) = PrismaClient::_mock();
+
+		let ctx = Ctx::mock_with_client(client, StumpConfig::debug());
+		let result = ctx
+			.enqueue_job(LibraryScanJob::new(
+				String::from("/does/not/exist"),
+				LibraryScanMode::None,
+			))
+			.await;
+
+		assert!(matches!(result, Err(JobError::InvalidJob(_))));
+	}
+}
@@ -13,7 +13,7 @@ use itertools::Itertools;
 use walkdir::WalkDir;
 
 use crate::{
-	db::entity::{FileStatus, Library, LibraryOptions, Media, Series},
+	db::entity::{FileStatus, Library, LibraryOptions, LogLevel, Media, Series},
 	event::CoreEvent,
 	filesystem::{
 		scanner::utils::{
@@ -162,6 +162,12 @@ impl SeriesScanner {
 			.filter(|e| e.path().is_file());
 
 		for entry in iter {
+			if self.worker_ctx.is_killed() {
+				tracing::debug!("Job was killed, abandoning series scan");
+				return;
+			}
+			self.worker_ctx.yield_if_over_budget().await;
+
 			let path = entry.path();
 			let path_str = path.to_str().unwrap_or("").to_string();
 
@@ -170,7 +176,12 @@ impl SeriesScanner {
 			let glob_match = glob_set.is_match(path);
 			let should_ignore = glob_match || path.should_ignore();
 			if should_ignore {
-				tracing::trace!(?path, glob_match, "Skipping ignored file");
+				self.worker_ctx
+					.push_log(
+						LogLevel::Debug,
+						format!("Skipping ignored file {:?}", path),
+					)
+					.await;
 				on_progress(format!("Skipping {:?}", path));
 				continue;
 			}
@@ -225,16 +236,27 @@ impl SeriesScanner {
 								});
 							},
 							Err(e) => {
-								tracing::error!(error = ?e, "Failed to update media");
-								// TODO: persist error
+								self.worker_ctx
+									.push_log(
+										LogLevel::Error,
+										format!(
+											"Failed to update media {:?}: {}",
+											path, e
+										),
+									)
+									.await;
 							},
 						}
-					} else {
-						tracing::error!(
-							error = ?build_result.err(),
-							?path,
-							"Failed to build media for update!",
-						);
+					} else if let Err(e) = build_result {
+						self.worker_ctx
+							.push_log(
+								LogLevel::Error,
+								format!(
+									"Failed to build media {:?} for update: {}",
+									path, e
+								),
+							)
+							.await;
 					}
 				}
 
@@ -259,12 +281,21 @@ impl SeriesScanner {
 							});
 						},
 						Err(e) => {
-							tracing::error!(error = ?e, ?path, "Failed to create media");
-							// TODO: persist error
+							self.worker_ctx
+								.push_log(
+									LogLevel::Error,
+									format!("Failed to create media {:?}: {}", path, e),
+								)
+								.await;
 						},
 					}
-				} else {
-					tracing::error!(error = ?build_result.err(), ?path, "Failed to build media");
+				} else if let Err(e) = build_result {
+					self.worker_ctx
+						.push_log(
+							LogLevel::Error,
+							format!("Failed to build media {:?}: {}", path, e),
+						)
+						.await;
 				}
 			}
 		}
@@ -276,10 +307,13 @@ impl SeriesScanner {
 			.collect::<Vec<String>>();
 
 		if !missing_media.is_empty() {
-			tracing::warn!(
-				missing_paths = ?missing_media,
-				"Some paths were not visited during series scan"
-			);
+			self.worker_ctx
+				.push_warning(format!(
+					"{} media in series {} were not found and will be marked as missing",
+					missing_media.len(),
+					series.id
+				))
+				.await;
 			let result = mark_media_paths_missing(&ctx.db, missing_media).await;
 
 			if let Err(err) = result {
@@ -0,0 +1,202 @@
+//! Temporary files (e.g. the unpacked contents of an archive being converted) are all
+//! allocated under a single directory within the config directory, so that any left behind by
+//! a crash can be found and removed.
+
+use std::{
+	collections::HashSet,
+	fs,
+	path::{Path, PathBuf},
+	sync::{Mutex, OnceLock},
+};
+
+use tracing::{error, trace};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use crate::config::StumpConfig;
+
+use super::FileError;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// The paths of every [TempDir] which hasn't yet been dropped. Anything else in the temporary
+/// directory is an orphan.
+fn live_temp_dirs() -> &'static Mutex<HashSet<PathBuf>> {
+	static LIVE_TEMP_DIRS: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+	LIVE_TEMP_DIRS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// A temporary directory, which is removed (along with its contents) when dropped.
+#[derive(Debug)]
+pub struct TempDir {
+	path: PathBuf,
+}
+
+impl TempDir {
+	/// Creates a new temporary directory, erroring if the temporary directory is already at
+	/// (or above) the configured size limit.
+	pub fn new(prefix: &str, config: &StumpConfig) -> Result<Self, FileError> {
+		let max_bytes =
+			(config.temp_dir_max_mb > 0).then_some(config.temp_dir_max_mb * BYTES_PER_MB);
+		Self::new_in(&config.get_temp_dir(), prefix, max_bytes)
+	}
+
+	fn new_in(
+		root: &Path,
+		prefix: &str,
+		max_bytes: Option<u64>,
+	) -> Result<Self, FileError> {
+		if let Some(max_bytes) = max_bytes {
+			let used_bytes = dir_size(root);
+			if used_bytes >= max_bytes {
+				return Err(FileError::TempDirFull {
+					used_mb: used_bytes / BYTES_PER_MB,
+					max_mb: max_bytes / BYTES_PER_MB,
+				});
+			}
+		}
+
+		let path = root.join(format!("{}-{}", prefix, Uuid::new_v4()));
+		// The path is registered before the directory exists, so that a concurrent sweep
+		// can't mistake it for an orphan
+		live_temp_dirs()
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+			.insert(path.clone());
+		let temp_dir = Self { path };
+		fs::create_dir_all(&temp_dir.path)?;
+
+		trace!(path = ?temp_dir.path, "Created temporary directory");
+		Ok(temp_dir)
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		if let Err(error) = fs::remove_dir_all(&self.path) {
+			error!(?error, path = ?self.path, "Failed to remove temporary directory");
+		}
+		live_temp_dirs()
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+			.remove(&self.path);
+	}
+}
+
+/// Removes everything in the temporary directory which doesn't belong to a live [TempDir],
+/// i.e. anything left behind by a crash. Returns the number of entries removed.
+pub fn sweep_temp_dir(config: &StumpConfig) -> Result<u64, FileError> {
+	let live = live_temp_dirs()
+		.lock()
+		.unwrap_or_else(|poisoned| poisoned.into_inner())
+		.clone();
+	sweep_orphans(&config.get_temp_dir(), &live)
+}
+
+fn sweep_orphans(root: &Path, live: &HashSet<PathBuf>) -> Result<u64, FileError> {
+	if !root.exists() {
+		return Ok(0);
+	}
+
+	let mut removed = 0;
+	for entry in fs::read_dir(root)?.filter_map(|entry| entry.ok()) {
+		let path = entry.path();
+		if live.contains(&path) {
+			continue;
+		}
+
+		let result = if path.is_dir() {
+			fs::remove_dir_all(&path)
+		} else {
+			fs::remove_file(&path)
+		};
+		match result {
+			Ok(_) => {
+				trace!(?path, "Removed orphaned temporary file");
+				removed += 1;
+			},
+			Err(error) => {
+				error!(?error, ?path, "Failed to remove orphaned temporary file")
+			},
+		}
+	}
+
+	Ok(removed)
+}
+
+/// Returns the total size (in bytes) of the files within the directory
+fn dir_size(path: &Path) -> u64 {
+	WalkDir::new(path)
+		.into_iter()
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| entry.metadata().ok())
+		.filter(|metadata| metadata.is_file())
+		.map(|metadata| metadata.len())
+		.sum()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_temp_dir_removed_on_drop() {
+		let root = tempfile::tempdir().expect("Failed to create temporary directory");
+
+		let temp_dir = TempDir::new_in(root.path(), "test", None).unwrap();
+		let path = temp_dir.path().to_path_buf();
+		fs::write(path.join("page.png"), [0u8; 10]).unwrap();
+		assert!(path.exists());
+
+		drop(temp_dir);
+		assert!(!path.exists());
+	}
+
+	#[test]
+	fn test_temp_dir_size_limit() {
+		let root = tempfile::tempdir().expect("Failed to create temporary directory");
+
+		let first = TempDir::new_in(root.path(), "test", Some(BYTES_PER_MB)).unwrap();
+		fs::write(
+			first.path().join("page.png"),
+			vec![0u8; BYTES_PER_MB as usize],
+		)
+		.unwrap();
+
+		let result = TempDir::new_in(root.path(), "test", Some(BYTES_PER_MB));
+		assert!(matches!(
+			result,
+			Err(FileError::TempDirFull {
+				used_mb: 1,
+				max_mb: 1
+			})
+		));
+
+		drop(first);
+		assert!(TempDir::new_in(root.path(), "test", Some(BYTES_PER_MB)).is_ok());
+	}
+
+	#[test]
+	fn test_sweep_removes_orphans() {
+		let root = tempfile::tempdir().expect("Failed to create temporary directory");
+
+		let live = TempDir::new_in(root.path(), "live", None).unwrap();
+		let orphan = TempDir::new_in(root.path(), "orphan", None).unwrap();
+		let orphan_path = orphan.path().to_path_buf();
+		fs::write(orphan_path.join("page.png"), [0u8; 10]).unwrap();
+		// Simulate a crash, where the handle is never dropped
+		std::mem::forget(orphan);
+
+		// After a restart, only the handles created since are live
+		let live_paths = HashSet::from([live.path().to_path_buf()]);
+		let removed = sweep_orphans(root.path(), &live_paths).unwrap();
+
+		assert_eq!(removed, 1);
+		assert!(!orphan_path.exists());
+		assert!(live.path().exists());
+	}
+}
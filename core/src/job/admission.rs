@@ -0,0 +1,221 @@
+//! Admission control for jobs. Before a job is started, the state of the system is checked
+//! against the thresholds in the job manager's [JobLimits], and the job is deferred (left at the front of the
+//! queue and re-checked later) rather than started when the system is already under pressure.
+
+use std::path::PathBuf;
+
+use super::JobLimits;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// A snapshot of the system's resource usage. Any stat which couldn't be determined (e.g.
+/// because the platform isn't supported) is `None`, and is never a reason to defer a job.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemStats {
+	/// The free disk space (in bytes) available on the volume holding the config directory
+	pub free_disk_bytes: Option<u64>,
+	/// The resident set size (in bytes) of the server process
+	pub rss_bytes: Option<u64>,
+	/// The 1 minute load average of the system
+	pub load_average: Option<f64>,
+}
+
+/// A source of [SystemStats]. This exists so that tests can inject fake stats.
+pub trait SystemStatsProvider: Send + Sync {
+	fn stats(&self) -> SystemStats;
+}
+
+/// A [SystemStatsProvider] which reads the stats from the operating system. Only Linux is
+/// currently supported, and other platforms report no stats.
+pub struct OsSystemStats {
+	/// The path used to determine which volume's free disk space is reported
+	disk_path: PathBuf,
+}
+
+impl OsSystemStats {
+	pub fn new(disk_path: PathBuf) -> Self {
+		Self { disk_path }
+	}
+}
+
+impl SystemStatsProvider for OsSystemStats {
+	#[cfg(target_os = "linux")]
+	fn stats(&self) -> SystemStats {
+		let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+		SystemStats {
+			free_disk_bytes: free_disk_bytes(&self.disk_path),
+			rss_bytes: std::fs::read_to_string("/proc/self/statm")
+				.ok()
+				.and_then(|statm| parse_statm_rss_pages(&statm))
+				.filter(|_| page_size > 0)
+				.map(|pages| pages * page_size as u64),
+			load_average: std::fs::read_to_string("/proc/loadavg")
+				.ok()
+				.and_then(|loadavg| parse_loadavg(&loadavg)),
+		}
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	fn stats(&self) -> SystemStats {
+		let _ = &self.disk_path;
+		SystemStats::default()
+	}
+}
+
+#[cfg(target_os = "linux")]
+// The statvfs field types vary between targets, so the casts aren't always necessary
+#[allow(clippy::unnecessary_cast)]
+fn free_disk_bytes(path: &std::path::Path) -> Option<u64> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+	let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+	let result = unsafe { libc::statvfs(path.as_ptr(), &mut stat) };
+
+	(result == 0).then_some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+/// Returns the resident set size (in pages) from the contents of `/proc/self/statm`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_statm_rss_pages(statm: &str) -> Option<u64> {
+	statm.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Returns the 1 minute load average from the contents of `/proc/loadavg`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_loadavg(loadavg: &str) -> Option<f64> {
+	loadavg.split_whitespace().next()?.parse().ok()
+}
+
+/// The thresholds beyond which jobs are deferred. A threshold of `None` is not checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AdmissionThresholds {
+	pub min_free_disk_bytes: Option<u64>,
+	pub max_rss_bytes: Option<u64>,
+	pub max_load_average: Option<f64>,
+}
+
+impl From<&JobLimits> for AdmissionThresholds {
+	fn from(limits: &JobLimits) -> Self {
+		Self {
+			min_free_disk_bytes: (limits.job_min_free_disk_mb > 0)
+				.then_some(limits.job_min_free_disk_mb * BYTES_PER_MB),
+			max_rss_bytes: (limits.job_max_rss_mb > 0)
+				.then_some(limits.job_max_rss_mb * BYTES_PER_MB),
+			max_load_average: (limits.job_max_load_average > 0.0)
+				.then_some(limits.job_max_load_average),
+		}
+	}
+}
+
+/// Returns the reason a job should be deferred, or `None` if it may be started.
+pub fn check_admission(
+	stats: &SystemStats,
+	thresholds: &AdmissionThresholds,
+) -> Option<String> {
+	if let (Some(free), Some(min)) =
+		(stats.free_disk_bytes, thresholds.min_free_disk_bytes)
+	{
+		if free < min {
+			return Some(format!(
+				"Free disk space ({}MB) is below the minimum of {}MB",
+				free / BYTES_PER_MB,
+				min / BYTES_PER_MB
+			));
+		}
+	}
+
+	if let (Some(rss), Some(max)) = (stats.rss_bytes, thresholds.max_rss_bytes) {
+		if rss > max {
+			return Some(format!(
+				"Memory usage ({}MB) is above the maximum of {}MB",
+				rss / BYTES_PER_MB,
+				max / BYTES_PER_MB
+			));
+		}
+	}
+
+	if let (Some(load), Some(max)) = (stats.load_average, thresholds.max_load_average) {
+		if load > max {
+			return Some(format!(
+				"Load average ({:.2}) is above the maximum of {:.2}",
+				load, max
+			));
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_check_admission() {
+		let thresholds = AdmissionThresholds {
+			min_free_disk_bytes: Some(512 * BYTES_PER_MB),
+			max_rss_bytes: Some(1024 * BYTES_PER_MB),
+			max_load_average: Some(4.0),
+		};
+		let healthy = SystemStats {
+			free_disk_bytes: Some(10 * 1024 * BYTES_PER_MB),
+			rss_bytes: Some(256 * BYTES_PER_MB),
+			load_average: Some(0.5),
+		};
+		assert_eq!(check_admission(&healthy, &thresholds), None);
+
+		let low_disk = SystemStats {
+			free_disk_bytes: Some(100 * BYTES_PER_MB),
+			..healthy.clone()
+		};
+		assert!(check_admission(&low_disk, &thresholds)
+			.unwrap()
+			.contains("Free disk space (100MB)"));
+
+		let high_memory = SystemStats {
+			rss_bytes: Some(2048 * BYTES_PER_MB),
+			..healthy.clone()
+		};
+		assert!(check_admission(&high_memory, &thresholds)
+			.unwrap()
+			.contains("Memory usage (2048MB)"));
+
+		let high_load = SystemStats {
+			load_average: Some(8.0),
+			..healthy
+		};
+		assert!(check_admission(&high_load, &thresholds)
+			.unwrap()
+			.contains("Load average (8.00)"));
+	}
+
+	#[test]
+	fn test_check_admission_unknown_or_disabled() {
+		let under_pressure = SystemStats {
+			free_disk_bytes: Some(0),
+			rss_bytes: Some(u64::MAX),
+			load_average: Some(100.0),
+		};
+		assert_eq!(
+			check_admission(&under_pressure, &AdmissionThresholds::default()),
+			None
+		);
+
+		let thresholds = AdmissionThresholds {
+			min_free_disk_bytes: Some(512 * BYTES_PER_MB),
+			max_rss_bytes: Some(1024 * BYTES_PER_MB),
+			max_load_average: Some(4.0),
+		};
+		assert_eq!(check_admission(&SystemStats::default(), &thresholds), None);
+	}
+
+	#[test]
+	fn test_parse_proc_files() {
+		assert_eq!(parse_statm_rss_pages("1204 348 280 9 0 135 0\n"), Some(348));
+		assert_eq!(parse_statm_rss_pages(""), None);
+		assert_eq!(parse_loadavg("0.52 0.58 0.59 1/467 12345\n"), Some(0.52));
+		assert_eq!(parse_loadavg("not a number"), None);
+	}
+}
@@ -0,0 +1,94 @@
+//! Monitoring of free disk space for jobs which write to disk (e.g. thumbnail generation). When
+//! free space drops below the configured threshold, those job types are paused so they can't
+//! fill the disk, and they are resumed once enough space is available again.
+
+use std::time::Duration;
+
+use crate::{
+	db::maintenance::DATABASE_MAINTENANCE_JOB_NAME, filesystem::image::THUMBNAIL_JOB_NAME,
+};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// The job types which write enough to disk that they are paused while free space is low
+pub(crate) const DISK_WRITING_JOB_TYPES: [&str; 2] =
+	[THUMBNAIL_JOB_NAME, DATABASE_MAINTENANCE_JOB_NAME];
+
+/// How often free disk space is checked
+pub(crate) const DISK_MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How far (as a percentage of the threshold) free space must recover above the threshold
+/// before paused jobs are resumed, so they don't flap around the threshold.
+const RECOVERY_MARGIN_PERCENT: u64 = 10;
+
+/// A change in whether free disk space is considered low
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DiskSpaceTransition {
+	/// Free space dropped below the threshold
+	Low,
+	/// Free space recovered to (comfortably) above the threshold
+	Recovered,
+}
+
+/// Returns the free disk space (in bytes) below which disk-writing jobs are paused, or `None`
+/// if the monitor is disabled.
+pub(crate) fn disk_pause_threshold_bytes(threshold_mb: u64) -> Option<u64> {
+	(threshold_mb > 0).then_some(threshold_mb * BYTES_PER_MB)
+}
+
+/// Returns the transition (if any) given the current free space and whether free space is
+/// already considered low. Free space which couldn't be determined never causes a transition.
+pub(crate) fn disk_space_transition(
+	free_bytes: Option<u64>,
+	threshold_bytes: u64,
+	is_low: bool,
+) -> Option<DiskSpaceTransition> {
+	let free_bytes = free_bytes?;
+
+	if !is_low && free_bytes < threshold_bytes {
+		Some(DiskSpaceTransition::Low)
+	} else if is_low
+		&& free_bytes >= threshold_bytes + threshold_bytes * RECOVERY_MARGIN_PERCENT / 100
+	{
+		Some(DiskSpaceTransition::Recovered)
+	} else {
+		None
+	}
+}
+
+pub(crate) fn bytes_to_mb(bytes: u64) -> u64 {
+	bytes / BYTES_PER_MB
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_disk_space_transition() {
+		let threshold = 1024 * BYTES_PER_MB;
+
+		assert_eq!(
+			disk_space_transition(Some(100 * BYTES_PER_MB), threshold, false),
+			Some(DiskSpaceTransition::Low)
+		);
+		assert_eq!(
+			disk_space_transition(Some(100 * BYTES_PER_MB), threshold, true),
+			None
+		);
+		// Just above the threshold isn't enough to recover
+		assert_eq!(
+			disk_space_transition(Some(1030 * BYTES_PER_MB), threshold, true),
+			None
+		);
+		assert_eq!(
+			disk_space_transition(Some(2048 * BYTES_PER_MB), threshold, true),
+			Some(DiskSpaceTransition::Recovered)
+		);
+		assert_eq!(
+			disk_space_transition(Some(2048 * BYTES_PER_MB), threshold, false),
+			None
+		);
+		assert_eq!(disk_space_transition(None, threshold, false), None);
+	}
+}
@@ -1,6 +1,7 @@
 use super::{
 	utils::persist_job_end, JobDetail, JobError, JobStatus, JobTrait, WorkerCtx,
 };
+use crate::{db::entity::LogLevel, Ctx};
 use uuid::Uuid;
 
 #[async_trait::async_trait]
@@ -9,6 +10,7 @@ pub trait JobExecutorTrait: Send + Sync {
 	fn description(&self) -> Option<Box<&str>>;
 	fn detail(&self) -> &Option<JobDetail>;
 	fn detail_mut(&mut self) -> &mut Option<JobDetail>;
+	async fn validate(&self, ctx: &Ctx) -> Result<(), JobError>;
 	async fn execute(&mut self, ctx: WorkerCtx) -> Result<(), JobError>;
 	async fn finish(
 		&self,
@@ -35,6 +37,23 @@ impl<InnerJob: JobTrait> Job<InnerJob> {
 			inner_job,
 		})
 	}
+
+	/// Sets the level of logs captured for the job, overriding the configured default.
+	pub fn with_log_level(mut self: Box<Self>, log_level: Option<LogLevel>) -> Box<Self> {
+		if let Some(detail) = self.detail.as_mut() {
+			detail.log_level = log_level;
+		}
+		self
+	}
+
+	/// Sets whether the job bypasses the admission checks, i.e. it starts even if the system
+	/// is under pressure.
+	pub fn with_force(mut self: Box<Self>, force: bool) -> Box<Self> {
+		if let Some(detail) = self.detail.as_mut() {
+			detail.force = force;
+		}
+		self
+	}
 }
 
 #[async_trait::async_trait]
@@ -55,6 +74,10 @@ impl<InnerJob: JobTrait> JobExecutorTrait for Job<InnerJob> {
 		self.inner_job.description()
 	}
 
+	async fn validate(&self, ctx: &Ctx) -> Result<(), JobError> {
+		self.inner_job.validate(ctx).await
+	}
+
 	async fn execute(&mut self, ctx: WorkerCtx) -> Result<(), JobError> {
 		let mut shutdown_rx = ctx.shutdown_rx();
 		let shutdown_rx_fut = shutdown_rx.recv();
@@ -69,6 +92,25 @@ impl<InnerJob: JobTrait> JobExecutorTrait for Job<InnerJob> {
 					running = false;
 
 					let duration = start.elapsed().as_millis() as u64;
+					// Whatever the job returned, its work was abandoned
+					if ctx.is_killed() {
+						tracing::warn!("Job was killed");
+						let persist_result = persist_job_end(
+							&ctx.core_ctx,
+							ctx.job_id.clone(),
+							JobStatus::Killed,
+							duration,
+							None,
+						)
+						.await;
+
+						if let Err(err) = persist_result {
+							tracing::error!(?err, "Failed to persist job end");
+						}
+
+						return Err(JobError::Killed);
+					}
+
 					let _persist_result = match job_result {
 						Ok(completed_count) => {
 							persist_job_end(
@@ -93,6 +135,24 @@ impl<InnerJob: JobTrait> JobExecutorTrait for Job<InnerJob> {
 						},
 					};
 				}
+				_ = ctx.kill_window_elapsed() => {
+					tracing::warn!("Job did not stop within the kill window, abandoning it");
+					let duration = start.elapsed().as_millis() as u64;
+					let persist_result = persist_job_end(
+						&ctx.core_ctx,
+						ctx.job_id.clone(),
+						JobStatus::Killed,
+						duration,
+						None,
+					)
+					.await;
+
+					if let Err(err) = persist_result {
+						tracing::error!(?err, "Failed to persist job end");
+					}
+
+					return Err(JobError::Killed);
+				}
 				// TODO: I think this might be wrong for pausing, in that even if the signal is
 				// meant to pause, it will kill the future above? Unless I pin it maybe?
 				shutdown_result = &mut shutdown_rx_fut => {
@@ -173,7 +233,10 @@ impl<InnerJob: JobTrait> JobExecutorTrait for Job<InnerJob> {
 		// 	persist_job_state(ctx.core_ctx.clone(), resolved_state, ctx.job_id.clone())
 		// 		.await;
 
-		ctx.emit_job_complete();
+		match job_result {
+			Err(JobError::Killed) => ctx.emit_job_killed(),
+			_ => ctx.emit_job_complete(),
+		}
 
 		Ok(())
 	}
@@ -1,19 +1,45 @@
 use prisma_client_rust::Direction;
+use serde::{Deserialize, Serialize};
+use specta::Type;
 use std::{
-	collections::{HashMap, VecDeque},
-	sync::Arc,
+	collections::{HashMap, HashSet, VecDeque},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+	},
+	time::Duration,
 };
 use tokio::sync::{broadcast, Mutex, RwLock};
 use tracing::error;
+use utoipa::ToSchema;
 
 use crate::{
-	job::{utils::persist_new_job, WorkerCtx},
+	config::StumpConfig,
+	db::entity::LogLevel,
+	event::{CoreEvent, InternalCoreTask},
+	job::{
+		utils::{persist_job_deferral, persist_new_job},
+		WorkerCtx,
+	},
 	prisma::job,
 	CoreError, Ctx,
 };
 
 use super::{
-	utils::update_job_status, worker::Worker, JobDetail, JobExecutorTrait, JobStatus,
+	admission::{
+		check_admission, AdmissionThresholds, OsSystemStats, SystemStatsProvider,
+	},
+	disk_monitor::{
+		bytes_to_mb, disk_pause_threshold_bytes, disk_space_transition,
+		DiskSpaceTransition, DISK_MONITOR_INTERVAL, DISK_WRITING_JOB_TYPES,
+	},
+	queue::{
+		estimate_queue_completion, estimate_wait_times, estimated_start_at, explain_wait,
+		get_average_durations, JobQueueSnapshot, JobQueueStatus, QueuedJob, WaitContext,
+	},
+	utils::{cancel_persisted_jobs, update_job_status},
+	worker::{Worker, JOB_KILL_WINDOW},
+	JobDetail, JobExecutorTrait, JobStatus,
 };
 
 #[derive(Debug, Clone)]
@@ -36,6 +62,8 @@ pub enum JobManagerError {
 	JobNotFound(String),
 	#[error("Job missing ID")]
 	JobMissingId,
+	#[error("Job cannot be cancelled: {0}")]
+	CancelFailed(CancelJobFailure),
 	#[error("A query error occurred {0}")]
 	QueryError(#[from] prisma_client_rust::QueryError),
 	#[error("An unknown error occurred {0}")]
@@ -50,11 +78,129 @@ impl From<CoreError> for JobManagerError {
 
 pub type JobManagerResult<T> = Result<T, JobManagerError>;
 
+/// The reason a job couldn't be cancelled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CancelJobFailure {
+	/// No job with the ID exists
+	NotFound,
+	/// The job has already finished, with the given status
+	AlreadyTerminal(JobStatus),
+	/// The job is recorded with the given (unfinished) status, but isn't queued or running,
+	/// e.g. because it was left behind by a restart
+	NotManaged(JobStatus),
+	/// The worker running the job is no longer listening for the cancellation signal, e.g.
+	/// because the job is already finishing
+	Unresponsive,
+}
+
+impl std::fmt::Display for CancelJobFailure {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			CancelJobFailure::NotFound => write!(f, "the job does not exist"),
+			CancelJobFailure::AlreadyTerminal(status) => {
+				write!(f, "the job has already finished ({})", status)
+			},
+			CancelJobFailure::NotManaged(status) => write!(
+				f,
+				"the job is recorded as {} but is not queued or running",
+				status
+			),
+			CancelJobFailure::Unresponsive => {
+				write!(f, "the job is not responding to cancellation")
+			},
+		}
+	}
+}
+
+/// The job system configuration the job manager is actually using. Unlike the config on disk,
+/// this includes any changes made at runtime, e.g. pausing a job type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type, ToSchema)]
+pub struct JobManagerConfig {
+	/// The number of jobs which may run at once
+	pub max_concurrent_jobs: u32,
+	/// The job types which are paused, including any paused because disk space is low
+	pub paused_job_types: Vec<String>,
+	/// The job types which were paused because free disk space is low
+	pub disk_paused_job_types: Vec<String>,
+	/// Whether the queue is paused
+	pub is_queue_paused: bool,
+	/// Whether queued jobs are held because of maintenance mode
+	pub is_maintenance_hold: bool,
+	/// Whether jobs are held, rather than rejected, during maintenance mode
+	pub hold_jobs_during_maintenance: bool,
+	/// How long (in milliseconds) a job may run before yielding to the runtime
+	pub job_yield_budget_ms: u64,
+	/// The limits currently in effect, including any changed at runtime
+	pub limits: JobLimits,
+	/// The default level of logs captured for jobs
+	pub job_log_level: LogLevel,
+	/// How long (in milliseconds) a killed job has to stop before it is abandoned
+	pub job_kill_window_ms: u64,
+}
+
+/// The limits the job manager enforces which may be changed at runtime, without restarting
+/// the server. They start out as configured, and runtime changes are not persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type, ToSchema)]
+pub struct JobLimits {
+	/// The free disk space (in MB) below which jobs are deferred. 0 disables the check.
+	pub job_min_free_disk_mb: u64,
+	/// The memory usage (in MB) above which jobs are deferred. 0 disables the check.
+	pub job_max_rss_mb: u64,
+	/// The load average above which jobs are deferred. 0 disables the check.
+	pub job_max_load_average: f64,
+	/// The free disk space (in MB) below which disk-writing job types are paused. 0
+	/// disables the check.
+	pub job_disk_pause_threshold_mb: u64,
+}
+
+impl From<&StumpConfig> for JobLimits {
+	fn from(config: &StumpConfig) -> Self {
+		Self {
+			job_min_free_disk_mb: config.job_min_free_disk_mb,
+			job_max_rss_mb: config.job_max_rss_mb,
+			job_max_load_average: config.job_max_load_average,
+			job_disk_pause_threshold_mb: config.job_disk_pause_threshold_mb,
+		}
+	}
+}
+
+/// A change to the job limits. Any limit which is not set is left as is.
+#[derive(Debug, Clone, Default, Deserialize, Type, ToSchema)]
+pub struct UpdateJobLimits {
+	pub job_min_free_disk_mb: Option<u64>,
+	pub job_max_rss_mb: Option<u64>,
+	pub job_max_load_average: Option<f64>,
+	pub job_disk_pause_threshold_mb: Option<u64>,
+}
+
+/// The number of jobs which may run at once
+const MAX_CONCURRENT_JOBS: usize = 1;
+
+/// How long to wait before re-checking whether a deferred job may be started
+const ADMISSION_RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 pub struct JobManager {
 	/// Queue of jobs waiting to be run in a worker thread.
 	job_queue: RwLock<VecDeque<Box<dyn JobExecutorTrait>>>,
 	/// Worker threads with a running job.
 	workers: RwLock<HashMap<String, Arc<Mutex<Worker>>>>,
+	/// The names of job types which are paused. Jobs of these types stay queued.
+	paused_job_types: RwLock<HashSet<String>>,
+	/// Whether the queue is paused. Running jobs are left to finish, but no queued job is
+	/// started until the queue is resumed.
+	queue_paused: AtomicBool,
+	/// The source of the system stats used to decide whether a job may be started.
+	stats_provider: Arc<dyn SystemStatsProvider>,
+	/// The reason the next job was deferred rather than started, if it was.
+	deferral_reason: RwLock<Option<String>>,
+	/// Whether a re-check of the deferred job is already scheduled.
+	admission_recheck_scheduled: AtomicBool,
+	/// The job types which were paused because free disk space is low. This excludes types
+	/// which were already paused, so that recovering doesn't resume them.
+	disk_paused_job_types: Mutex<Option<Vec<String>>>,
+	/// The limits used for admission control and the disk space monitor, which may be
+	/// changed at runtime.
+	limits: RwLock<JobLimits>,
 	/// A channel to send shutdown signals to all or some workers.
 	shutdown_tx: Arc<broadcast::Sender<JobManagerShutdownSignal>>,
 	/// A pointer to the core context.
@@ -64,15 +210,34 @@ pub struct JobManager {
 impl JobManager {
 	pub fn new(core_ctx: Arc<Ctx>) -> Self {
 		let (shutdown_tx, _) = broadcast::channel(1024);
+		let stats_provider = OsSystemStats::new(core_ctx.config.get_config_dir());
 
 		Self {
 			job_queue: RwLock::new(VecDeque::new()),
 			workers: RwLock::new(HashMap::new()),
+			paused_job_types: RwLock::new(HashSet::new()),
+			queue_paused: AtomicBool::new(false),
+			stats_provider: Arc::new(stats_provider),
+			deferral_reason: RwLock::new(None),
+			admission_recheck_scheduled: AtomicBool::new(false),
+			disk_paused_job_types: Mutex::new(None),
+			limits: RwLock::new(JobLimits::from(core_ctx.config.as_ref())),
 			shutdown_tx: Arc::new(shutdown_tx),
 			core_ctx,
 		}
 	}
 
+	/// Replaces the source of the system stats used for admission control.
+	pub fn with_stats_provider(
+		self,
+		stats_provider: Arc<dyn SystemStatsProvider>,
+	) -> Self {
+		Self {
+			stats_provider,
+			..self
+		}
+	}
+
 	/// Wrap the job manager in an Arc.
 	pub fn arced(self) -> Arc<Self> {
 		Arc::new(self)
@@ -84,28 +249,32 @@ impl JobManager {
 	}
 
 	/// Cancels a job by ID. If the job is not running but in the queue, it will be removed.
+	/// If the job can't be cancelled, the error carries a [CancelJobFailure] explaining why.
 	pub async fn cancel_job(self: Arc<Self>, job_id: String) -> JobManagerResult<()> {
 		tracing::trace!(job_id, "Attempting to cancel job");
 		let mut workers = self.workers.write().await;
 		if workers.get(&job_id).is_some() {
 			tracing::trace!(job_id, "Sending shutdown signal to worker");
-			self.shutdown_tx
-				.send(JobManagerShutdownSignal::Worker(job_id.clone()))
-				.map_or_else(
-					|error| {
-						tracing::error!(
-							?error,
-							"Failed to send shutdown signal to worker!"
-						);
-					},
-					|_| {
-						tracing::trace!(job_id, "Shutdown signal sent to worker");
-					},
-				);
+			let result = self
+				.shutdown_tx
+				.send(JobManagerShutdownSignal::Worker(job_id.clone()));
 			workers.remove(&job_id);
 			drop(workers);
-			return Ok(());
+
+			return match result {
+				Ok(_) => {
+					tracing::trace!(job_id, "Shutdown signal sent to worker");
+					Ok(())
+				},
+				Err(error) => {
+					tracing::error!(?error, "Failed to send shutdown signal to worker!");
+					Err(JobManagerError::CancelFailed(
+						CancelJobFailure::Unresponsive,
+					))
+				},
+			};
 		}
+		drop(workers);
 
 		let mut job_queue = self.job_queue.write().await;
 		let maybe_index = job_queue.iter().position(|job| {
@@ -121,8 +290,44 @@ impl JobManager {
 			job_queue.remove(job_index);
 			return Ok(());
 		}
+		drop(job_queue);
 
-		Err(JobManagerError::WorkerNotFound(job_id))
+		let persisted_job = self
+			.core_ctx
+			.db
+			.job()
+			.find_unique(job::id::equals(job_id))
+			.exec()
+			.await?;
+		let failure = match persisted_job {
+			Some(job) => {
+				let status = JobStatus::from(job.status.as_str());
+				if status.is_terminal() {
+					CancelJobFailure::AlreadyTerminal(status)
+				} else {
+					CancelJobFailure::NotManaged(status)
+				}
+			},
+			None => CancelJobFailure::NotFound,
+		};
+
+		Err(JobManagerError::CancelFailed(failure))
+	}
+
+	/// Kills a running job, flipping its kill switch. Unlike [JobManager::cancel_job], the job
+	/// is expected to abandon its work immediately, and is abandoned by the executor if it
+	/// doesn't stop within [JOB_KILL_WINDOW](super::JOB_KILL_WINDOW). A job which isn't
+	/// running is cancelled instead, since it has no work to abandon.
+	pub async fn kill_job(self: Arc<Self>, job_id: String) -> JobManagerResult<()> {
+		let worker = self.workers.read().await.get(&job_id).cloned();
+		match worker {
+			Some(worker) => {
+				tracing::warn!(job_id, "Killing job");
+				worker.lock().await.kill();
+				Ok(())
+			},
+			None => self.cancel_job(job_id).await,
+		}
 	}
 
 	/// DONT USE: This won't work as expected until pausing is supported. This will
@@ -146,15 +351,38 @@ impl JobManager {
 		self: Arc<Self>,
 		mut job: Box<dyn JobExecutorTrait>,
 	) -> JobManagerResult<()> {
+		if self.core_ctx.is_holding_jobs() {
+			tracing::debug!(job = job.name(), "Holding job until maintenance mode ends");
+			self.job_queue.write().await.push_back(job);
+			return Ok(());
+		} else if self.paused_job_types.read().await.contains(job.name()) {
+			tracing::debug!(job = job.name(), "Holding job until its type is unpaused");
+			self.job_queue.write().await.push_back(job);
+			return Ok(());
+		} else if self.is_queue_paused() {
+			tracing::debug!(job = job.name(), "Holding job until the queue is resumed");
+			self.job_queue.write().await.push_back(job);
+			return Ok(());
+		}
+
 		let mut workers = self.workers.write().await;
 
-		if workers.is_empty() {
+		if workers.len() < MAX_CONCURRENT_JOBS {
+			if !self.admit(job.detail().as_ref()).await {
+				self.job_queue.write().await.push_back(job);
+				return Ok(());
+			}
+
 			println!("Starting job: {}", job.name());
 
-			let job_detail = job
+			let mut job_detail = job
 				.detail_mut()
 				.take()
 				.expect("Job initialized without state!");
+			let log_level = job_detail
+				.log_level
+				.unwrap_or(self.core_ctx.config.job_log_level);
+			job_detail.log_level = Some(log_level);
 
 			let job_id = job_detail.id.clone();
 			let job_name = job.name().to_string();
@@ -164,16 +392,19 @@ impl JobManager {
 				job_id.clone(),
 				job_name,
 				job_description,
+				job_detail.created_at.clone(),
+				log_level,
 			)
 			.await?;
 
 			let worker = Worker::new(job, job_detail);
 			let worker_mtx = Arc::new(Mutex::new(worker));
-			let worker_ctx = WorkerCtx {
-				job_id: job_id.clone(),
-				shutdown_tx: self.get_shutdown_tx(),
-				core_ctx: Arc::clone(&self.core_ctx),
-			};
+			let worker_ctx = WorkerCtx::new(
+				job_id.clone(),
+				self.get_shutdown_tx(),
+				Arc::clone(&self.core_ctx),
+			)
+			.with_log_level(log_level);
 
 			Worker::spawn(worker_ctx, Arc::clone(&self), Arc::clone(&worker_mtx))
 				.await
@@ -204,15 +435,357 @@ impl JobManager {
 			return Err(JobManagerError::WorkerNotFound(job_id));
 		}
 
-		let next_job = self.job_queue.write().await.pop_front();
-		if let Some(job) = next_job {
+		// Held jobs are left in the queue, in order, until maintenance mode ends (or the
+		// queue is resumed)
+		if self.core_ctx.is_holding_jobs() || self.is_queue_paused() {
+			return Ok(());
+		}
+
+		// Deferred jobs are left at the front of the queue until they are admitted
+		match self.next_runnable_job_detail().await {
+			Some(detail) if self.admit(Some(&detail)).await => {},
+			_ => return Ok(()),
+		}
+
+		if let Some(job) = self.pop_next_runnable_job().await {
+			// The job was already validated when it was first enqueued
 			// TODO: error handling
-			let _ = self.core_ctx.dispatch_job(job);
+			let _ = self
+				.core_ctx
+				.dispatch_task(InternalCoreTask::EnqueueJob(job));
 		}
 
 		Ok(())
 	}
 
+	/// Starts the next job in the queue if no job is running. This is used to resume the
+	/// jobs held while maintenance mode was active (or while their type was paused, or while
+	/// the system was under pressure), since nothing else would start them.
+	pub async fn resume_held_jobs(self: Arc<Self>) -> JobManagerResult<()> {
+		if self.core_ctx.is_holding_jobs()
+			|| self.is_queue_paused()
+			|| self.workers.read().await.len() >= MAX_CONCURRENT_JOBS
+		{
+			return Ok(());
+		}
+
+		match self.next_runnable_job_detail().await {
+			Some(detail) if self.admit(Some(&detail)).await => {},
+			_ => return Ok(()),
+		}
+
+		if let Some(job) = self.pop_next_runnable_job().await {
+			tracing::debug!(
+				held_count = self.job_queue.read().await.len() + 1,
+				"Resuming held jobs"
+			);
+			self.enqueue_job(job).await?;
+		}
+
+		Ok(())
+	}
+
+	/// Returns whether a job may be started given the current system stats. When the system
+	/// is under pressure, the reason is recorded and emitted, and a re-check is scheduled.
+	/// Jobs forced by the user are always admitted.
+	async fn admit(self: &Arc<Self>, detail: Option<&JobDetail>) -> bool {
+		if let Some(detail) = detail.filter(|detail| detail.force) {
+			tracing::debug!(
+				job_id = detail.id,
+				"Bypassing admission checks for forced job"
+			);
+			return true;
+		}
+
+		let thresholds = AdmissionThresholds::from(&*self.limits.read().await);
+		let reason = check_admission(&self.stats_provider.stats(), &thresholds);
+		*self.deferral_reason.write().await = reason.clone();
+
+		let reason = match reason {
+			Some(reason) => reason,
+			None => return true,
+		};
+
+		tracing::warn!(
+			job_id = detail.map(|detail| detail.id.as_str()),
+			reason,
+			"Deferring job while the system is under pressure"
+		);
+		if let Some(detail) = detail {
+			if let Err(error) =
+				persist_job_deferral(&self.core_ctx, detail, reason.clone()).await
+			{
+				error!(?error, "Failed to persist job deferral");
+			}
+			self.core_ctx.emit_event(CoreEvent::JobDeferred {
+				job_id: detail.id.clone(),
+				reason,
+			});
+		}
+		self.schedule_admission_recheck();
+
+		false
+	}
+
+	/// Schedules an attempt to start the deferred job, unless one is already scheduled.
+	fn schedule_admission_recheck(self: &Arc<Self>) {
+		if self
+			.admission_recheck_scheduled
+			.swap(true, Ordering::SeqCst)
+		{
+			return;
+		}
+
+		let job_manager = Arc::clone(self);
+		tokio::spawn(async move {
+			tokio::time::sleep(ADMISSION_RECHECK_INTERVAL).await;
+			job_manager
+				.admission_recheck_scheduled
+				.store(false, Ordering::SeqCst);
+
+			if let Err(error) = job_manager.resume_held_jobs().await {
+				error!(?error, "Failed to resume deferred jobs");
+			}
+		});
+	}
+
+	/// Spawns a task which periodically checks free disk space, pausing the job types which
+	/// write to disk while it is low. The threshold is read on every check, since it may be
+	/// changed at runtime.
+	pub fn spawn_disk_space_monitor(self: Arc<Self>) {
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(DISK_MONITOR_INTERVAL);
+			loop {
+				interval.tick().await;
+				self.clone().check_disk_space().await;
+			}
+		});
+	}
+
+	/// Checks free disk space against the configured threshold. Disk-writing job types are
+	/// paused (and an alert is emitted) when it drops below the threshold, and resumed once it
+	/// has recovered, or once the threshold is disabled.
+	pub async fn check_disk_space(self: Arc<Self>) {
+		let threshold_bytes = disk_pause_threshold_bytes(
+			self.limits.read().await.job_disk_pause_threshold_mb,
+		);
+
+		let mut disk_paused_job_types = self.disk_paused_job_types.lock().await;
+		let (transition, free_bytes, threshold_bytes) = match threshold_bytes {
+			Some(threshold_bytes) => {
+				let free_bytes = self.stats_provider.stats().free_disk_bytes;
+				let transition = disk_space_transition(
+					free_bytes,
+					threshold_bytes,
+					disk_paused_job_types.is_some(),
+				);
+				(transition, free_bytes, threshold_bytes)
+			},
+			// The monitor was disabled at runtime, so anything it paused is resumed
+			None if disk_paused_job_types.is_some() => (
+				Some(DiskSpaceTransition::Recovered),
+				self.stats_provider.stats().free_disk_bytes,
+				0,
+			),
+			None => return,
+		};
+		let free_mb = bytes_to_mb(free_bytes.unwrap_or_default());
+
+		match transition {
+			Some(DiskSpaceTransition::Low) => {
+				let mut paused_job_types = self.paused_job_types.write().await;
+				let newly_paused = DISK_WRITING_JOB_TYPES
+					.iter()
+					.filter(|job_name| paused_job_types.insert(job_name.to_string()))
+					.map(|job_name| job_name.to_string())
+					.collect::<Vec<String>>();
+				drop(paused_job_types);
+
+				let threshold_mb = bytes_to_mb(threshold_bytes);
+				tracing::warn!(
+					free_mb,
+					threshold_mb,
+					?newly_paused,
+					"Free disk space is low, pausing jobs which write to disk"
+				);
+				self.core_ctx.emit_event(CoreEvent::DiskSpaceLow {
+					free_mb,
+					threshold_mb,
+					paused_job_types: newly_paused.clone(),
+				});
+				*disk_paused_job_types = Some(newly_paused);
+			},
+			Some(DiskSpaceTransition::Recovered) => {
+				let resumed = disk_paused_job_types.take().unwrap_or_default();
+				drop(disk_paused_job_types);
+
+				tracing::info!(
+					free_mb,
+					?resumed,
+					"Free disk space has recovered, resuming paused jobs"
+				);
+				for job_name in resumed.iter() {
+					self.clone()
+						.set_job_type_paused(job_name.clone(), false)
+						.await;
+				}
+				self.core_ctx.emit_event(CoreEvent::DiskSpaceRecovered {
+					free_mb,
+					resumed_job_types: resumed,
+				});
+			},
+			None => {},
+		}
+	}
+
+	/// Returns the detail of the first job in the queue whose type isn't paused.
+	async fn next_runnable_job_detail(&self) -> Option<JobDetail> {
+		let paused_job_types = self.paused_job_types.read().await;
+		let job_queue = self.job_queue.read().await;
+
+		let index = next_runnable_index(
+			job_queue.iter().map(|job| job.name()),
+			&paused_job_types,
+		)?;
+		job_queue.get(index)?.detail().clone()
+	}
+
+	/// Removes and returns the first job in the queue whose type isn't paused.
+	async fn pop_next_runnable_job(&self) -> Option<Box<dyn JobExecutorTrait>> {
+		let paused_job_types = self.paused_job_types.read().await;
+		let mut job_queue = self.job_queue.write().await;
+
+		let index = next_runnable_index(
+			job_queue.iter().map(|job| job.name()),
+			&paused_job_types,
+		)?;
+		job_queue.remove(index)
+	}
+
+	/// Pauses or unpauses a job type, returning the job types which are now paused. When a
+	/// type is unpaused, its held jobs are eligible to run again.
+	pub async fn set_job_type_paused(
+		self: Arc<Self>,
+		job_name: String,
+		paused: bool,
+	) -> Vec<String> {
+		let mut paused_job_types = self.paused_job_types.write().await;
+		if paused {
+			paused_job_types.insert(job_name.clone());
+		} else {
+			paused_job_types.remove(&job_name);
+		}
+		drop(paused_job_types);
+		tracing::info!(job_name, paused, "Updated paused job type");
+
+		if !paused {
+			if let Err(error) = self.clone().resume_held_jobs().await {
+				error!(?error, "Failed to resume held jobs");
+			}
+		}
+
+		self.get_paused_job_types().await
+	}
+
+	/// Pauses (or resumes) the queue, returning whether it is now paused. While the queue is
+	/// paused, running jobs are left to finish and new jobs are queued, but no queued job is
+	/// started. Unlike maintenance mode, nothing is rejected or cancelled.
+	pub fn set_queue_paused(&self, paused: bool) -> bool {
+		let was_paused = self.queue_paused.swap(paused, Ordering::SeqCst);
+		tracing::info!(paused, "Updated queue paused state");
+
+		if was_paused && !paused {
+			if let Err(error) = self
+				.core_ctx
+				.dispatch_task(InternalCoreTask::ResumeHeldJobs)
+			{
+				error!(?error, "Failed to dispatch task to resume queued jobs");
+			}
+		}
+
+		paused
+	}
+
+	pub fn is_queue_paused(&self) -> bool {
+		self.queue_paused.load(Ordering::SeqCst)
+	}
+
+	/// Returns the names of the job types which are currently paused.
+	pub async fn get_paused_job_types(&self) -> Vec<String> {
+		let mut paused_job_types = self
+			.paused_job_types
+			.read()
+			.await
+			.iter()
+			.cloned()
+			.collect::<Vec<String>>();
+		paused_job_types.sort();
+		paused_job_types
+	}
+
+	/// Returns the job system configuration currently in effect, see [JobManagerConfig].
+	pub async fn config_snapshot(&self) -> JobManagerConfig {
+		let limits = *self.limits.read().await;
+		let config = &self.core_ctx.config;
+		// The disk monitor locks these before the paused job types, so they're read first
+		let disk_paused_job_types = self
+			.disk_paused_job_types
+			.lock()
+			.await
+			.clone()
+			.unwrap_or_default();
+
+		JobManagerConfig {
+			max_concurrent_jobs: MAX_CONCURRENT_JOBS as u32,
+			paused_job_types: self.get_paused_job_types().await,
+			disk_paused_job_types,
+			is_queue_paused: self.is_queue_paused(),
+			is_maintenance_hold: self.core_ctx.is_holding_jobs(),
+			hold_jobs_during_maintenance: config.hold_jobs_during_maintenance,
+			job_yield_budget_ms: config.job_yield_budget_ms,
+			limits,
+			job_log_level: config.job_log_level,
+			job_kill_window_ms: JOB_KILL_WINDOW.as_millis() as u64,
+		}
+	}
+
+	/// Changes the limits used for admission control and the disk space monitor, without
+	/// restarting the server. The change isn't persisted, so the configured limits apply again
+	/// after a restart. Returns the job system configuration now in effect.
+	pub async fn update_limits(
+		self: Arc<Self>,
+		update: UpdateJobLimits,
+	) -> JobManagerConfig {
+		{
+			let mut limits = self.limits.write().await;
+			if let Some(job_min_free_disk_mb) = update.job_min_free_disk_mb {
+				limits.job_min_free_disk_mb = job_min_free_disk_mb;
+			}
+			if let Some(job_max_rss_mb) = update.job_max_rss_mb {
+				limits.job_max_rss_mb = job_max_rss_mb;
+			}
+			if let Some(job_max_load_average) = update.job_max_load_average {
+				limits.job_max_load_average = job_max_load_average;
+			}
+			if let Some(job_disk_pause_threshold_mb) = update.job_disk_pause_threshold_mb
+			{
+				limits.job_disk_pause_threshold_mb = job_disk_pause_threshold_mb;
+			}
+			tracing::info!(?limits, "Updated job limits");
+		}
+
+		// The new limits may pause or resume disk-writing job types, or admit a deferred job
+		self.clone().check_disk_space().await;
+		if let Err(error) = self.clone().resume_held_jobs().await {
+			error!(
+				?error,
+				"Failed to resume held jobs after updating job limits"
+			);
+		}
+
+		self.config_snapshot().await
+	}
+
 	/// Removes a job from the pending queue by index.
 	async fn dequeue_pending_job(self: Arc<Self>, index: usize) -> JobManagerResult<()> {
 		let result = self.job_queue.write().await.remove(index);
@@ -274,6 +847,140 @@ impl JobManager {
 		})
 	}
 
+	/// Returns a snapshot of the running job and the queue, including each queued job's
+	/// position and estimated start. Estimates are based on the average duration of
+	/// recently completed jobs of the same type.
+	pub async fn queue_snapshot(self: Arc<Self>) -> JobManagerResult<JobQueueSnapshot> {
+		let average_durations = get_average_durations(self.core_ctx.get_db()).await?;
+
+		let mut running_job_id = None;
+		let mut running_job_remaining_ms = Some(0);
+		let mut running_job_blocking_tasks = None;
+		for (job_id, worker) in self.workers.read().await.iter() {
+			let worker = worker.lock().await;
+			running_job_id = Some(job_id.clone());
+			running_job_blocking_tasks = Some(worker.running_blocking_tasks());
+			running_job_remaining_ms = average_durations
+				.get(&worker.job_detail().name)
+				.map(|average_ms| average_ms.saturating_sub(worker.ms_elapsed()));
+		}
+
+		let is_queue_paused = self.is_queue_paused();
+		let is_maintenance_hold = self.core_ctx.is_holding_jobs();
+		let is_holding_jobs = is_maintenance_hold || is_queue_paused;
+		// The disk monitor locks these before the paused job types, so they're read first
+		let disk_paused_job_types = self
+			.disk_paused_job_types
+			.lock()
+			.await
+			.clone()
+			.unwrap_or_default();
+		let paused_job_types = self.paused_job_types.read().await;
+		let deferred_reason = self.deferral_reason.read().await.clone();
+		let job_queue = self.job_queue.read().await;
+
+		let queued = job_queue
+			.iter()
+			.map(|job| {
+				(
+					average_durations.get(job.name()).copied(),
+					is_holding_jobs || paused_job_types.contains(job.name()),
+				)
+			})
+			.collect::<Vec<_>>();
+		let wait_times = estimate_wait_times(running_job_remaining_ms, &queued);
+		let completion_estimate = estimate_queue_completion(
+			running_job_id.as_ref().map(|_| running_job_remaining_ms),
+			&queued,
+		);
+
+		let mut runnable_ahead = 0;
+		let queued = job_queue
+			.iter()
+			.zip(queued.iter().zip(wait_times))
+			.enumerate()
+			.map(|(position, (job, ((_, is_held), wait_ms)))| {
+				let wait_explanation = explain_wait(WaitContext {
+					job_name: job.name(),
+					is_maintenance_hold,
+					is_queue_paused,
+					is_disk_paused: disk_paused_job_types
+						.iter()
+						.any(|name| name == job.name()),
+					is_type_paused: paused_job_types.contains(job.name()),
+					runnable_ahead,
+					has_running_job: running_job_id.is_some(),
+					deferred_reason: deferred_reason.as_deref(),
+				});
+				if !is_held {
+					runnable_ahead += 1;
+				}
+
+				QueuedJob {
+					id: job
+						.detail()
+						.as_ref()
+						.map(|detail| detail.id.clone())
+						.unwrap_or_default(),
+					name: job.name().to_string(),
+					description: job.description().map(|s| s.to_string()),
+					position,
+					is_held: *is_held,
+					estimated_wait_ms: wait_ms,
+					estimated_start_at: wait_ms.map(estimated_start_at),
+					wait_explanation,
+				}
+			})
+			.collect();
+
+		Ok(JobQueueSnapshot {
+			deferred_reason,
+			completion_estimate,
+			running_job_remaining_ms: running_job_id
+				.as_ref()
+				.and(running_job_remaining_ms),
+			running_job_blocking_tasks,
+			running_job_id,
+			is_paused: is_queue_paused,
+			queued,
+		})
+	}
+
+	/// Returns the status of a job by ID, or `None` if it is neither queued nor persisted.
+	/// While the job has yet to start, its position and estimated start are taken from
+	/// [JobManager::queue_snapshot] so that the two always agree.
+	pub async fn job_queue_status(
+		self: Arc<Self>,
+		job_id: String,
+	) -> JobManagerResult<Option<JobQueueStatus>> {
+		let queued = self
+			.clone()
+			.queue_snapshot()
+			.await?
+			.into_queued_job(&job_id);
+		if queued.is_some() {
+			return Ok(Some(JobQueueStatus {
+				id: job_id,
+				job: None,
+				queued,
+			}));
+		}
+
+		let job = self
+			.core_ctx
+			.get_db()
+			.job()
+			.find_unique(job::id::equals(job_id.clone()))
+			.exec()
+			.await?;
+
+		Ok(job.map(|job| JobQueueStatus {
+			id: job_id,
+			job: Some(JobDetail::from(job)),
+			queued: None,
+		}))
+	}
+
 	// TODO: remove this...
 	pub async fn report(self: Arc<Self>) -> JobManagerResult<Vec<JobDetail>> {
 		let db = self.core_ctx.get_db();
@@ -293,19 +1000,8 @@ impl JobManager {
 
 	// TODO: this will eventually go away!
 	pub async fn init(self: Arc<Self>) -> JobManagerResult<()> {
-		let result = self
-			.core_ctx
-			.db
-			.job()
-			.update_many(
-				vec![job::status::equals(JobStatus::Running.to_string())],
-				vec![
-					job::status::set(JobStatus::Cancelled.to_string()),
-					job::completed_at::set(None),
-				],
-			)
-			.exec()
-			.await?;
+		let result =
+			cancel_persisted_jobs(&self.core_ctx.db, vec![JobStatus::Running]).await?;
 
 		tracing::trace!(canceled_count = ?result, "Canceling running jobs on startup");
 
@@ -325,3 +1021,563 @@ impl JobManager {
 		self.clear_queue().await;
 	}
 }
+
+/// Returns the index of the first job (by name) which isn't of a paused type.
+fn next_runnable_index<'a>(
+	job_names: impl Iterator<Item = &'a str>,
+	paused_job_types: &HashSet<String>,
+) -> Option<usize> {
+	job_names
+		.enumerate()
+		.find(|(_, name)| !paused_job_types.contains(*name))
+		.map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::sync::mpsc::unbounded_channel;
+
+	use super::*;
+	use crate::{
+		config::StumpConfig,
+		event::InternalCoreTask,
+		job::{Job, JobError, JobTrait, SystemStats},
+		prisma::PrismaClient,
+	};
+
+	struct FakeSystemStats(SystemStats);
+
+	impl SystemStatsProvider for FakeSystemStats {
+		fn stats(&self) -> SystemStats {
+			self.0.clone()
+		}
+	}
+
+	/// System stats which can be changed during a test
+	struct ChangingSystemStats(std::sync::Mutex<SystemStats>);
+
+	impl SystemStatsProvider for ChangingSystemStats {
+		fn stats(&self) -> SystemStats {
+			self.0.lock().unwrap().clone()
+		}
+	}
+
+	struct NoopJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for NoopJob {
+		fn name(&self) -> &'static str {
+			"noop_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, _ctx: WorkerCtx) -> Result<u64, JobError> {
+			Ok(0)
+		}
+	}
+
+	struct OtherJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for OtherJob {
+		fn name(&self) -> &'static str {
+			"other_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, _ctx: WorkerCtx) -> Result<u64, JobError> {
+			Ok(0)
+		}
+	}
+
+	struct PanickingJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for PanickingJob {
+		fn name(&self) -> &'static str {
+			"panicking_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, _ctx: WorkerCtx) -> Result<u64, JobError> {
+			panic!("Malformed archive")
+		}
+	}
+
+	async fn wait_for_job_status(
+		client: &PrismaClient,
+		job_id: &str,
+		status: JobStatus,
+	) -> bool {
+		let start = std::time::Instant::now();
+		while start.elapsed() < Duration::from_secs(5) {
+			let current = client
+				.job()
+				.find_unique(job::id::equals(job_id.to_string()))
+				.exec()
+				.await
+				.unwrap()
+				.map(|job| JobStatus::from(job.status.as_str()));
+			if current.as_ref() == Some(&status) {
+				return true;
+			}
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+		false
+	}
+
+	#[tokio::test]
+	async fn test_queue_continues_after_job_panic() {
+		let (client, _dir) = Ctx::mock_db().await;
+		let (internal_tx, mut internal_rx) = unbounded_channel::<InternalCoreTask>();
+		let core_ctx = Arc::new(Ctx {
+			internal_sender: Arc::new(internal_tx),
+			..Ctx::mock_with_client(client, StumpConfig::debug())
+		});
+		let job_manager = JobManager::new(core_ctx.clone())
+			.with_stats_provider(Arc::new(FakeSystemStats(SystemStats::default())))
+			.arced();
+
+		let panicking = Job::new(PanickingJob);
+		let panicking_id = panicking.detail().clone().unwrap().id;
+		let next = Job::new(NoopJob);
+		let next_id = next.detail().clone().unwrap().id;
+		job_manager.clone().enqueue_job(panicking).await.unwrap();
+		job_manager.clone().enqueue_job(next).await.unwrap();
+		assert_eq!(job_manager.job_queue.read().await.len(), 1);
+
+		// Once the panicking job is dequeued, the next job is sent back to be started
+		let job = loop {
+			let task = tokio::time::timeout(Duration::from_secs(5), internal_rx.recv())
+				.await
+				.expect("The next job should have been dispatched")
+				.expect("The internal channel should be open");
+			if let InternalCoreTask::EnqueueJob(job) = task {
+				break job;
+			}
+		};
+		job_manager.clone().enqueue_job(job).await.unwrap();
+
+		assert!(
+			wait_for_job_status(&core_ctx.db, &panicking_id, JobStatus::Failed).await
+		);
+		assert!(wait_for_job_status(&core_ctx.db, &next_id, JobStatus::Completed).await);
+		assert!(job_manager.job_queue.read().await.is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_paused_job_type_stays_queued_while_others_run() {
+		let (client, _dir) = Ctx::mock_db().await;
+		let (internal_tx, mut internal_rx) = unbounded_channel::<InternalCoreTask>();
+		let core_ctx = Arc::new(Ctx {
+			internal_sender: Arc::new(internal_tx),
+			..Ctx::mock_with_client(client, StumpConfig::debug())
+		});
+		let job_manager = JobManager::new(core_ctx.clone())
+			.with_stats_provider(Arc::new(FakeSystemStats(SystemStats::default())))
+			.arced();
+
+		let paused = job_manager
+			.clone()
+			.set_job_type_paused(String::from("noop_job"), true)
+			.await;
+		assert_eq!(paused, vec![String::from("noop_job")]);
+
+		let held = Job::new(NoopJob);
+		let held_id = held.detail().clone().unwrap().id;
+		let other = Job::new(OtherJob);
+		let other_id = other.detail().clone().unwrap().id;
+		job_manager.clone().enqueue_job(held).await.unwrap();
+		job_manager.clone().enqueue_job(other).await.unwrap();
+
+		// The other job starts straight away, while the paused job stays queued
+		assert!(wait_for_job_status(&core_ctx.db, &other_id, JobStatus::Completed).await);
+		assert_eq!(job_manager.job_queue.read().await.len(), 1);
+		let held_job = core_ctx
+			.db
+			.job()
+			.find_unique(job::id::equals(held_id))
+			.exec()
+			.await
+			.unwrap();
+		assert!(
+			held_job.is_none(),
+			"The paused job should not have been started"
+		);
+		assert!(!matches!(
+			internal_rx.try_recv(),
+			Ok(InternalCoreTask::EnqueueJob(_))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_job_deferred_under_pressure() {
+		let (client, _dir) = Ctx::mock_db().await;
+		let core_ctx = Arc::new(Ctx::mock_with_client(
+			client,
+			StumpConfig {
+				job_min_free_disk_mb: 512,
+				..StumpConfig::debug()
+			},
+		));
+		let mut receiver = core_ctx.get_client_receiver();
+
+		let low_disk = SystemStats {
+			free_disk_bytes: Some(100 * 1024 * 1024),
+			..Default::default()
+		};
+		let job_manager = JobManager::new(core_ctx.clone())
+			.with_stats_provider(Arc::new(FakeSystemStats(low_disk)))
+			.arced();
+
+		let job = Job::new(NoopJob);
+		let job_id = job.detail().clone().unwrap().id;
+		job_manager.clone().enqueue_job(job).await.unwrap();
+
+		// The job should have been left in the queue, rather than started
+		assert!(job_manager.workers.read().await.is_empty());
+		assert_eq!(job_manager.job_queue.read().await.len(), 1);
+		let reason = job_manager.deferral_reason.read().await.clone();
+		assert!(reason.unwrap().contains("Free disk space (100MB)"));
+
+		match receiver.try_recv() {
+			Ok(CoreEvent::JobDeferred {
+				job_id: deferred_job_id,
+				..
+			}) => assert_eq!(deferred_job_id, job_id),
+			other => panic!("Expected a JobDeferred event, got {:?}", other),
+		}
+
+		// The deferral reason is recorded on the (still queued) job
+		let deferred_job = core_ctx
+			.db
+			.job()
+			.find_unique(job::id::equals(job_id.clone()))
+			.with(job::logs::fetch(vec![]))
+			.exec()
+			.await
+			.unwrap()
+			.expect("The deferred job should have been persisted");
+		assert_eq!(
+			JobStatus::from(deferred_job.status.as_str()),
+			JobStatus::Queued
+		);
+		let logs = deferred_job.logs().unwrap();
+		assert_eq!(logs.len(), 1);
+		assert_eq!(logs[0].level, LogLevel::Warn.to_string());
+		assert!(logs[0].message.contains("Free disk space (100MB)"));
+	}
+
+	#[tokio::test]
+	async fn test_forced_job_bypasses_admission() {
+		let (client, _dir) = Ctx::mock_db().await;
+		let core_ctx = Arc::new(Ctx::mock_with_client(
+			client,
+			StumpConfig {
+				job_min_free_disk_mb: 512,
+				..StumpConfig::debug()
+			},
+		));
+		let low_disk = SystemStats {
+			free_disk_bytes: Some(100 * 1024 * 1024),
+			..Default::default()
+		};
+		let job_manager = JobManager::new(core_ctx.clone())
+			.with_stats_provider(Arc::new(FakeSystemStats(low_disk)))
+			.arced();
+
+		let job = Job::new(NoopJob).with_force(true);
+		let job_id = job.detail().clone().unwrap().id;
+		job_manager.clone().enqueue_job(job).await.unwrap();
+
+		// The forced job starts despite the low disk space
+		assert!(job_manager.job_queue.read().await.is_empty());
+		assert!(job_manager.deferral_reason.read().await.is_none());
+		assert!(wait_for_job_status(&core_ctx.db, &job_id, JobStatus::Completed).await);
+	}
+
+	#[tokio::test]
+	async fn test_paused_queue_keeps_jobs() {
+		let (client, _mock) = PrismaClient::_mock();
+		let (internal_tx, mut internal_rx) = unbounded_channel::<InternalCoreTask>();
+		let core_ctx = Arc::new(Ctx {
+			internal_sender: Arc::new(internal_tx),
+			..Ctx::mock_with_client(client, StumpConfig::debug())
+		});
+		let job_manager = JobManager::new(core_ctx)
+			.with_stats_provider(Arc::new(FakeSystemStats(SystemStats::default())))
+			.arced();
+
+		// Simulate a running job
+		let running = Job::new(NoopJob);
+		let running_detail = running.detail().clone().unwrap();
+		let running_id = running_detail.id.clone();
+		job_manager.workers.write().await.insert(
+			running_id.clone(),
+			Arc::new(Mutex::new(Worker::new(running, running_detail))),
+		);
+
+		assert!(job_manager.set_queue_paused(true));
+		job_manager
+			.clone()
+			.enqueue_job(Job::new(NoopJob))
+			.await
+			.unwrap();
+		assert_eq!(job_manager.job_queue.read().await.len(), 1);
+
+		// Once the running job finishes, the queued job should not be started
+		job_manager.clone().dequeue_job(running_id).await.unwrap();
+		assert!(job_manager.workers.read().await.is_empty());
+		assert_eq!(job_manager.job_queue.read().await.len(), 1);
+		assert!(internal_rx.try_recv().is_err());
+
+		// Resuming the queue should restart the queued job
+		assert!(!job_manager.set_queue_paused(false));
+		assert!(matches!(
+			internal_rx.try_recv(),
+			Ok(InternalCoreTask::ResumeHeldJobs)
+		));
+	}
+
+	#[test]
+	fn test_paused_job_types_stay_queued() {
+		let paused = HashSet::from([String::from("media_conversion")]);
+		let queue = ["media_conversion", "media_conversion", "library_scan"];
+
+		assert_eq!(next_runnable_index(queue.into_iter(), &paused), Some(2));
+		assert_eq!(
+			next_runnable_index(queue.into_iter(), &HashSet::new()),
+			Some(0)
+		);
+		assert_eq!(
+			next_runnable_index(["media_conversion"].into_iter(), &paused),
+			None
+		);
+	}
+
+	#[tokio::test]
+	async fn test_low_disk_space_pauses_writing_jobs() {
+		let (client, _mock) = PrismaClient::_mock();
+		let core_ctx = Arc::new(Ctx::mock_with_client(
+			client,
+			StumpConfig {
+				job_disk_pause_threshold_mb: 1024,
+				..StumpConfig::debug()
+			},
+		));
+		let mut receiver = core_ctx.get_client_receiver();
+
+		let stats = Arc::new(ChangingSystemStats(std::sync::Mutex::new(SystemStats {
+			free_disk_bytes: Some(100 * 1024 * 1024),
+			..Default::default()
+		})));
+		let job_manager = JobManager::new(core_ctx)
+			.with_stats_provider(stats.clone())
+			.arced();
+		// A type which is already paused should stay paused after recovering
+		job_manager
+			.clone()
+			.set_job_type_paused(String::from("database_maintenance"), true)
+			.await;
+
+		job_manager.clone().check_disk_space().await;
+		assert_eq!(
+			job_manager.get_paused_job_types().await,
+			vec!["database_maintenance", "thumbnail_generation"]
+		);
+		match receiver.try_recv() {
+			Ok(CoreEvent::DiskSpaceLow {
+				free_mb,
+				paused_job_types,
+				..
+			}) => {
+				assert_eq!(free_mb, 100);
+				assert_eq!(paused_job_types, vec!["thumbnail_generation"]);
+			},
+			other => panic!("Expected a DiskSpaceLow event, got {:?}", other),
+		}
+
+		// Checking again while still low shouldn't alert again
+		job_manager.clone().check_disk_space().await;
+		assert!(receiver.try_recv().is_err());
+
+		stats.0.lock().unwrap().free_disk_bytes = Some(10 * 1024 * 1024 * 1024);
+		job_manager.clone().check_disk_space().await;
+		assert_eq!(
+			job_manager.get_paused_job_types().await,
+			vec!["database_maintenance"]
+		);
+		match receiver.try_recv() {
+			Ok(CoreEvent::DiskSpaceRecovered {
+				resumed_job_types, ..
+			}) => assert_eq!(resumed_job_types, vec!["thumbnail_generation"]),
+			other => panic!("Expected a DiskSpaceRecovered event, got {:?}", other),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_job_queue_status() {
+		let (client, _dir) = Ctx::mock_db().await;
+		client
+			.job()
+			.create(
+				String::from("completed"),
+				String::from("noop"),
+				vec![job::status::set(JobStatus::Completed.to_string())],
+			)
+			.exec()
+			.await
+			.unwrap();
+
+		let core_ctx = Arc::new(Ctx::mock_with_client(client, StumpConfig::debug()));
+		let job_manager = JobManager::new(core_ctx).arced();
+
+		assert!(job_manager.set_queue_paused(true));
+		let mut queued_ids = vec![];
+		for _ in 0..2 {
+			let job = Job::new(NoopJob);
+			queued_ids.push(job.detail().as_ref().unwrap().id.clone());
+			job_manager.clone().enqueue_job(job).await.unwrap();
+		}
+
+		let status = job_manager
+			.clone()
+			.job_queue_status(queued_ids[1].clone())
+			.await
+			.unwrap()
+			.expect("Queued job should have a status");
+		assert!(status.job.is_none());
+		let queued = status.queued.expect("Job should be queued");
+		assert_eq!(queued.position, 1);
+		assert!(queued.is_held);
+
+		// The position should update as the queue drains
+		job_manager.job_queue.write().await.pop_front();
+		let status = job_manager
+			.clone()
+			.job_queue_status(queued_ids[1].clone())
+			.await
+			.unwrap()
+			.unwrap();
+		assert_eq!(status.queued.map(|queued| queued.position), Some(0));
+
+		let status = job_manager
+			.clone()
+			.job_queue_status(String::from("completed"))
+			.await
+			.unwrap()
+			.expect("Persisted job should have a status");
+		assert!(status.queued.is_none());
+		assert_eq!(status.job.map(|job| job.status), Some(JobStatus::Completed));
+
+		assert!(job_manager
+			.job_queue_status(String::from("unknown"))
+			.await
+			.unwrap()
+			.is_none());
+	}
+
+	#[tokio::test]
+	async fn test_cancel_job_failure_reasons() {
+		let (client, _dir) = Ctx::mock_db().await;
+		client
+			.job()
+			.create(
+				String::from("completed"),
+				String::from("library_scan"),
+				vec![job::status::set(JobStatus::Completed.to_string())],
+			)
+			.exec()
+			.await
+			.unwrap();
+
+		let core_ctx = Arc::new(Ctx::mock_with_client(client, StumpConfig::debug()));
+		let job_manager = JobManager::new(core_ctx).arced();
+
+		let result = job_manager
+			.clone()
+			.cancel_job(String::from("completed"))
+			.await;
+		assert!(matches!(
+			result,
+			Err(JobManagerError::CancelFailed(
+				CancelJobFailure::AlreadyTerminal(JobStatus::Completed)
+			))
+		));
+
+		let result = job_manager.cancel_job(String::from("unknown")).await;
+		assert!(matches!(
+			result,
+			Err(JobManagerError::CancelFailed(CancelJobFailure::NotFound))
+		));
+	}
+
+	#[tokio::test]
+	async fn test_config_snapshot_reflects_runtime_changes() {
+		let (client, _mock) = PrismaClient::_mock();
+		let core_ctx = Arc::new(Ctx::mock_with_client(
+			client,
+			StumpConfig {
+				job_max_rss_mb: 2048,
+				..StumpConfig::debug()
+			},
+		));
+		let job_manager = JobManager::new(core_ctx).arced();
+
+		let config = job_manager.config_snapshot().await;
+		assert_eq!(config.max_concurrent_jobs, MAX_CONCURRENT_JOBS as u32);
+		assert_eq!(config.limits.job_max_rss_mb, 2048);
+		assert!(config.paused_job_types.is_empty());
+		assert!(!config.is_queue_paused);
+
+		job_manager
+			.clone()
+			.set_job_type_paused(String::from("library_scan"), true)
+			.await;
+		job_manager.set_queue_paused(true);
+
+		let config = job_manager.config_snapshot().await;
+		assert_eq!(config.paused_job_types, vec!["library_scan"]);
+		assert!(config.is_queue_paused);
+	}
+
+	#[tokio::test]
+	async fn test_lowered_limit_is_live() {
+		let (client, _mock) = PrismaClient::_mock();
+		let core_ctx = Arc::new(Ctx::mock_with_client(
+			client,
+			StumpConfig {
+				job_max_rss_mb: 2048,
+				..StumpConfig::debug()
+			},
+		));
+		let job_manager = JobManager::new(core_ctx)
+			.with_stats_provider(Arc::new(FakeSystemStats(SystemStats {
+				rss_bytes: Some(1024 * 1024 * 1024),
+				..Default::default()
+			})))
+			.arced();
+		assert!(job_manager.admit(None).await);
+
+		let config = job_manager
+			.clone()
+			.update_limits(UpdateJobLimits {
+				job_max_rss_mb: Some(512),
+				..Default::default()
+			})
+			.await;
+		assert_eq!(config.limits.job_max_rss_mb, 512);
+		assert_eq!(job_manager.config_snapshot().await, config);
+		// The lowered limit is enforced, not just reported
+		assert!(!job_manager.admit(None).await);
+	}
+}
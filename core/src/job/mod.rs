@@ -1,29 +1,48 @@
+mod admission;
+mod disk_monitor;
 mod executor;
 mod job_manager;
+mod queue;
 mod scheduler;
 pub(crate) mod utils;
 mod worker;
 
+pub use admission::{SystemStats, SystemStatsProvider};
 pub use executor::{Job, JobExecutorTrait};
 pub use job_manager::{
-	JobManager, JobManagerError, JobManagerResult, JobManagerShutdownSignal,
+	CancelJobFailure, JobLimits, JobManager, JobManagerConfig, JobManagerError,
+	JobManagerResult, JobManagerShutdownSignal, UpdateJobLimits,
 };
 use prisma_client_rust::{chrono::Utc, QueryError};
+pub use queue::{
+	get_wait_times, JobQueueSnapshot, JobQueueStatus, JobWaitTimes,
+	QueueCompletionEstimate, QueuedJob, WaitExplanation, WaitReason,
+};
 pub use scheduler::JobScheduler;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+pub use utils::cancel_persisted_jobs;
 use utoipa::ToSchema;
-pub use worker::{Worker, WorkerCtx};
+pub use worker::{Worker, WorkerCtx, JOB_KILL_WINDOW};
 
-use crate::{db::entity::Cursor, filesystem::FileError, prisma, CoreError};
+use crate::{
+	db::entity::{Cursor, LogLevel},
+	filesystem::FileError,
+	prisma, CoreError, Ctx,
+};
 
 #[derive(Clone, Debug)]
 pub enum JobError {
 	// Paused(Vec<u8>),
 	Cancelled,
+	/// The job was killed, abandoning its work immediately
+	Killed,
 	SpawnFailed,
 	// InvalidState(String),
 	InvalidJob(String),
+	MaintenanceMode(String),
+	/// The job targets an archived library, which is never scanned
+	LibraryArchived(String),
 	Unknown(String),
 }
 
@@ -48,7 +67,9 @@ impl From<FileError> for JobError {
 	}
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize, Type, ToSchema)]
+#[derive(
+	Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema,
+)]
 pub enum JobStatus {
 	#[serde(rename = "RUNNING")]
 	Running,
@@ -58,6 +79,10 @@ pub enum JobStatus {
 	Completed,
 	#[serde(rename = "CANCELLED")]
 	Cancelled,
+	/// The job was killed, abandoning any partial progress. Unlike a cancelled job, a
+	/// killed job isn't given the chance to finish its current work.
+	#[serde(rename = "KILLED")]
+	Killed,
 	#[serde(rename = "FAILED")]
 	Failed,
 	#[default]
@@ -72,12 +97,26 @@ impl std::fmt::Display for JobStatus {
 			// JobStatus::Paused => write!(f, "PAUSED"),
 			JobStatus::Completed => write!(f, "COMPLETED"),
 			JobStatus::Cancelled => write!(f, "CANCELLED"),
+			JobStatus::Killed => write!(f, "KILLED"),
 			JobStatus::Failed => write!(f, "FAILED"),
 			JobStatus::Queued => write!(f, "QUEUED"),
 		}
 	}
 }
 
+impl JobStatus {
+	/// Whether the job has finished, i.e. it can no longer run
+	pub fn is_terminal(&self) -> bool {
+		matches!(
+			self,
+			JobStatus::Completed
+				| JobStatus::Cancelled
+				| JobStatus::Killed
+				| JobStatus::Failed
+		)
+	}
+}
+
 impl From<&str> for JobStatus {
 	fn from(s: &str) -> Self {
 		match s {
@@ -85,6 +124,7 @@ impl From<&str> for JobStatus {
 			// "PAUSED" => JobStatus::Paused,
 			"COMPLETED" => JobStatus::Completed,
 			"CANCELLED" => JobStatus::Cancelled,
+			"KILLED" => JobStatus::Killed,
 			"FAILED" => JobStatus::Failed,
 			"QUEUED" => JobStatus::Queued,
 			_ => unreachable!(),
@@ -100,7 +140,7 @@ pub struct JobDetail {
 	pub name: String,
 	/// The extra details of the job, e.g. "/Users/oromei/Documents/Stump/MainLibrary"
 	pub description: Option<String>,
-	/// The status of the job. e.g. Running, Paused, Completed, Cancelled, Failed, Queued
+	/// The status of the job. e.g. Running, Paused, Completed, Cancelled, Killed, Failed, Queued
 	pub status: JobStatus,
 	/// The total number of tasks
 	pub task_count: Option<i32>,
@@ -108,10 +148,19 @@ pub struct JobDetail {
 	pub completed_task_count: Option<i32>,
 	/// The time (in milliseconds) to complete the job
 	pub ms_elapsed: Option<u64>,
-	/// The datetime stamp of when the job was created
+	/// The datetime stamp of when the job was created, i.e. when it was queued
 	pub created_at: Option<String>,
+	/// The datetime stamp of when the job started running
+	pub started_at: Option<String>,
 	/// The datetime stamp of when the job completed
 	pub completed_at: Option<String>,
+	/// The level of logs captured for the job. Before the job starts, this is only set if a
+	/// level was requested, and otherwise the configured default is used.
+	pub log_level: Option<LogLevel>,
+	/// Whether the job bypasses the admission checks, i.e. it was forced to start by the
+	/// user even if the system is under pressure. This is not persisted.
+	#[serde(default)]
+	pub force: bool,
 }
 
 impl Cursor for JobDetail {
@@ -131,7 +180,10 @@ impl JobDetail {
 			completed_task_count: None,
 			ms_elapsed: None,
 			created_at: Some(Utc::now().to_rfc3339()),
+			started_at: None,
 			completed_at: None,
+			log_level: None,
+			force: false,
 		}
 	}
 }
@@ -147,7 +199,10 @@ impl From<prisma::job::Data> for JobDetail {
 			completed_task_count: Some(data.completed_task_count),
 			ms_elapsed: Some(data.ms_elapsed as u64),
 			created_at: Some(data.created_at.to_rfc3339()),
+			started_at: data.started_at.map(|dt| dt.to_rfc3339()),
 			completed_at: data.completed_at.map(|dt| dt.to_rfc3339()),
+			log_level: data.log_level.parse().ok(),
+			force: false,
 		}
 	}
 }
@@ -159,6 +214,11 @@ pub struct JobUpdate {
 	pub task_count: u64,
 	pub message: Option<String>,
 	pub status: Option<JobStatus>,
+	/// The position of the update among the events emitted for the job, which increases
+	/// with every event. Consumers must ignore any event with a lower sequence than the
+	/// last one seen for the job, since events may be delivered out of order.
+	#[serde(default)]
+	pub sequence: u64,
 }
 
 impl JobUpdate {
@@ -169,6 +229,7 @@ impl JobUpdate {
 			task_count,
 			message,
 			status: Some(JobStatus::Running),
+			sequence: 0,
 		}
 	}
 
@@ -184,6 +245,7 @@ impl JobUpdate {
 			task_count,
 			message,
 			status: Some(JobStatus::Running),
+			sequence: 0,
 		}
 	}
 }
@@ -213,6 +275,12 @@ pub trait JobTrait: Send + Sync + Sized {
 
 	fn name(&self) -> &'static str;
 	fn description(&self) -> Option<Box<&str>>;
+	/// Validates the job's parameters before it is enqueued, so that invalid jobs are
+	/// rejected up front rather than failing once they are run. By default, all jobs are
+	/// considered valid.
+	async fn validate(&self, _ctx: &Ctx) -> Result<(), JobError> {
+		Ok(())
+	}
 	// TODO: once jobs are stateful, the run return does not need to include the completed count
 	async fn run(
 		&mut self,
@@ -0,0 +1,534 @@
+use std::collections::HashMap;
+
+use prisma_client_rust::{chrono::Utc, Direction};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::{
+	prisma::{job, PrismaClient},
+	telemetry::{duration_percentiles, JobDurationPercentiles},
+	CoreResult,
+};
+
+use super::{JobDetail, JobStatus};
+
+/// The number of recent jobs used to determine average durations and wait times
+const DURATION_SAMPLE_SIZE: i64 = 500;
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type, ToSchema)]
+pub struct QueuedJob {
+	/// The ID of the job
+	pub id: String,
+	/// The name of job, e.g. library_scan
+	pub name: String,
+	/// The extra details of the job, e.g. "/Users/oromei/Documents/Stump/MainLibrary"
+	pub description: Option<String>,
+	/// The position of the job in the queue, where 0 is the next job to run
+	pub position: usize,
+	/// Whether the job is held, either because its type (or the queue) is paused or because
+	/// of maintenance mode. Held jobs have no estimated start.
+	pub is_held: bool,
+	/// The estimated time (in milliseconds) until the job starts
+	pub estimated_wait_ms: Option<u64>,
+	/// The datetime stamp of when the job is estimated to start
+	pub estimated_start_at: Option<String>,
+	/// The dominant reason the job hasn't started yet
+	pub wait_explanation: WaitExplanation,
+}
+
+/// The reasons a queued job may be waiting, from most to least dominant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WaitReason {
+	/// Jobs are held while the server is in maintenance mode
+	MaintenanceMode,
+	/// The whole queue is paused
+	QueuePaused,
+	/// The job's type was paused because free disk space is low
+	LowDiskSpace,
+	/// The job's type was paused by a user
+	JobTypePaused,
+	/// Other jobs (running or queued) must finish first
+	JobsAhead,
+	/// The job is next, but was deferred because the system is under pressure
+	Deferred,
+	/// The job is next and will start shortly
+	Next,
+}
+
+/// A human-readable explanation of why a queued job is waiting
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+pub struct WaitExplanation {
+	pub reason: WaitReason,
+	/// e.g. "Waiting: the running job and 2 queued jobs are ahead"
+	pub message: String,
+}
+
+/// The state relevant to why a single queued job is waiting
+#[derive(Debug, Default)]
+pub(crate) struct WaitContext<'a> {
+	pub job_name: &'a str,
+	pub is_maintenance_hold: bool,
+	pub is_queue_paused: bool,
+	pub is_disk_paused: bool,
+	pub is_type_paused: bool,
+	/// The number of queued jobs ahead of this one which aren't held
+	pub runnable_ahead: usize,
+	pub has_running_job: bool,
+	/// The reason the next job to start was deferred, if any
+	pub deferred_reason: Option<&'a str>,
+}
+
+/// Returns the dominant reason a queued job is waiting. Holds take precedence over
+/// everything else, since the job won't start regardless of what is ahead of it.
+pub(crate) fn explain_wait(context: WaitContext) -> WaitExplanation {
+	let (reason, message) = if context.is_maintenance_hold {
+		(
+			WaitReason::MaintenanceMode,
+			String::from("Held: Stump is in maintenance mode"),
+		)
+	} else if context.is_queue_paused {
+		(
+			WaitReason::QueuePaused,
+			String::from("Held: the job queue is paused"),
+		)
+	} else if context.is_disk_paused {
+		(
+			WaitReason::LowDiskSpace,
+			format!(
+				"Held: {} jobs are paused while free disk space is low",
+				context.job_name
+			),
+		)
+	} else if context.is_type_paused {
+		(
+			WaitReason::JobTypePaused,
+			format!("Held: {} jobs are paused", context.job_name),
+		)
+	} else if context.has_running_job || context.runnable_ahead > 0 {
+		let queued_ahead = match context.runnable_ahead {
+			1 => String::from("1 queued job"),
+			count => format!("{} queued jobs", count),
+		};
+		let message = match (context.has_running_job, context.runnable_ahead) {
+			(true, 0) => String::from("Waiting: the running job must finish first"),
+			(true, _) => {
+				format!("Waiting: the running job and {} are ahead", queued_ahead)
+			},
+			(false, 1) => String::from("Waiting: 1 queued job is ahead"),
+			(false, _) => format!("Waiting: {} are ahead", queued_ahead),
+		};
+		(WaitReason::JobsAhead, message)
+	} else if let Some(deferred_reason) = context.deferred_reason {
+		(
+			WaitReason::Deferred,
+			format!("Deferred: {}", deferred_reason),
+		)
+	} else {
+		(WaitReason::Next, String::from("Next to start"))
+	};
+
+	WaitExplanation { reason, message }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Type, ToSchema)]
+pub struct JobQueueSnapshot {
+	/// The ID of the running job, if any
+	pub running_job_id: Option<String>,
+	/// The estimated time (in milliseconds) until the running job completes
+	pub running_job_remaining_ms: Option<u64>,
+	/// The number of blocking tasks spawned by the running job which are still running
+	pub running_job_blocking_tasks: Option<u64>,
+	/// Whether the queue is paused, in which case no queued job is started until it is resumed
+	pub is_paused: bool,
+	/// The jobs waiting to run, in the order they will be considered
+	pub queued: Vec<QueuedJob>,
+	/// The reason the next job was deferred rather than started, if the system is under
+	/// pressure (e.g. low on disk space)
+	pub deferred_reason: Option<String>,
+	/// The estimate of when the running job and every queued job will have completed, if
+	/// there are any
+	pub completion_estimate: Option<QueueCompletionEstimate>,
+}
+
+impl JobQueueSnapshot {
+	/// Returns the queued job with the given ID, if it has yet to start
+	pub fn into_queued_job(self, job_id: &str) -> Option<QueuedJob> {
+		self.queued.into_iter().find(|job| job.id == job_id)
+	}
+}
+
+/// The status of a single job. Jobs are only persisted once they start, so a job which is
+/// still queued (or held) is described by its place in the queue instead.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, ToSchema)]
+pub struct JobQueueStatus {
+	/// The ID of the job
+	pub id: String,
+	/// The persisted job, once it has started
+	pub job: Option<JobDetail>,
+	/// The position and estimated start of the job, while it has yet to start. This is
+	/// the same as the job's entry in the queue snapshot.
+	pub queued: Option<QueuedJob>,
+}
+
+/// An estimate of when the queue will be empty. Only jobs of a type with a known average
+/// duration can be estimated, and held jobs are excluded since they won't run until they
+/// are released.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+pub struct QueueCompletionEstimate {
+	/// The estimated time (in milliseconds) until every estimable job has completed
+	pub remaining_ms: u64,
+	/// The datetime stamp of when every estimable job is estimated to have completed
+	pub completes_at: String,
+	/// The number of jobs (including the running job) excluded from the estimate because
+	/// no average duration is known for their type
+	pub unknown_duration_count: usize,
+	/// The number of held jobs excluded from the estimate
+	pub held_count: usize,
+}
+
+/// How long jobs of a type have recently waited in the queue before starting
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+pub struct JobWaitTimes {
+	/// The name of job, e.g. library_scan
+	pub name: String,
+	/// The average time (in milliseconds) jobs waited before starting
+	pub average_ms: u64,
+	/// Percentiles of the time jobs waited before starting
+	pub percentiles: JobDurationPercentiles,
+}
+
+/// Returns how long recently started jobs waited in the queue, by job name.
+pub async fn get_wait_times(client: &PrismaClient) -> CoreResult<Vec<JobWaitTimes>> {
+	let started_jobs = client
+		.job()
+		.find_many(vec![job::started_at::not(None)])
+		.order_by(job::started_at::order(Direction::Desc))
+		.take(DURATION_SAMPLE_SIZE)
+		.exec()
+		.await?;
+
+	let waits = started_jobs
+		.into_iter()
+		.filter_map(|job| {
+			let wait = job.started_at? - job.created_at;
+			Some((job.name, wait.num_milliseconds().max(0) as u64))
+		})
+		.collect::<Vec<(String, u64)>>();
+
+	Ok(summarize_wait_times(waits))
+}
+
+/// Summarizes the given (job name, wait in milliseconds) pairs by job name, sorted by name.
+fn summarize_wait_times(waits: Vec<(String, u64)>) -> Vec<JobWaitTimes> {
+	let mut by_name = HashMap::<String, Vec<u64>>::new();
+	for (name, wait_ms) in waits {
+		by_name.entry(name).or_default().push(wait_ms);
+	}
+
+	let mut wait_times = by_name
+		.into_iter()
+		.map(|(name, waits)| JobWaitTimes {
+			name,
+			average_ms: waits.iter().sum::<u64>() / waits.len() as u64,
+			percentiles: duration_percentiles(waits),
+		})
+		.collect::<Vec<JobWaitTimes>>();
+	wait_times.sort_by(|a, b| a.name.cmp(&b.name));
+
+	wait_times
+}
+
+/// Returns the average duration (in milliseconds) of recently completed jobs, by job name.
+pub(crate) async fn get_average_durations(
+	client: &PrismaClient,
+) -> CoreResult<HashMap<String, u64>> {
+	let completed_jobs = client
+		.job()
+		.find_many(vec![job::status::equals(JobStatus::Completed.to_string())])
+		.order_by(job::completed_at::order(Direction::Desc))
+		.take(DURATION_SAMPLE_SIZE)
+		.exec()
+		.await?;
+
+	let mut totals = HashMap::<String, (u64, u64)>::new();
+	for job in completed_jobs {
+		let (total, count) = totals.entry(job.name).or_default();
+		*total += job.ms_elapsed.max(0) as u64;
+		*count += 1;
+	}
+
+	Ok(totals
+		.into_iter()
+		.map(|(name, (total, count))| (name, total / count))
+		.collect())
+}
+
+/// Estimates how long each queued job will wait before it starts. Jobs only run one at a
+/// time, so a job waits for the running job (`running_remaining_ms`, which is `Some(0)`
+/// when nothing is running) plus every runnable job ahead of it. Each queued job is given
+/// as its average duration and whether it is held. Held jobs are skipped, and once a job
+/// with an unknown duration is ahead, no further estimates can be made.
+pub(crate) fn estimate_wait_times(
+	running_remaining_ms: Option<u64>,
+	queued: &[(Option<u64>, bool)],
+) -> Vec<Option<u64>> {
+	let mut elapsed = running_remaining_ms;
+
+	queued
+		.iter()
+		.map(|(average_ms, is_held)| {
+			if *is_held {
+				return None;
+			}
+
+			let wait_ms = elapsed;
+			elapsed = elapsed.zip(*average_ms).map(|(a, b)| a + b);
+			wait_ms
+		})
+		.collect()
+}
+
+/// Estimates when the queue will be empty. Jobs run one at a time, so this is the remaining
+/// time of the running job (`None` if it is unknown) plus the average duration of every
+/// queued job which isn't held, given as in [estimate_wait_times]. Jobs of unknown
+/// duration are counted rather than making the whole estimate unknown. Returns `None` if
+/// there is nothing running or queued.
+pub(crate) fn estimate_queue_completion(
+	running_remaining_ms: Option<Option<u64>>,
+	queued: &[(Option<u64>, bool)],
+) -> Option<QueueCompletionEstimate> {
+	if running_remaining_ms.is_none() && queued.is_empty() {
+		return None;
+	}
+
+	let mut remaining_ms = 0;
+	let mut unknown_duration_count = 0;
+	let mut held_count = 0;
+
+	match running_remaining_ms {
+		Some(Some(running_ms)) => remaining_ms += running_ms,
+		Some(None) => unknown_duration_count += 1,
+		None => {},
+	}
+	for (average_ms, is_held) in queued {
+		match (average_ms, is_held) {
+			(_, true) => held_count += 1,
+			(Some(average_ms), false) => remaining_ms += average_ms,
+			(None, false) => unknown_duration_count += 1,
+		}
+	}
+
+	Some(QueueCompletionEstimate {
+		remaining_ms,
+		completes_at: estimated_start_at(remaining_ms),
+		unknown_duration_count,
+		held_count,
+	})
+}
+
+/// Returns the datetime stamp `wait_ms` milliseconds from now.
+pub(crate) fn estimated_start_at(wait_ms: u64) -> String {
+	let wait = prisma_client_rust::chrono::Duration::milliseconds(wait_ms as i64);
+	(Utc::now() + wait).to_rfc3339()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Ctx;
+
+	#[test]
+	fn test_estimate_wait_times() {
+		assert_eq!(
+			estimate_wait_times(Some(1_000), &[(Some(500), false), (Some(250), false)]),
+			vec![Some(1_000), Some(1_500)]
+		);
+		assert_eq!(
+			estimate_wait_times(Some(0), &[(Some(500), false), (None, false)]),
+			vec![Some(0), Some(500)]
+		);
+	}
+
+	#[test]
+	fn test_estimate_wait_times_unknown() {
+		// An unknown duration ahead makes everything after it unknown
+		assert_eq!(
+			estimate_wait_times(
+				Some(0),
+				&[(None, false), (Some(500), false), (Some(500), false)]
+			),
+			vec![Some(0), None, None]
+		);
+		// As does a running job without a known duration
+		assert_eq!(estimate_wait_times(None, &[(Some(500), false)]), vec![None]);
+	}
+
+	#[test]
+	fn test_summarize_wait_times() {
+		let waits = vec![
+			(String::from("library_scan"), 1_000),
+			(String::from("thumbnail_generation"), 0),
+			(String::from("library_scan"), 3_000),
+			(String::from("library_scan"), 2_000),
+		];
+
+		assert_eq!(
+			summarize_wait_times(waits),
+			vec![
+				JobWaitTimes {
+					name: String::from("library_scan"),
+					average_ms: 2_000,
+					percentiles: JobDurationPercentiles {
+						sample_size: 3,
+						p50_ms: Some(2_000),
+						p90_ms: Some(3_000),
+						p99_ms: Some(3_000),
+					},
+				},
+				JobWaitTimes {
+					name: String::from("thumbnail_generation"),
+					average_ms: 0,
+					percentiles: JobDurationPercentiles {
+						sample_size: 1,
+						p50_ms: Some(0),
+						p90_ms: Some(0),
+						p99_ms: Some(0),
+					},
+				},
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_get_wait_times() {
+		let (client, _dir) = Ctx::mock_db().await;
+
+		let queued_at = Utc::now();
+		for (id, waited_secs) in [("first", 10), ("second", 30)] {
+			let started_at =
+				queued_at + prisma_client_rust::chrono::Duration::seconds(waited_secs);
+			client
+				.job()
+				.create(
+					id.to_string(),
+					String::from("library_scan"),
+					vec![
+						job::created_at::set(queued_at.into()),
+						job::started_at::set(Some(started_at.into())),
+					],
+				)
+				.exec()
+				.await
+				.unwrap();
+		}
+		// Jobs which never started have no wait time
+		client
+			.job()
+			.create(String::from("third"), String::from("library_scan"), vec![])
+			.exec()
+			.await
+			.unwrap();
+
+		let wait_times = get_wait_times(&client).await.unwrap();
+		assert_eq!(wait_times.len(), 1);
+		assert_eq!(wait_times[0].percentiles.sample_size, 2);
+		assert_eq!(wait_times[0].average_ms, 20_000);
+	}
+
+	#[test]
+	fn test_explain_wait_hold_dominates() {
+		// A job held by a low disk space pause is explained by the pause, even with jobs
+		// ahead of it
+		let explanation = explain_wait(WaitContext {
+			job_name: "thumbnail_generation",
+			is_disk_paused: true,
+			is_type_paused: true,
+			runnable_ahead: 2,
+			has_running_job: true,
+			..Default::default()
+		});
+		assert_eq!(explanation.reason, WaitReason::LowDiskSpace);
+		assert_eq!(
+			explanation.message,
+			"Held: thumbnail_generation jobs are paused while free disk space is low"
+		);
+
+		let explanation = explain_wait(WaitContext {
+			job_name: "library_scan",
+			is_maintenance_hold: true,
+			is_queue_paused: true,
+			..Default::default()
+		});
+		assert_eq!(explanation.reason, WaitReason::MaintenanceMode);
+	}
+
+	#[test]
+	fn test_explain_wait_jobs_ahead() {
+		let explanation = explain_wait(WaitContext {
+			job_name: "library_scan",
+			runnable_ahead: 2,
+			has_running_job: true,
+			deferred_reason: Some("Load average (8.00) is above the maximum of 4.00"),
+			..Default::default()
+		});
+		assert_eq!(explanation.reason, WaitReason::JobsAhead);
+		assert_eq!(
+			explanation.message,
+			"Waiting: the running job and 2 queued jobs are ahead"
+		);
+
+		let explanation = explain_wait(WaitContext {
+			job_name: "library_scan",
+			deferred_reason: Some("Load average (8.00) is above the maximum of 4.00"),
+			..Default::default()
+		});
+		assert_eq!(explanation.reason, WaitReason::Deferred);
+
+		let explanation = explain_wait(WaitContext {
+			job_name: "library_scan",
+			..Default::default()
+		});
+		assert_eq!(explanation.reason, WaitReason::Next);
+	}
+
+	#[test]
+	fn test_estimate_queue_completion() {
+		// A scan (30s average) is 10s in, with two more scans and a thumbnail generation
+		// (45s average) queued behind it
+		let queued = [
+			(Some(30_000), false),
+			(Some(45_000), false),
+			(Some(30_000), false),
+		];
+		let estimate = estimate_queue_completion(Some(Some(20_000)), &queued).unwrap();
+		assert_eq!(estimate.remaining_ms, 20_000 + 30_000 + 45_000 + 30_000);
+		assert_eq!(estimate.unknown_duration_count, 0);
+		assert_eq!(estimate.held_count, 0);
+
+		assert_eq!(estimate_queue_completion(None, &[]), None);
+	}
+
+	#[test]
+	fn test_estimate_queue_completion_unknown_and_held() {
+		let queued = [(None, false), (Some(30_000), true), (Some(45_000), false)];
+		let estimate = estimate_queue_completion(Some(None), &queued).unwrap();
+
+		// Only the job with a known duration which isn't held is counted
+		assert_eq!(estimate.remaining_ms, 45_000);
+		assert_eq!(estimate.unknown_duration_count, 2);
+		assert_eq!(estimate.held_count, 1);
+	}
+
+	#[test]
+	fn test_estimate_wait_times_held() {
+		assert_eq!(
+			estimate_wait_times(
+				Some(100),
+				&[(Some(500), true), (Some(200), false), (None, true)]
+			),
+			vec![None, Some(100), None]
+		);
+	}
+}
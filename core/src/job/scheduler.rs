@@ -1,19 +1,30 @@
-use std::sync::Arc;
+use std::{path::Path, str::FromStr, sync::Arc};
+
+use prisma_client_rust::chrono::Utc;
 
 use crate::{
-	db::entity::LibraryScanMode,
-	filesystem::scanner::LibraryScanJob,
-	prisma::{job_schedule_config, library},
+	db::{
+		entity::{LibraryScanMode, LogLevel, ScheduleCatchUpPolicy},
+		maintenance::{DatabaseMaintenanceJob, DatabaseMaintenanceStage},
+	},
+	filesystem::{scanner::LibraryScanJob, sweep_temp_dir},
+	prisma::{job_schedule_config, library, series},
+	warm_up::is_library_root_available,
 	CoreResult, Ctx,
 };
 
+/// The interval (in seconds) between scheduled database maintenance runs, i.e. 30 days
+const DATABASE_MAINTENANCE_INTERVAL_SECS: u64 = 60 * 60 * 24 * 30;
+
 pub struct JobScheduler {
 	pub scheduler_handle: Option<tokio::task::JoinHandle<()>>,
+	pub maintenance_handle: tokio::task::JoinHandle<()>,
 }
 
 impl JobScheduler {
 	pub async fn init(core_ctx: Arc<Ctx>) -> CoreResult<Arc<Self>> {
 		let client = core_ctx.db.clone();
+		let maintenance_handle = Self::schedule_database_maintenance(core_ctx.clone());
 
 		let result = client
 			.job_schedule_config()
@@ -49,56 +60,248 @@ impl JobScheduler {
 					);
 					86400
 				});
+			let interval_secs = interval_secs.max(1);
+
+			// Without a previous run, the first run happens immediately
+			let (missed_runs, secs_until_next_run) = schedule_config
+				.last_run_at
+				.map(|last_run_at| {
+					let elapsed_secs = Utc::now()
+						.signed_duration_since(last_run_at)
+						.num_seconds()
+						.max(0) as u64;
+					(
+						missed_runs(elapsed_secs, interval_secs),
+						secs_until_next_run(elapsed_secs, interval_secs),
+					)
+				})
+				.unwrap_or((0, 0));
+			let catch_up_policy =
+				ScheduleCatchUpPolicy::from_str(&schedule_config.catch_up_policy)
+					.unwrap_or_default();
+			let catch_up_runs = catch_up_policy.catch_up_runs(missed_runs);
+			let log_level = schedule_config
+				.log_level
+				.as_deref()
+				.and_then(|level| LogLevel::from_str(level).ok());
+			tracing::debug!(
+				missed_runs,
+				catch_up_runs,
+				secs_until_next_run,
+				"Computed scheduled runs missed while the server was down"
+			);
+
+			let config_id = schedule_config.id.clone();
+			let update_result = client
+				.job_schedule_config()
+				.update(
+					job_schedule_config::id::equals(config_id.clone()),
+					vec![job_schedule_config::missed_runs::set(
+						missed_runs.try_into().unwrap_or(i32::MAX),
+					)],
+				)
+				.exec()
+				.await;
+			if let Err(error) = update_result {
+				tracing::error!(?error, "Failed to persist missed scheduled runs");
+			}
 
 			let handle = tokio::spawn(async move {
 				let scheduler_ctx = core_ctx.clone();
-				let client = scheduler_ctx.db.clone();
-				let mut interval =
-					tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+				// Scanning a library before its directory is available would mark its books
+				// as missing, so nothing is scanned until the warm-up has finished
+				scheduler_ctx.warm_up.wait_until_complete().await;
+
+				for _ in 0..catch_up_runs {
+					tracing::info!(
+						"Scanning libraries to catch up on a missed scheduled run"
+					);
+					enqueue_scheduled_scans(
+						&scheduler_ctx,
+						&excluded_library_ids,
+						log_level,
+					)
+					.await;
+					record_scheduled_run(&scheduler_ctx, &config_id).await;
+				}
+
+				let period = std::time::Duration::from_secs(interval_secs);
+				let mut interval = tokio::time::interval_at(
+					tokio::time::Instant::now()
+						+ std::time::Duration::from_secs(secs_until_next_run),
+					period,
+				);
 
 				// TODO: Support persisting/resuming the scheduler state so that it can be resumed
 				loop {
 					interval.tick().await;
 
 					tracing::info!("Scanning libraries on schedule");
-					let libraries_to_scan = client
-						.library()
-						.find_many(vec![library::id::not_in_vec(
-							excluded_library_ids.clone(),
-						)])
-						.exec()
-						.await
-						.unwrap_or_else(|e| {
-							tracing::error!(?e, "Failed to fetch libraries to scan");
-							vec![]
-						});
-
-					for library in libraries_to_scan.iter() {
-						// TODO: support default scan mode on libraries
-						// let scan_mode = library.default_scan_mode.clone();
-						let library_path = library.path.clone();
-						let result = scheduler_ctx.dispatch_job(LibraryScanJob::new(
-							library_path,
-							LibraryScanMode::Default,
-						));
-						if result.is_err() {
-							tracing::error!(
-								?library,
-								"Failed to dispatch scan job for library"
-							);
-						}
-					}
+					enqueue_scheduled_scans(
+						&scheduler_ctx,
+						&excluded_library_ids,
+						log_level,
+					)
+					.await;
+					record_scheduled_run(&scheduler_ctx, &config_id).await;
 				}
 			});
 
 			Ok(Arc::new(Self {
 				scheduler_handle: Some(handle),
+				maintenance_handle,
 			}))
 		} else {
 			tracing::info!("No schedule config found. Scheduling is disabled.");
 			Ok(Arc::new(Self {
 				scheduler_handle: None,
+				maintenance_handle,
 			}))
 		}
 	}
+
+	/// Schedules the default database maintenance (without VACUUM) to run every 30 days,
+	/// along with a sweep of orphaned temporary files. The first run happens one interval
+	/// after startup, not immediately.
+	fn schedule_database_maintenance(core_ctx: Arc<Ctx>) -> tokio::task::JoinHandle<()> {
+		tokio::spawn(async move {
+			let period =
+				std::time::Duration::from_secs(DATABASE_MAINTENANCE_INTERVAL_SECS);
+			let mut interval =
+				tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+
+			loop {
+				interval.tick().await;
+
+				match sweep_temp_dir(&core_ctx.config) {
+					Ok(removed) => {
+						tracing::info!(removed, "Removed orphaned temporary files")
+					},
+					Err(error) => {
+						tracing::error!(
+							?error,
+							"Failed to remove orphaned temporary files"
+						)
+					},
+				}
+
+				tracing::info!("Running database maintenance on schedule");
+				let result = core_ctx
+					.enqueue_job(DatabaseMaintenanceJob::new(
+						DatabaseMaintenanceStage::defaults(),
+					))
+					.await;
+				if let Err(error) = result {
+					tracing::error!(
+						?error,
+						"Failed to dispatch database maintenance job"
+					);
+				}
+			}
+		})
+	}
+}
+
+/// Enqueues a scan of every library which isn't excluded from scheduled scans (or archived)
+async fn enqueue_scheduled_scans(
+	core_ctx: &Ctx,
+	excluded_library_ids: &[String],
+	log_level: Option<LogLevel>,
+) {
+	let libraries_to_scan = core_ctx
+		.db
+		.library()
+		.find_many(vec![
+			library::id::not_in_vec(excluded_library_ids.to_vec()),
+			library::archived::equals(false),
+		])
+		.exec()
+		.await
+		.unwrap_or_else(|e| {
+			tracing::error!(?e, "Failed to fetch libraries to scan");
+			vec![]
+		});
+
+	for library in libraries_to_scan.iter() {
+		if core_ctx.warm_up.is_library_unavailable(&library.id) {
+			let has_series = core_ctx
+				.db
+				.series()
+				.count(vec![series::library_id::equals(Some(library.id.clone()))])
+				.exec()
+				.await
+				.map(|count| count > 0)
+				.unwrap_or(true);
+			if !is_library_root_available(Path::new(&library.path), has_series) {
+				tracing::warn!(
+					path = library.path.as_str(),
+					"Skipping scheduled scan of unavailable library"
+				);
+				continue;
+			}
+			core_ctx.warm_up.mark_library_available(&library.id);
+		}
+
+		// TODO: support default scan mode on libraries
+		// let scan_mode = library.default_scan_mode.clone();
+		let library_path = library.path.clone();
+		let result = core_ctx
+			.enqueue_job(
+				LibraryScanJob::new(library_path, LibraryScanMode::Default)
+					.with_log_level(log_level),
+			)
+			.await;
+		if let Err(error) = result {
+			tracing::error!(?library, ?error, "Failed to dispatch scan job for library");
+		}
+	}
+}
+
+/// Records that the scheduled scans were just enqueued, so that runs missed while the server
+/// is down can be caught up on the next startup
+async fn record_scheduled_run(core_ctx: &Ctx, config_id: &str) {
+	let result = core_ctx
+		.db
+		.job_schedule_config()
+		.update(
+			job_schedule_config::id::equals(config_id.to_string()),
+			vec![job_schedule_config::last_run_at::set(Some(
+				Utc::now().into(),
+			))],
+		)
+		.exec()
+		.await;
+	if let Err(error) = result {
+		tracing::error!(?error, "Failed to record scheduled run");
+	}
+}
+
+/// Returns the number of scheduled runs which were due in the time since the last run
+fn missed_runs(elapsed_secs: u64, interval_secs: u64) -> u64 {
+	elapsed_secs / interval_secs
+}
+
+/// Returns the time (in seconds) until the next run, keeping to the schedule of the last run
+fn secs_until_next_run(elapsed_secs: u64, interval_secs: u64) -> u64 {
+	interval_secs - elapsed_secs % interval_secs
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_missed_runs() {
+		let day = 60 * 60 * 24;
+		// The server was restarted before the next run was due
+		assert_eq!(missed_runs(day / 2, day), 0);
+		assert_eq!(secs_until_next_run(day / 2, day), day / 2);
+		// The server was down for two and a half days
+		assert_eq!(missed_runs(day * 5 / 2, day), 2);
+		assert_eq!(secs_until_next_run(day * 5 / 2, day), day / 2);
+		// The server came back exactly when a run was due
+		assert_eq!(missed_runs(day, day), 1);
+		assert_eq!(secs_until_next_run(day, day), day);
+	}
 }
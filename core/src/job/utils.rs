@@ -1,26 +1,108 @@
-use crate::{prisma::job, CoreError, CoreResult, Ctx};
-use prisma_client_rust::chrono::Utc;
+use crate::{
+	db::entity::LogLevel,
+	prisma::{job, log, PrismaClient},
+	CoreError, CoreResult, Ctx,
+};
+use prisma_client_rust::chrono::{DateTime, Utc};
 use std::num::TryFromIntError;
 use tracing::trace;
 
-use super::JobStatus;
+use super::{JobDetail, JobStatus};
 
+/// Persists a job as it starts running. `queued_at` is when the job was enqueued, which may be
+/// well before it started if it waited in the queue. A deferred job was already persisted when
+/// it was deferred, so its row is updated instead.
 pub async fn persist_new_job(
 	core_ctx: &Ctx,
 	id: String,
 	name: String,
 	description: Option<String>,
+	queued_at: Option<String>,
+	log_level: LogLevel,
 ) -> CoreResult<job::Data> {
 	let db = core_ctx.get_db();
+	let queued_at = queued_at
+		.and_then(|queued_at| DateTime::parse_from_rfc3339(&queued_at).ok())
+		.unwrap_or_else(|| Utc::now().into());
 	let job = db
 		.job()
-		.create(id, name, vec![job::description::set(description)])
+		.upsert(
+			job::id::equals(id.clone()),
+			(
+				id,
+				name,
+				vec![
+					job::description::set(description),
+					job::created_at::set(queued_at),
+					job::started_at::set(Some(Utc::now().into())),
+					job::log_level::set(log_level.to_string()),
+				],
+			),
+			vec![
+				job::started_at::set(Some(Utc::now().into())),
+				job::log_level::set(log_level.to_string()),
+			],
+		)
 		.exec()
 		.await?;
 	trace!(?job, "Persisted new job to database");
 	Ok(job)
 }
 
+/// Persists a job which was deferred by the admission checks, along with a log recording
+/// why it was deferred. The job stays queued until it is admitted.
+pub async fn persist_job_deferral(
+	core_ctx: &Ctx,
+	detail: &JobDetail,
+	reason: String,
+) -> CoreResult<()> {
+	let db = core_ctx.get_db();
+	let queued_at = detail
+		.created_at
+		.as_ref()
+		.and_then(|queued_at| DateTime::parse_from_rfc3339(queued_at).ok())
+		.unwrap_or_else(|| Utc::now().into());
+	let log_level = detail.log_level.unwrap_or(core_ctx.config.job_log_level);
+
+	db._transaction()
+		.run(|client| async move {
+			client
+				.job()
+				.upsert(
+					job::id::equals(detail.id.clone()),
+					(
+						detail.id.clone(),
+						detail.name.clone(),
+						vec![
+							job::description::set(detail.description.clone()),
+							job::status::set(JobStatus::Queued.to_string()),
+							job::created_at::set(queued_at),
+							job::log_level::set(log_level.to_string()),
+						],
+					),
+					vec![],
+				)
+				.exec()
+				.await?;
+
+			client
+				.log()
+				.create(
+					format!("Job deferred: {}", reason),
+					vec![
+						log::job_id::set(Some(detail.id.clone())),
+						log::level::set(LogLevel::Warn.to_string()),
+					],
+				)
+				.exec()
+				.await
+		})
+		.await?;
+	trace!(job_id = detail.id, "Persisted job deferral to database");
+
+	Ok(())
+}
+
 pub async fn persist_job_start(
 	core_ctx: &Ctx,
 	job_id: String,
@@ -96,3 +178,27 @@ pub async fn update_job_status(
 
 	Ok(())
 }
+
+/// Marks any persisted jobs with one of the given statuses as cancelled, returning the
+/// number of jobs affected. This is used to clean up jobs which can no longer be run,
+/// e.g. jobs which were running when the server was stopped.
+pub async fn cancel_persisted_jobs(
+	client: &PrismaClient,
+	statuses: Vec<JobStatus>,
+) -> CoreResult<i64> {
+	let affected_rows = client
+		.job()
+		.update_many(
+			vec![job::status::in_vec(
+				statuses.iter().map(JobStatus::to_string).collect(),
+			)],
+			vec![
+				job::status::set(JobStatus::Cancelled.to_string()),
+				job::completed_at::set(None),
+			],
+		)
+		.exec()
+		.await?;
+
+	Ok(affected_rows)
+}
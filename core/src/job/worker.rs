@@ -1,22 +1,92 @@
-use std::sync::Arc;
-use tokio::sync::{broadcast, Mutex};
-use tracing::error;
+use std::{
+	any::Any,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+	time::{Duration, Instant},
+};
+use tokio::{
+	sync::{broadcast, watch, Mutex},
+	task::JoinHandle,
+};
+use tracing::{error, warn, Instrument, Span};
 
-use crate::{event::CoreEvent, Ctx};
+use crate::{db::entity::LogLevel, event::CoreEvent, prisma::log, Ctx};
 
 use super::{
 	job_manager::{JobManager, JobManagerShutdownSignal},
-	JobDetail, JobError, JobExecutorTrait, JobUpdate,
+	utils::persist_job_end,
+	JobDetail, JobError, JobExecutorTrait, JobStatus, JobUpdate,
 };
 
+/// How long a killed job has to stop on its own before the executor abandons it
+pub const JOB_KILL_WINDOW: Duration = Duration::from_secs(2);
+
+/// Counts the blocking tasks spawned by a job, so that any which outlive the job are noticed.
+#[derive(Debug, Default)]
+struct BlockingTaskTracker {
+	spawned: AtomicU64,
+	running: AtomicU64,
+}
+
+/// Marks a tracked blocking task as no longer running when dropped, which happens when the
+/// task returns, panics, or is discarded without ever running.
+struct RunningBlockingTask(Arc<BlockingTaskTracker>);
+
+impl Drop for RunningBlockingTask {
+	fn drop(&mut self) {
+		self.0.running.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// The sequence of the events emitted for a job. Events are numbered and sent while holding
+/// the lock, so they're sent in sequence order, and nothing is sent after the terminal event.
+#[derive(Debug, Default)]
+struct EventSequence {
+	last: u64,
+	terminated: bool,
+}
+
 #[derive(Clone)]
 pub struct WorkerCtx {
 	pub job_id: String,
 	pub shutdown_tx: Arc<broadcast::Sender<JobManagerShutdownSignal>>,
 	pub core_ctx: Arc<Ctx>,
+	last_yielded_at: Arc<std::sync::Mutex<Instant>>,
+	blocking_tasks: Arc<BlockingTaskTracker>,
+	event_sequence: Arc<std::sync::Mutex<EventSequence>>,
+	kill_switch: Arc<watch::Sender<bool>>,
+	log_level: LogLevel,
 }
 
 impl WorkerCtx {
+	pub fn new(
+		job_id: String,
+		shutdown_tx: Arc<broadcast::Sender<JobManagerShutdownSignal>>,
+		core_ctx: Arc<Ctx>,
+	) -> Self {
+		Self {
+			job_id,
+			shutdown_tx,
+			core_ctx,
+			last_yielded_at: Arc::new(std::sync::Mutex::new(Instant::now())),
+			blocking_tasks: Arc::new(BlockingTaskTracker::default()),
+			event_sequence: Arc::new(std::sync::Mutex::new(EventSequence::default())),
+			kill_switch: Arc::new(watch::channel(false).0),
+			log_level: LogLevel::default(),
+		}
+	}
+
+	/// Sets the level of logs captured for the job
+	pub fn with_log_level(self, log_level: LogLevel) -> Self {
+		Self { log_level, ..self }
+	}
+
+	pub fn log_level(&self) -> LogLevel {
+		self.log_level
+	}
+
 	pub fn shutdown_rx(&self) -> broadcast::Receiver<JobManagerShutdownSignal> {
 		self.shutdown_tx.subscribe()
 	}
@@ -25,36 +95,211 @@ impl WorkerCtx {
 		&self.job_id
 	}
 
+	/// Returns true if the job has been killed. Jobs should poll this between units of work
+	/// and stop as soon as it is set, without finishing their current work. A job which
+	/// doesn't stop within [JOB_KILL_WINDOW] is abandoned by the executor.
+	pub fn is_killed(&self) -> bool {
+		*self.kill_switch.borrow()
+	}
+
+	/// Errors with [JobError::Killed] if the job has been killed, see [WorkerCtx::is_killed].
+	pub fn check_killed(&self) -> Result<(), JobError> {
+		if self.is_killed() {
+			Err(JobError::Killed)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Completes once the job has been killed and [JOB_KILL_WINDOW] has passed, i.e. once the
+	/// job should have stopped on its own.
+	pub(crate) async fn kill_window_elapsed(&self) {
+		// The sender is owned by the ctx, so it can't be dropped while waiting
+		let _ = self
+			.kill_switch
+			.subscribe()
+			.wait_for(|killed| *killed)
+			.await;
+		tokio::time::sleep(JOB_KILL_WINDOW).await;
+	}
+
+	/// Emits the event built for the next sequence number, unless the job's terminal event
+	/// has already been emitted. Events are dropped once the job has finished, so a late
+	/// progress update can never shadow its completion.
+	fn emit_sequenced(&self, is_terminal: bool, build: impl FnOnce(u64) -> CoreEvent) {
+		let mut sequence = self
+			.event_sequence
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+		if sequence.terminated {
+			tracing::trace!(
+				job_id = self.job_id,
+				"Dropping event emitted after job finished"
+			);
+			return;
+		}
+
+		sequence.last += 1;
+		sequence.terminated = is_terminal;
+		self.core_ctx.emit_event(build(sequence.last));
+	}
+
+	/// Marks the job as finished, returning the sequence number of its terminal event. No
+	/// further events are emitted for the job afterwards.
+	fn finish_sequence(&self) -> u64 {
+		let mut sequence = self
+			.event_sequence
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+		if !sequence.terminated {
+			sequence.last += 1;
+			sequence.terminated = true;
+		}
+		sequence.last
+	}
+
 	pub fn emit_progress(&self, progress: JobUpdate) {
-		self.core_ctx.emit_event(CoreEvent::JobProgress(progress))
+		self.emit_sequenced(false, |sequence| {
+			CoreEvent::JobProgress(JobUpdate {
+				sequence,
+				..progress
+			})
+		})
 	}
 
 	pub fn emit_job_message(&self, message: &str) {
-		self.core_ctx.emit_event(CoreEvent::JobProgress(JobUpdate {
-			job_id: self.job_id.clone(),
-			message: Some(message.to_string()),
-			..Default::default()
-		}))
+		self.emit_sequenced(false, |sequence| {
+			CoreEvent::JobProgress(JobUpdate {
+				job_id: self.job_id.clone(),
+				message: Some(message.to_string()),
+				sequence,
+				..Default::default()
+			})
+		})
 	}
 
 	pub fn emit_job_started(&self, task_count: u64, message: Option<String>) {
-		self.core_ctx
-			.emit_event(CoreEvent::JobStarted(JobUpdate::started(
-				self.job_id.clone(),
-				task_count,
-				message,
-			)))
+		self.emit_sequenced(false, |sequence| {
+			CoreEvent::JobStarted(JobUpdate {
+				sequence,
+				..JobUpdate::started(self.job_id.clone(), task_count, message)
+			})
+		})
 	}
 
 	pub fn emit_job_complete(&self) {
-		self.core_ctx
-			.emit_event(CoreEvent::JobComplete(self.job_id.clone()))
+		self.emit_sequenced(true, |sequence| CoreEvent::JobComplete {
+			job_id: self.job_id.clone(),
+			sequence,
+		})
+	}
+
+	pub fn emit_job_killed(&self) {
+		self.emit_sequenced(true, |sequence| CoreEvent::JobKilled {
+			job_id: self.job_id.clone(),
+			sequence,
+		})
+	}
+
+	/// Yields back to the async runtime if the job has run for longer than the configured
+	/// [StumpConfig::job_yield_budget_ms](crate::config::StumpConfig::job_yield_budget_ms)
+	/// since it last yielded. Jobs should call this between units of work, so that a long
+	/// running job can't starve other tasks on the runtime (e.g. serving requests, or
+	/// delivering events to clients).
+	///
+	/// This only helps if the work between calls is reasonably short. Heavy synchronous work
+	/// (e.g. image processing) must instead be offloaded to a blocking thread using
+	/// [WorkerCtx::spawn_blocking], since it never reaches a yield point.
+	pub async fn yield_if_over_budget(&self) {
+		let budget = Duration::from_millis(self.core_ctx.config.job_yield_budget_ms);
+
+		let should_yield = {
+			let mut last_yielded_at = self
+				.last_yielded_at
+				.lock()
+				.unwrap_or_else(|poisoned| poisoned.into_inner());
+			if last_yielded_at.elapsed() >= budget {
+				*last_yielded_at = Instant::now();
+				true
+			} else {
+				false
+			}
+		};
+
+		if should_yield {
+			tokio::task::yield_now().await;
+		}
+	}
+
+	/// Runs the closure on a blocking thread, like [tokio::task::spawn_blocking], but tracks
+	/// the task against the job. Jobs should always spawn blocking tasks through this, so that
+	/// any task still running once the job has finished (e.g. one which wasn't awaited before
+	/// the job was cancelled) is reported.
+	pub fn spawn_blocking<F, R>(&self, f: F) -> JoinHandle<R>
+	where
+		F: FnOnce() -> R + Send + 'static,
+		R: Send + 'static,
+	{
+		self.blocking_tasks.spawned.fetch_add(1, Ordering::SeqCst);
+		self.blocking_tasks.running.fetch_add(1, Ordering::SeqCst);
+		let running = RunningBlockingTask(self.blocking_tasks.clone());
+
+		tokio::task::spawn_blocking(move || {
+			let _running = running;
+			f()
+		})
+	}
+
+	/// Returns the number of blocking tasks spawned by the job which are still running
+	pub fn running_blocking_tasks(&self) -> u64 {
+		self.blocking_tasks.running.load(Ordering::SeqCst)
+	}
+
+	/// Persists a log for the job, unless the level isn't captured at the job's log level.
+	/// Logs which aren't captured are left out of the tracing output too, so that stdout
+	/// matches what is persisted.
+	pub async fn push_log(&self, level: LogLevel, message: impl Into<String>) {
+		if !self.log_level.includes(level) {
+			return;
+		}
+
+		let message = message.into();
+		match level {
+			LogLevel::Error => tracing::error!(job_id = self.job_id, "{}", message),
+			LogLevel::Warn => tracing::warn!(job_id = self.job_id, "{}", message),
+			LogLevel::Info => tracing::info!(job_id = self.job_id, "{}", message),
+			LogLevel::Debug => tracing::debug!(job_id = self.job_id, "{}", message),
+		}
+
+		let result = self
+			.core_ctx
+			.db
+			.log()
+			.create(
+				message,
+				vec![
+					log::job_id::set(Some(self.job_id.clone())),
+					log::level::set(level.to_string()),
+				],
+			)
+			.exec()
+			.await;
+		if let Err(error) = result {
+			error!(?error, "Failed to persist job log");
+		}
+	}
+
+	pub async fn push_warning(&self, message: impl Into<String>) {
+		self.push_log(LogLevel::Warn, message).await
 	}
 }
 
 pub struct Worker {
 	job: Option<Box<dyn JobExecutorTrait>>,
 	job_detail: JobDetail,
+	started_at: std::time::Instant,
+	blocking_tasks: Arc<BlockingTaskTracker>,
+	kill_switch: Arc<watch::Sender<bool>>,
 }
 
 impl Worker {
@@ -62,6 +307,9 @@ impl Worker {
 		Self {
 			job: Some(job),
 			job_detail: initial_detail,
+			started_at: std::time::Instant::now(),
+			blocking_tasks: Arc::new(BlockingTaskTracker::default()),
+			kill_switch: Arc::new(watch::channel(false).0),
 		}
 	}
 
@@ -69,31 +317,504 @@ impl Worker {
 		self.job_detail.clone()
 	}
 
+	/// Returns the time (in milliseconds) since the worker was started
+	pub fn ms_elapsed(&self) -> u64 {
+		self.started_at.elapsed().as_millis() as u64
+	}
+
+	/// Returns the number of blocking tasks spawned by the job which are still running
+	pub fn running_blocking_tasks(&self) -> u64 {
+		self.blocking_tasks.running.load(Ordering::SeqCst)
+	}
+
+	/// Flips the kill switch of the job, see [WorkerCtx::is_killed]
+	pub fn kill(&self) {
+		self.kill_switch.send_replace(true);
+	}
+
 	pub async fn spawn(
 		worker_ctx: WorkerCtx,
 		job_manager: Arc<JobManager>,
 		worker_mtx: Arc<Mutex<Self>>,
 	) -> Result<(), JobError> {
 		let job_id = worker_ctx.job_id.clone();
-		let mut job = worker_mtx
-			.lock()
-			.await
-			.job
-			.take()
-			.ok_or(JobError::SpawnFailed)?;
+		let job = {
+			let mut worker = worker_mtx.lock().await;
+			worker.blocking_tasks = worker_ctx.blocking_tasks.clone();
+			worker.kill_switch = worker_ctx.kill_switch.clone();
+			worker.job.take().ok_or(JobError::SpawnFailed)?
+		};
 
 		tokio::spawn(async move {
+			let start = std::time::Instant::now();
+			if let Err(message) = execute_isolated(job, worker_ctx.clone()).await {
+				let duration = start.elapsed().as_millis() as u64;
+				handle_job_panic(&worker_ctx, message, duration).await;
+			}
+			report_leaked_blocking_tasks(&worker_ctx);
+
+			if let Err(error) = job_manager.dequeue_job(job_id).await {
+				error!(?error, "Failed to dequeue job!")
+			}
+		});
+
+		Ok(())
+	}
+}
+
+/// Creates the tracing span for a job, at the job's log level
+fn job_span(worker_ctx: &WorkerCtx) -> Span {
+	let job_id = worker_ctx.job_id.as_str();
+	let log_level = worker_ctx.log_level.to_string();
+	let log_level = log_level.as_str();
+	match worker_ctx.log_level {
+		LogLevel::Error => tracing::error_span!("job", job_id, log_level),
+		LogLevel::Warn => tracing::warn_span!("job", job_id, log_level),
+		LogLevel::Info => tracing::info_span!("job", job_id, log_level),
+		LogLevel::Debug => tracing::debug_span!("job", job_id, log_level),
+	}
+}
+
+/// Executes (and finishes) the job in a dedicated task, so that a panic within the job
+/// surfaces as an error carrying the panic message, rather than taking down the worker
+/// and leaving the job stuck as RUNNING.
+async fn execute_isolated(
+	mut job: Box<dyn JobExecutorTrait>,
+	worker_ctx: WorkerCtx,
+) -> Result<(), String> {
+	let span = job_span(&worker_ctx);
+	tokio::spawn(
+		async move {
 			let result = job.execute(worker_ctx.clone()).await;
 
 			if let Err(error) = job.finish(result, worker_ctx).await {
 				error!(?error, "Failed to finish job!")
 			}
+		}
+		.instrument(span),
+	)
+	.await
+	.map_err(|join_error| {
+		if join_error.is_panic() {
+			panic_message(join_error.into_panic())
+		} else {
+			join_error.to_string()
+		}
+	})
+}
 
-			if let Err(error) = job_manager.dequeue_job(job_id).await {
-				error!(?error, "Failed to dequeue job!")
+/// Warns about any blocking tasks spawned by the job which are still running now that the job
+/// has finished, returning how many there are.
+fn report_leaked_blocking_tasks(worker_ctx: &WorkerCtx) -> u64 {
+	let running = worker_ctx.running_blocking_tasks();
+	if running > 0 {
+		warn!(
+			job_id = worker_ctx.job_id,
+			running,
+			spawned = worker_ctx.blocking_tasks.spawned.load(Ordering::SeqCst),
+			"Job finished with blocking tasks still running"
+		);
+	}
+	running
+}
+
+/// Marks a job which panicked as failed, and reports the panic message via the
+/// JobFailed event and the job's logs.
+async fn handle_job_panic(worker_ctx: &WorkerCtx, message: String, ms_elapsed: u64) {
+	error!(job_id = worker_ctx.job_id, message, "Job panicked!");
+
+	let persist_result = persist_job_end(
+		&worker_ctx.core_ctx,
+		worker_ctx.job_id.clone(),
+		JobStatus::Failed,
+		ms_elapsed,
+		None,
+	)
+	.await;
+	if let Err(error) = persist_result {
+		error!(?error, "Failed to persist job end");
+	}
+
+	worker_ctx
+		.core_ctx
+		.handle_failure_event(CoreEvent::JobFailed {
+			job_id: worker_ctx.job_id.clone(),
+			message: format!("Job panicked: {}", message),
+			sequence: worker_ctx.finish_sequence(),
+		})
+		.await;
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+	if let Some(message) = payload.downcast_ref::<&str>() {
+		message.to_string()
+	} else if let Some(message) = payload.downcast_ref::<String>() {
+		message.clone()
+	} else {
+		String::from("Unknown panic")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::sync::broadcast::channel;
+
+	use super::*;
+	use crate::{
+		config::StumpConfig,
+		job::{Job, JobTrait},
+		prisma::{job, PrismaClient},
+	};
+
+	struct PanickingJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for PanickingJob {
+		fn name(&self) -> &'static str {
+			"panicking_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, _ctx: WorkerCtx) -> Result<u64, JobError> {
+			// e.g. indexing into the pages of a malformed archive
+			let pages: Vec<u64> = Vec::new();
+			Ok(pages[1])
+		}
+	}
+
+	struct CpuHeavyJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for CpuHeavyJob {
+		fn name(&self) -> &'static str {
+			"cpu_heavy_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, ctx: WorkerCtx) -> Result<u64, JobError> {
+			let task_count = 10;
+			for task in 0..task_count {
+				let start = Instant::now();
+				while start.elapsed() < Duration::from_millis(5) {
+					std::hint::spin_loop();
+				}
+				ctx.emit_progress(JobUpdate::tick(
+					ctx.job_id.clone(),
+					task + 1,
+					task_count,
+					None,
+				));
+				ctx.yield_if_over_budget().await;
+			}
+			Ok(task_count)
+		}
+	}
+
+	/// A job which spawns a blocking task but doesn't wait for it, e.g. because it returned
+	/// early after an error. The blocking task runs until `release` is dropped.
+	struct LeakyJob {
+		release: Option<std::sync::mpsc::Receiver<()>>,
+	}
+
+	#[async_trait::async_trait]
+	impl JobTrait for LeakyJob {
+		fn name(&self) -> &'static str {
+			"leaky_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, ctx: WorkerCtx) -> Result<u64, JobError> {
+			let release = self.release.take().expect("The job should only run once");
+			let _handle = ctx.spawn_blocking(move || {
+				let _ = release.recv();
+			});
+			Ok(1)
+		}
+	}
+
+	/// A job which polls the kill switch between units of work
+	struct PollingJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for PollingJob {
+		fn name(&self) -> &'static str {
+			"polling_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, ctx: WorkerCtx) -> Result<u64, JobError> {
+			loop {
+				ctx.check_killed()?;
+				tokio::time::sleep(Duration::from_millis(10)).await;
+			}
+		}
+	}
+
+	/// A job which never checks the kill switch, e.g. stuck waiting on a network share
+	struct UnresponsiveJob;
+
+	#[async_trait::async_trait]
+	impl JobTrait for UnresponsiveJob {
+		fn name(&self) -> &'static str {
+			"unresponsive_job"
+		}
+
+		fn description(&self) -> Option<Box<&str>> {
+			None
+		}
+
+		async fn run(&mut self, _ctx: WorkerCtx) -> Result<u64, JobError> {
+			tokio::time::sleep(Duration::from_secs(3600)).await;
+			Ok(1)
+		}
+	}
+
+	fn mock_worker_ctx(job_id: &str, config: StumpConfig) -> WorkerCtx {
+		let (client, _mock) = PrismaClient::_mock();
+
+		WorkerCtx::new(
+			job_id.to_string(),
+			Arc::new(channel(1024).0),
+			Arc::new(Ctx::mock_with_client(client, config)),
+		)
+	}
+
+	#[tokio::test]
+	async fn test_job_panic_is_isolated() {
+		let worker_ctx = mock_worker_ctx("panicking_job_id", StumpConfig::debug());
+		let result = execute_isolated(Job::new(PanickingJob), worker_ctx).await;
+
+		let message = result.expect_err("The panic should be reported as an error");
+		assert!(message.contains("index out of bounds"));
+	}
+
+	#[tokio::test]
+	async fn test_cpu_heavy_job_yields() {
+		let config = StumpConfig {
+			job_yield_budget_ms: 1,
+			..StumpConfig::debug()
+		};
+		let heavy_ctx = mock_worker_ctx("cpu_heavy_job_id", config);
+		let other_ctx = WorkerCtx::new(
+			String::from("other_job_id"),
+			heavy_ctx.shutdown_tx.clone(),
+			heavy_ctx.core_ctx.clone(),
+		);
+		let mut receiver = heavy_ctx.core_ctx.get_client_receiver();
+
+		// The test runtime is single threaded, so the other job only gets to make progress
+		// if the heavy job yields
+		let heavy_handle = tokio::spawn(async move { CpuHeavyJob.run(heavy_ctx).await });
+		let other_handle = tokio::spawn(async move {
+			other_ctx.emit_job_message("Still making progress");
+		});
+		heavy_handle.await.unwrap().unwrap();
+		other_handle.await.unwrap();
+
+		let mut heavy_ticks_before_other = None;
+		let mut heavy_ticks = 0;
+		while let Ok(CoreEvent::JobProgress(update)) = receiver.try_recv() {
+			if update.job_id == "other_job_id" {
+				heavy_ticks_before_other = Some(heavy_ticks);
+			} else {
+				heavy_ticks += 1;
 			}
+		}
+
+		assert_eq!(heavy_ticks, 10);
+		let heavy_ticks_before_other =
+			heavy_ticks_before_other.expect("The other job should have emitted progress");
+		assert!(heavy_ticks_before_other < heavy_ticks);
+	}
+
+	#[tokio::test]
+	async fn test_leaked_blocking_task_is_reported() {
+		let worker_ctx = mock_worker_ctx("leaky_job_id", StumpConfig::debug());
+		let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+		LeakyJob {
+			release: Some(release_rx),
+		}
+		.run(worker_ctx.clone())
+		.await
+		.unwrap();
+		assert_eq!(report_leaked_blocking_tasks(&worker_ctx), 1);
+
+		drop(release_tx);
+		let start = Instant::now();
+		while worker_ctx.running_blocking_tasks() > 0 {
+			assert!(
+				start.elapsed() < Duration::from_secs(5),
+				"The blocking task should finish once released"
+			);
+			tokio::time::sleep(Duration::from_millis(10)).await;
+		}
+		assert_eq!(report_leaked_blocking_tasks(&worker_ctx), 0);
+	}
+
+	#[tokio::test]
+	async fn test_awaited_blocking_task_is_not_reported() {
+		let worker_ctx = mock_worker_ctx("blocking_job_id", StumpConfig::debug());
+
+		let result = worker_ctx.spawn_blocking(|| 42).await.unwrap();
+		assert_eq!(result, 42);
+		assert_eq!(report_leaked_blocking_tasks(&worker_ctx), 0);
+	}
+
+	#[test]
+	fn test_panic_message() {
+		assert_eq!(panic_message(Box::new("static message")), "static message");
+		assert_eq!(
+			panic_message(Box::new(String::from("owned message"))),
+			"owned message"
+		);
+		assert_eq!(panic_message(Box::new(42)), "Unknown panic");
+	}
+
+	#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+	async fn test_terminal_event_is_never_shadowed() {
+		let (client, _mock) = PrismaClient::_mock();
+		let core_ctx = Arc::new(Ctx {
+			response_channel: Arc::new(channel::<CoreEvent>(100_000)),
+			..Ctx::mock_with_client(client, StumpConfig::debug())
 		});
+		let mut receiver = core_ctx.get_client_receiver();
+		let worker_ctx =
+			WorkerCtx::new(String::from("job_id"), Arc::new(channel(1024).0), core_ctx);
 
-		Ok(())
+		// Progress is emitted from several tasks, which race with the job completing
+		let handles = (0..4)
+			.map(|_| {
+				let ctx = worker_ctx.clone();
+				tokio::spawn(async move {
+					for task in 0..2_500 {
+						ctx.emit_progress(JobUpdate::tick(
+							ctx.job_id.clone(),
+							task,
+							2_500,
+							None,
+						));
+						if task % 100 == 0 {
+							tokio::task::yield_now().await;
+						}
+					}
+				})
+			})
+			.collect::<Vec<_>>();
+		tokio::time::sleep(Duration::from_millis(1)).await;
+		worker_ctx.emit_job_complete();
+		for handle in handles {
+			handle.await.unwrap();
+		}
+
+		let mut last_sequence = 0;
+		let mut completed_sequence = None;
+		while let Ok(event) = receiver.try_recv() {
+			assert!(
+				completed_sequence.is_none(),
+				"An event was emitted after the job completed: {:?}",
+				event
+			);
+			let sequence = match event {
+				CoreEvent::JobProgress(update) => update.sequence,
+				CoreEvent::JobComplete { sequence, .. } => {
+					completed_sequence = Some(sequence);
+					sequence
+				},
+				other => panic!("Unexpected event: {:?}", other),
+			};
+			assert!(sequence > last_sequence, "Events were sent out of order");
+			last_sequence = sequence;
+		}
+
+		assert_eq!(completed_sequence, Some(last_sequence));
+	}
+
+	#[tokio::test]
+	async fn test_killed_job_emits_killed_event() {
+		let worker_ctx = mock_worker_ctx("killed_job_id", StumpConfig::debug());
+		let mut receiver = worker_ctx.core_ctx.get_client_receiver();
+
+		Job::new(PollingJob)
+			.finish(Err(JobError::Killed), worker_ctx)
+			.await
+			.unwrap();
+
+		assert!(matches!(
+			receiver.try_recv(),
+			Ok(CoreEvent::JobKilled { job_id, .. }) if job_id == "killed_job_id"
+		));
+		assert!(receiver.try_recv().is_err());
+	}
+
+	#[tokio::test]
+	async fn test_killed_job_stops_within_window() {
+		let (client, _dir) = Ctx::mock_db().await;
+		for (job_id, name) in [
+			("polling", "polling_job"),
+			("unresponsive", "unresponsive_job"),
+		] {
+			client
+				.job()
+				.create(
+					String::from(job_id),
+					String::from(name),
+					vec![job::status::set(JobStatus::Running.to_string())],
+				)
+				.exec()
+				.await
+				.unwrap();
+		}
+
+		let core_ctx = Arc::new(Ctx::mock_with_client(client, StumpConfig::debug()));
+		let shutdown_tx = Arc::new(channel(1024).0);
+
+		let polling_ctx = WorkerCtx::new(
+			String::from("polling"),
+			shutdown_tx.clone(),
+			core_ctx.clone(),
+		);
+		let handle = tokio::spawn({
+			let ctx = polling_ctx.clone();
+			async move { Job::new(PollingJob).execute(ctx).await }
+		});
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		let killed_at = Instant::now();
+		polling_ctx.kill_switch.send_replace(true);
+		let result = handle.await.unwrap();
+		assert!(matches!(result, Err(JobError::Killed)));
+		assert!(killed_at.elapsed() < JOB_KILL_WINDOW);
+
+		let unresponsive_ctx =
+			WorkerCtx::new(String::from("unresponsive"), shutdown_tx, core_ctx.clone());
+		unresponsive_ctx.kill_switch.send_replace(true);
+		let killed_at = Instant::now();
+		let result = Job::new(UnresponsiveJob).execute(unresponsive_ctx).await;
+		assert!(matches!(result, Err(JobError::Killed)));
+		assert!(killed_at.elapsed() < JOB_KILL_WINDOW + Duration::from_secs(1));
+
+		for job_id in ["polling", "unresponsive"] {
+			let status = core_ctx
+				.db
+				.job()
+				.find_unique(job::id::equals(String::from(job_id)))
+				.exec()
+				.await
+				.unwrap()
+				.map(|job| JobStatus::from(job.status.as_str()));
+			assert_eq!(status, Some(JobStatus::Killed));
+		}
 	}
 }
@@ -13,7 +13,12 @@ pub mod db;
 pub mod event;
 pub mod filesystem;
 pub mod job;
+pub mod maintenance;
 pub mod opds;
+pub mod setup;
+pub mod support;
+pub mod telemetry;
+pub mod warm_up;
 
 mod context;
 pub mod error;
@@ -122,9 +127,10 @@ impl StumpCore {
 		STUMP_SHADOW_TEXT
 	}
 
-	/// Runs the database migrations
+	/// Runs the database migrations. Destructive migrations will only be applied if the
+	/// configuration explicitly allows it.
 	pub async fn run_migrations(&self) -> Result<(), CoreError> {
-		db::migration::run_migrations(&self.ctx.db).await
+		db::migration::run_migrations(&self.ctx.db, &self.ctx.config).await
 	}
 
 	/// Initializes the server configuration record. This will only create a new record if one
@@ -195,6 +201,33 @@ impl StumpCore {
 		}
 	}
 
+	/// Restores maintenance mode if it was active when the server last shut down.
+	pub async fn init_maintenance_mode(&self) -> Result<(), CoreError> {
+		maintenance::restore_maintenance_mode(&self.ctx).await?;
+		Ok(())
+	}
+
+	/// Enters setup mode if the server has no users yet, returning whether setup is required.
+	pub async fn init_setup_mode(&self) -> Result<bool, CoreError> {
+		setup::init_setup_mode(&self.ctx).await
+	}
+
+	/// Starts waiting (in the background) for library directories to become available.
+	/// This should be called before the scheduler is initialized, so that scheduled scans
+	/// wait for it.
+	pub fn init_warm_up(&self) {
+		warm_up::spawn_warm_up(self.ctx.get_ctx());
+	}
+
+	/// Removes any temporary files left behind by a previous run, e.g. after a crash.
+	pub fn init_temp_dir(&self) -> Result<(), CoreError> {
+		let removed = filesystem::sweep_temp_dir(&self.ctx.config)?;
+		if removed > 0 {
+			tracing::info!(removed, "Removed orphaned temporary files");
+		}
+		Ok(())
+	}
+
 	pub async fn init_scheduler(&self) -> Result<Arc<JobScheduler>, CoreError> {
 		JobScheduler::init(self.ctx.arced()).await
 	}
@@ -211,13 +244,20 @@ mod tests {
 
 	use crate::{
 		db::{
+			bundle::*,
 			entity::*,
 			filter::*,
+			maintenance::DatabaseMaintenanceStage,
+			migration::*,
 			query::{ordering::*, pagination::*},
+			reading_stats::ReadingStats,
 		},
 		event::*,
 		filesystem::{image::*, *},
 		job::*,
+		maintenance::*,
+		telemetry::*,
+		warm_up::*,
 	};
 
 	#[allow(dead_code)]
@@ -337,6 +377,9 @@ mod tests {
 		file.write_all(format!("{}\n\n", ts_export::<JobUpdate>()?).as_bytes())?;
 		file.write_all(format!("{}\n\n", ts_export::<JobDetail>()?).as_bytes())?;
 		file.write_all(format!("{}\n\n", ts_export::<JobSchedulerConfig>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<ScheduleCatchUpPolicy>()?).as_bytes(),
+		)?;
 
 		file.write_all(format!("{}\n\n", ts_export::<CoreEvent>()?).as_bytes())?;
 
@@ -366,6 +409,61 @@ mod tests {
 		file.write_all(format!("{}\n\n", ts_export::<LogMetadata>()?).as_bytes())?;
 		file.write_all(format!("{}\n\n", ts_export::<LogLevel>()?).as_bytes())?;
 
+		file.write_all(format!("{}\n\n", ts_export::<MigrationRisk>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<MigrationState>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<AppliedMigration>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<PendingMigration>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<MigrationStatus>()?).as_bytes())?;
+
+		file.write_all(format!("{}\n\n", ts_export::<BundledUser>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<BundledLibrary>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<BundledNotifier>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<BundledScanSchedule>()?).as_bytes(),
+		)?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<BundledReadProgress>()?).as_bytes(),
+		)?;
+		file.write_all(format!("{}\n\n", ts_export::<EncryptedSecrets>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<ServerBundle>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<ConflictResolution>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<BundleImportOptions>()?).as_bytes(),
+		)?;
+		file.write_all(format!("{}\n\n", ts_export::<BundleItemKind>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<PlannedAction>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<PlannedChange>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<ImportPlan>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<MaintenanceMode>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<LibraryAvailabilityStatus>()?).as_bytes(),
+		)?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<LibraryAvailability>()?).as_bytes(),
+		)?;
+		file.write_all(format!("{}\n\n", ts_export::<WarmUpStatus>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<WaitReason>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<WaitExplanation>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<QueuedJob>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<QueueCompletionEstimate>()?).as_bytes(),
+		)?;
+		file.write_all(format!("{}\n\n", ts_export::<JobQueueSnapshot>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<JobQueueStatus>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<JobLimits>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<UpdateJobLimits>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<JobManagerConfig>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<JobWaitTimes>()?).as_bytes())?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<DatabaseMaintenanceStage>()?).as_bytes(),
+		)?;
+		file.write_all(
+			format!("{}\n\n", ts_export::<JobDurationPercentiles>()?).as_bytes(),
+		)?;
+		file.write_all(format!("{}\n\n", ts_export::<JobTypeDurations>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<TelemetryReport>()?).as_bytes())?;
+		file.write_all(format!("{}\n\n", ts_export::<ReadingStats>()?).as_bytes())?;
+
 		file.write_all(format!("{}\n\n", ts_export::<Direction>()?).as_bytes())?;
 		file.write_all(format!("{}\n\n", ts_export::<PageParams>()?).as_bytes())?;
 		file.write_all(format!("{}\n\n", ts_export::<QueryOrder>()?).as_bytes())?;
@@ -0,0 +1,204 @@
+//! Read-only maintenance mode. While maintenance mode is active, consumers are expected to
+//! reject anything which writes to the database (see the server's maintenance middleware),
+//! and new jobs are either rejected or held in the queue, depending on
+//! [StumpConfig::hold_jobs_during_maintenance](crate::config::StumpConfig::hold_jobs_during_maintenance).
+//!
+//! The mode is persisted on the `server_config` record so that it survives restarts, and
+//! an optional expiry acts as a safety net in case it is never exited manually.
+
+use std::sync::{Arc, RwLock};
+
+use prisma_client_rust::chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::{event::InternalCoreTask, prisma::server_config, CoreResult, Ctx};
+
+/// The in-memory maintenance state, shared between every copy of a [Ctx].
+pub type MaintenanceState = Arc<RwLock<Option<MaintenanceMode>>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub struct MaintenanceMode {
+	/// The message set by the server owner, shown to clients while maintenance is active
+	pub message: Option<String>,
+	/// The datetime stamp of when maintenance mode was entered
+	pub enabled_at: String,
+	/// The datetime stamp after which maintenance mode will automatically be exited
+	pub expires_at: Option<String>,
+}
+
+impl MaintenanceMode {
+	/// Returns true if the mode has an expiry which has passed, relative to `now`.
+	pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+		self.expires_at
+			.as_deref()
+			.and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+			.map(|expires_at| expires_at <= now)
+			.unwrap_or(false)
+	}
+}
+
+impl From<server_config::Data> for Option<MaintenanceMode> {
+	fn from(data: server_config::Data) -> Self {
+		data.maintenance_enabled_at
+			.map(|enabled_at| MaintenanceMode {
+				message: data.maintenance_message,
+				enabled_at: enabled_at.to_rfc3339(),
+				expires_at: data.maintenance_expires_at.map(|dt| dt.to_rfc3339()),
+			})
+	}
+}
+
+/// Enters maintenance mode, persisting it so that it is restored on restart. If maintenance
+/// mode is already active, the message and expiry are replaced.
+pub async fn enter_maintenance_mode(
+	ctx: &Ctx,
+	message: Option<String>,
+	expires_at: Option<DateTime<FixedOffset>>,
+) -> CoreResult<MaintenanceMode> {
+	let enabled_at: DateTime<FixedOffset> = Utc::now().into();
+
+	ctx.db
+		.server_config()
+		.update_many(
+			vec![],
+			vec![
+				server_config::maintenance_enabled_at::set(Some(enabled_at)),
+				server_config::maintenance_message::set(message.clone()),
+				server_config::maintenance_expires_at::set(expires_at),
+			],
+		)
+		.exec()
+		.await?;
+
+	let mode = MaintenanceMode {
+		message,
+		enabled_at: enabled_at.to_rfc3339(),
+		expires_at: expires_at.map(|dt| dt.to_rfc3339()),
+	};
+	set_maintenance_mode(ctx, Some(mode.clone()));
+	spawn_expiry_watcher(ctx.get_ctx(), mode.clone());
+
+	tracing::info!(?mode, "Entered maintenance mode");
+
+	Ok(mode)
+}
+
+/// Exits maintenance mode and resumes any jobs which were held while it was active.
+pub async fn exit_maintenance_mode(ctx: &Ctx) -> CoreResult<()> {
+	ctx.db
+		.server_config()
+		.update_many(
+			vec![],
+			vec![
+				server_config::maintenance_enabled_at::set(None),
+				server_config::maintenance_message::set(None),
+				server_config::maintenance_expires_at::set(None),
+			],
+		)
+		.exec()
+		.await?;
+
+	set_maintenance_mode(ctx, None);
+	if let Err(error) = ctx.dispatch_task(InternalCoreTask::ResumeHeldJobs) {
+		tracing::error!(?error, "Failed to resume held jobs");
+	}
+
+	tracing::info!("Exited maintenance mode");
+
+	Ok(())
+}
+
+/// Restores a persisted maintenance mode on startup. A mode which expired while the server
+/// was down is exited immediately.
+pub async fn restore_maintenance_mode(ctx: &Ctx) -> CoreResult<Option<MaintenanceMode>> {
+	let persisted = ctx
+		.db
+		.server_config()
+		.find_first(vec![])
+		.exec()
+		.await?
+		.and_then(Option::<MaintenanceMode>::from);
+
+	match persisted {
+		Some(mode) if mode.is_expired(Utc::now()) => {
+			tracing::debug!(?mode, "Persisted maintenance mode has expired");
+			exit_maintenance_mode(ctx).await?;
+			Ok(None)
+		},
+		Some(mode) => {
+			tracing::warn!(?mode, "Restoring persisted maintenance mode");
+			set_maintenance_mode(ctx, Some(mode.clone()));
+			spawn_expiry_watcher(ctx.get_ctx(), mode.clone());
+			Ok(Some(mode))
+		},
+		None => Ok(None),
+	}
+}
+
+fn set_maintenance_mode(ctx: &Ctx, mode: Option<MaintenanceMode>) {
+	match ctx.maintenance.write() {
+		Ok(mut state) => *state = mode,
+		Err(error) => tracing::error!(?error, "Maintenance state lock is poisoned"),
+	}
+}
+
+/// Spawns a task which exits maintenance mode once it expires, so long as the mode wasn't
+/// exited or replaced in the meantime.
+fn spawn_expiry_watcher(ctx: Ctx, mode: MaintenanceMode) {
+	let expires_at = match mode
+		.expires_at
+		.as_deref()
+		.and_then(|expires_at| DateTime::parse_from_rfc3339(expires_at).ok())
+	{
+		Some(expires_at) => expires_at,
+		None => return,
+	};
+
+	tokio::spawn(async move {
+		let remaining = (expires_at.with_timezone(&Utc) - Utc::now())
+			.to_std()
+			.unwrap_or_default();
+		tokio::time::sleep(remaining).await;
+
+		let is_same_mode = ctx
+			.maintenance
+			.read()
+			.map(|state| state.as_ref() == Some(&mode))
+			.unwrap_or(false);
+		if !is_same_mode {
+			return;
+		}
+
+		tracing::info!("Maintenance mode expired");
+		if let Err(error) = exit_maintenance_mode(&ctx).await {
+			tracing::error!(?error, "Failed to exit expired maintenance mode");
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn mode_expiring_at(expires_at: Option<&str>) -> MaintenanceMode {
+		MaintenanceMode {
+			message: Some(String::from("Moving the library to a new disk")),
+			enabled_at: String::from("2024-02-16T17:00:00+00:00"),
+			expires_at: expires_at.map(String::from),
+		}
+	}
+
+	#[test]
+	fn test_maintenance_mode_expiry() {
+		let now = DateTime::parse_from_rfc3339("2024-02-16T18:00:00+00:00")
+			.unwrap()
+			.with_timezone(&Utc);
+
+		assert!(!mode_expiring_at(None).is_expired(now));
+		assert!(!mode_expiring_at(Some("2024-02-16T19:00:00+00:00")).is_expired(now));
+		assert!(mode_expiring_at(Some("2024-02-16T18:00:00+00:00")).is_expired(now));
+		assert!(mode_expiring_at(Some("2024-02-16T19:00:00+02:00")).is_expired(now));
+	}
+}
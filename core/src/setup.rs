@@ -0,0 +1,80 @@
+//! First-run setup. A server without any users starts in setup mode, where consumers are
+//! expected to reject everything except the setup routes (see the server's setup
+//! middleware). Completing setup requires a one-time token, which is only ever written to
+//! the server log, so that whoever reaches an unclaimed server first over the network can't
+//! claim it.
+
+use std::sync::{Arc, RwLock};
+
+use uuid::Uuid;
+
+use crate::{CoreResult, Ctx};
+
+/// The in-memory setup state, shared between every copy of a [Ctx]. It holds the setup token
+/// while setup is pending, and is `None` otherwise.
+pub type SetupState = Arc<RwLock<Option<String>>>;
+
+/// Enters setup mode if no users exist, generating a setup token and writing it to the log.
+/// Returns whether setup is required.
+pub async fn init_setup_mode(ctx: &Ctx) -> CoreResult<bool> {
+	let has_users = ctx.db.user().find_first(vec![]).exec().await?.is_some();
+	if has_users {
+		return Ok(false);
+	}
+
+	let token = Uuid::new_v4().simple().to_string();
+	tracing::warn!(
+		setup_token = token,
+		"No users exist, so Stump is in setup mode. Use this token to complete setup"
+	);
+	set_setup_token(ctx, Some(token));
+
+	Ok(true)
+}
+
+/// Returns true if the given token matches the pending setup token. Always false once setup
+/// has been completed.
+pub fn verify_setup_token(ctx: &Ctx, token: &str) -> bool {
+	ctx.setup
+		.read()
+		.ok()
+		.and_then(|state| state.clone())
+		.is_some_and(|expected| tokens_match(&expected, token))
+}
+
+/// Exits setup mode, invalidating the setup token.
+pub fn complete_setup(ctx: &Ctx) {
+	set_setup_token(ctx, None);
+	tracing::info!("Setup completed");
+}
+
+fn set_setup_token(ctx: &Ctx, token: Option<String>) {
+	match ctx.setup.write() {
+		Ok(mut state) => *state = token,
+		Err(error) => tracing::error!(?error, "Setup state lock is poisoned"),
+	}
+}
+
+/// Compares the tokens in constant time (for tokens of the same length), so the token can't
+/// be guessed a character at a time.
+fn tokens_match(expected: &str, actual: &str) -> bool {
+	expected.len() == actual.len()
+		&& expected
+			.bytes()
+			.zip(actual.bytes())
+			.fold(0, |acc, (a, b)| acc | (a ^ b))
+			== 0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_tokens_match() {
+		assert!(tokens_match("4f1c2a", "4f1c2a"));
+		assert!(!tokens_match("4f1c2a", "4f1c2b"));
+		assert!(!tokens_match("4f1c2a", "4f1c2"));
+		assert!(!tokens_match("4f1c2a", ""));
+	}
+}
@@ -0,0 +1,194 @@
+//! Support bundles, which gather the state of the server that maintainers need to diagnose a
+//! reported problem (e.g. with the job system) into a single zip archive. The bundle never
+//! contains secrets, and the user-identifiable segments of paths can optionally be redacted.
+
+use std::io::{Cursor, Write};
+
+use prisma_client_rust::Direction;
+use regex::Regex;
+use serde::Serialize;
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::{
+	config::StumpConfig,
+	db::{
+		entity::Log,
+		migration::{get_migration_status, MigrationStatus},
+	},
+	job::{JobDetail, JobQueueSnapshot},
+	prisma::{job, log, PrismaClient},
+	CoreError, CoreResult,
+};
+
+/// The number of jobs included in a bundle by default
+pub const DEFAULT_SUPPORT_BUNDLE_JOB_LIMIT: i64 = 50;
+/// The number of lines from the end of the server log included in a bundle
+const LOG_TAIL_LINES: usize = 1_000;
+/// The replacement for redacted path segments
+const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Clone)]
+pub struct SupportBundleOptions {
+	/// The number of most recent jobs (with their logs) to include
+	pub job_limit: i64,
+	/// Whether to redact the user-identifiable segments of paths, e.g. the username in
+	/// `/home/oromei`
+	pub redact_paths: bool,
+}
+
+/// A job in a support bundle, alongside its persisted logs and output
+#[derive(Serialize)]
+struct SupportBundleJob {
+	#[serde(flatten)]
+	detail: JobDetail,
+	/// The extra metadata the job persisted, if it was valid JSON
+	output: Option<serde_json::Value>,
+	logs: Vec<Log>,
+}
+
+/// Builds a support bundle, returning the bytes of the zip archive. The queue snapshot is
+/// provided by the caller, since only the job manager can produce it.
+pub async fn build_support_bundle(
+	client: &PrismaClient,
+	config: &StumpConfig,
+	queue: Option<JobQueueSnapshot>,
+	options: SupportBundleOptions,
+) -> CoreResult<Vec<u8>> {
+	let jobs = get_recent_jobs(client, options.job_limit).await?;
+	let migrations: MigrationStatus = get_migration_status(client).await?;
+	let log_tail = read_log_tail(config);
+
+	let files = vec![
+		("config.json", serde_json::to_string_pretty(config)?),
+		("jobs.json", serde_json::to_string_pretty(&jobs)?),
+		("queue.json", serde_json::to_string_pretty(&queue)?),
+		(
+			"migrations.json",
+			serde_json::to_string_pretty(&migrations)?,
+		),
+		("server.log", log_tail),
+	];
+
+	let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+	for (name, contents) in files {
+		let contents = if options.redact_paths {
+			redact_user_paths(&contents)
+		} else {
+			contents
+		};
+
+		writer
+			.start_file(name, FileOptions::default())
+			.map_err(|error| CoreError::InternalError(error.to_string()))?;
+		writer.write_all(contents.as_bytes())?;
+	}
+
+	let archive = writer
+		.finish()
+		.map_err(|error| CoreError::InternalError(error.to_string()))?;
+
+	Ok(archive.into_inner())
+}
+
+async fn get_recent_jobs(
+	client: &PrismaClient,
+	limit: i64,
+) -> CoreResult<Vec<SupportBundleJob>> {
+	let jobs = client
+		.job()
+		.find_many(vec![])
+		.with(job::logs::fetch(vec![]).order_by(log::created_at::order(Direction::Asc)))
+		.order_by(job::created_at::order(Direction::Desc))
+		.take(limit)
+		.exec()
+		.await?;
+
+	Ok(jobs
+		.into_iter()
+		.map(|mut job| {
+			let logs = job
+				.logs
+				.take()
+				.unwrap_or_default()
+				.into_iter()
+				.map(|log| Log {
+					level: log.level.parse().unwrap_or_default(),
+					message: log.message,
+					created_at: log.created_at.to_rfc3339(),
+					job_id: log.job_id,
+					id: log.id,
+				})
+				.collect();
+			let output = job
+				.extra_metadata
+				.as_ref()
+				.and_then(|bytes| serde_json::from_slice(bytes).ok());
+
+			SupportBundleJob {
+				detail: JobDetail::from(job),
+				output,
+				logs,
+			}
+		})
+		.collect())
+}
+
+/// Returns the last [LOG_TAIL_LINES] lines of the server log, or an empty string if it
+/// can't be read
+fn read_log_tail(config: &StumpConfig) -> String {
+	let contents = match std::fs::read(config.get_log_file()) {
+		Ok(contents) => String::from_utf8_lossy(&contents).into_owned(),
+		Err(error) => {
+			tracing::warn!(?error, "Failed to read the log file for a support bundle");
+			return String::new();
+		},
+	};
+
+	tail_lines(&contents, LOG_TAIL_LINES)
+}
+
+fn tail_lines(contents: &str, count: usize) -> String {
+	let lines = contents.lines().collect::<Vec<&str>>();
+	lines[lines.len().saturating_sub(count)..].join("\n")
+}
+
+/// Replaces the username segment of home directory paths (e.g. `/home/oromei`,
+/// `/Users/oromei` or `C:\Users\oromei`) with a placeholder.
+fn redact_user_paths(contents: &str) -> String {
+	let pattern = Regex::new(r#"(/home/|/Users/|\\Users\\|\\\\Users\\\\)[^/\\"\s]+"#)
+		.expect("Failed to compile path redaction pattern");
+
+	pattern
+		.replace_all(contents, format!("${{1}}{}", REDACTED))
+		.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_redact_user_paths() {
+		assert_eq!(
+			redact_user_paths(r#"{"config_dir":"/home/oromei/.stump"}"#),
+			r#"{"config_dir":"/home/<redacted>/.stump"}"#
+		);
+		assert_eq!(
+			redact_user_paths("Scanning /Users/oromei/Documents/Stump"),
+			"Scanning /Users/<redacted>/Documents/Stump"
+		);
+		// Backslashes are escaped once serialized to JSON
+		assert_eq!(
+			redact_user_paths(r#"{"db_path":"C:\\Users\\oromei\\stump.db"}"#),
+			r#"{"db_path":"C:\\Users\\<redacted>\\stump.db"}"#
+		);
+		assert_eq!(redact_user_paths("/data/library"), "/data/library");
+	}
+
+	#[test]
+	fn test_tail_lines() {
+		assert_eq!(tail_lines("one\ntwo\nthree\n", 2), "two\nthree");
+		assert_eq!(tail_lines("one", 5), "one");
+		assert_eq!(tail_lines("", 5), "");
+	}
+}
@@ -0,0 +1,250 @@
+//! Opt-in, anonymous usage telemetry. Nothing in this module is collected unless an
+//! owner has explicitly opted in via [StumpConfig::enable_telemetry], and the report
+//! only ever contains coarse aggregates (never names, paths or identifiers). The
+//! report can always be inspected locally before anything would leave the server.
+//!
+//! Once opted into, the server periodically sends the report to the configured
+//! [StumpConfig::telemetry_url] (and nowhere, if none is configured). The report
+//! previewed here is exactly what is sent.
+
+use prisma_client_rust::{raw, PrismaValue};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use utoipa::ToSchema;
+
+use crate::{
+	config::{env_keys::DISABLE_TELEMETRY_KEY, StumpConfig},
+	job::JobStatus,
+	prisma::PrismaClient,
+	CoreResult,
+};
+
+/// The upper bounds of the buckets used to report counts, so that exact library sizes are
+/// never reported.
+const COUNT_BUCKETS: [i64; 6] = [0, 10, 100, 1_000, 10_000, 100_000];
+
+#[derive(
+	Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema,
+)]
+pub struct JobDurationPercentiles {
+	/// The number of jobs the percentiles were calculated from
+	pub sample_size: usize,
+	pub p50_ms: Option<u64>,
+	pub p90_ms: Option<u64>,
+	pub p99_ms: Option<u64>,
+}
+
+/// Percentiles of the duration of the completed jobs of a single type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Type, ToSchema)]
+pub struct JobTypeDurations {
+	/// The type of the job, e.g. library_scan
+	pub job_type: String,
+	/// The number of jobs the percentiles were calculated from
+	pub sample_size: i64,
+	pub p50_ms: i64,
+	pub p90_ms: i64,
+	pub p99_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, ToSchema)]
+pub struct TelemetryReport {
+	/// The version of Stump the report was generated by
+	pub version: String,
+	/// The bucketed number of libraries, e.g. 1-10
+	pub library_count: String,
+	/// The bucketed number of media, e.g. 1001-10000
+	pub media_count: String,
+	/// Percentiles of the duration of completed jobs, per job type
+	pub job_durations: Vec<JobTypeDurations>,
+	/// The (optional) features which are in use on the server
+	pub enabled_features: Vec<String>,
+}
+
+/// Returns whether telemetry should be collected. The [DISABLE_TELEMETRY_KEY] environment
+/// variable acts as a kill switch, and overrides any persisted opt-in.
+pub fn is_telemetry_enabled(config: &StumpConfig) -> bool {
+	let killed = std::env::var(DISABLE_TELEMETRY_KEY)
+		.map(|value| value == "true")
+		.unwrap_or(false);
+
+	config.enable_telemetry && !killed
+}
+
+/// Builds the [TelemetryReport] for the server. This is safe to call regardless of whether
+/// telemetry is enabled, since it is also used to preview what would be reported.
+pub async fn collect_telemetry_report(
+	client: &PrismaClient,
+	config: &StumpConfig,
+) -> CoreResult<TelemetryReport> {
+	let library_count = client.library().count(vec![]).exec().await?;
+	let media_count = client.media().count(vec![]).exec().await?;
+	let smart_list_count = client.smart_list().count(vec![]).exec().await?;
+	let book_club_count = client.book_club().count(vec![]).exec().await?;
+	let notifier_count = client.notifier().count(vec![]).exec().await?;
+
+	let scheduled_scans = client
+		.server_config()
+		.find_first(vec![])
+		.exec()
+		.await?
+		.map(|server_config| server_config.job_schedule_config_id.is_some())
+		.unwrap_or(false);
+
+	// The nearest-rank percentiles are calculated in the query (see [duration_percentiles]),
+	// so that the durations of every job are never loaded
+	let job_durations = client
+		._query_raw::<JobTypeDurations>(raw!(
+			"WITH ranked AS (SELECT name, ms_elapsed, ROW_NUMBER() OVER (PARTITION BY name ORDER BY ms_elapsed) as position, COUNT(*) OVER (PARTITION BY name) as job_count FROM jobs WHERE status={}) SELECT name as job_type, MAX(job_count) as sample_size, MAX(CASE WHEN position = MAX(1, (50 * job_count + 99) / 100) THEN ms_elapsed END) as p50_ms, MAX(CASE WHEN position = MAX(1, (90 * job_count + 99) / 100) THEN ms_elapsed END) as p90_ms, MAX(CASE WHEN position = MAX(1, (99 * job_count + 99) / 100) THEN ms_elapsed END) as p99_ms FROM ranked GROUP BY name ORDER BY name",
+			PrismaValue::String(JobStatus::Completed.to_string())
+		))
+		.exec()
+		.await?;
+
+	let enabled_features = [
+		("scheduled_scans", scheduled_scans),
+		("smart_lists", smart_list_count > 0),
+		("book_clubs", book_club_count > 0),
+		("notifiers", notifier_count > 0),
+		("pdfium", config.pdfium_path.is_some()),
+		("swagger", !config.disable_swagger),
+	]
+	.into_iter()
+	.filter(|(_, enabled)| *enabled)
+	.map(|(feature, _)| feature.to_string())
+	.collect();
+
+	Ok(TelemetryReport {
+		version: env!("CARGO_PKG_VERSION").to_string(),
+		library_count: count_bucket(library_count),
+		media_count: count_bucket(media_count),
+		job_durations,
+		enabled_features,
+	})
+}
+
+fn count_bucket(count: i64) -> String {
+	let mut lower = 0;
+	for upper in COUNT_BUCKETS {
+		if count <= upper {
+			return if upper == 0 {
+				String::from("0")
+			} else {
+				format!("{}-{}", lower, upper)
+			};
+		}
+		lower = upper + 1;
+	}
+
+	format!("{}+", lower)
+}
+
+pub(crate) fn duration_percentiles(mut durations: Vec<u64>) -> JobDurationPercentiles {
+	durations.sort_unstable();
+
+	let percentile = |pct: usize| {
+		if durations.is_empty() {
+			None
+		} else {
+			// Nearest-rank method
+			let rank = ((pct * durations.len() + 99) / 100).max(1);
+			durations.get(rank - 1).copied()
+		}
+	};
+
+	JobDurationPercentiles {
+		sample_size: durations.len(),
+		p50_ms: percentile(50),
+		p90_ms: percentile(90),
+		p99_ms: percentile(99),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{prisma::job, Ctx};
+
+	#[test]
+	fn test_count_bucket() {
+		assert_eq!(count_bucket(0), "0");
+		assert_eq!(count_bucket(1), "1-10");
+		assert_eq!(count_bucket(10), "1-10");
+		assert_eq!(count_bucket(11), "11-100");
+		assert_eq!(count_bucket(4_500), "1001-10000");
+		assert_eq!(count_bucket(250_000), "100001+");
+	}
+
+	#[test]
+	fn test_duration_percentiles() {
+		assert_eq!(
+			duration_percentiles(vec![]),
+			JobDurationPercentiles::default()
+		);
+
+		let durations = (1..=100).rev().collect::<Vec<u64>>();
+		let percentiles = duration_percentiles(durations);
+		assert_eq!(percentiles.sample_size, 100);
+		assert_eq!(percentiles.p50_ms, Some(50));
+		assert_eq!(percentiles.p90_ms, Some(90));
+		assert_eq!(percentiles.p99_ms, Some(99));
+
+		let percentiles = duration_percentiles(vec![42]);
+		assert_eq!(percentiles.p50_ms, Some(42));
+		assert_eq!(percentiles.p99_ms, Some(42));
+	}
+
+	#[tokio::test]
+	async fn test_job_durations_are_aggregated_per_type() {
+		let (client, _dir) = Ctx::mock_db().await;
+
+		for (job_id, name, status, ms_elapsed) in [
+			("scan-1", "library_scan", JobStatus::Completed, 100),
+			("scan-2", "library_scan", JobStatus::Completed, 301),
+			("scan-3", "library_scan", JobStatus::Failed, 10_000),
+			(
+				"thumbnails",
+				"thumbnail_generation",
+				JobStatus::Completed,
+				50,
+			),
+		] {
+			client
+				.job()
+				.create(
+					String::from(job_id),
+					String::from(name),
+					vec![
+						job::status::set(status.to_string()),
+						job::ms_elapsed::set(ms_elapsed),
+					],
+				)
+				.exec()
+				.await
+				.unwrap();
+		}
+
+		let report = collect_telemetry_report(&client, &StumpConfig::debug())
+			.await
+			.unwrap();
+
+		assert_eq!(
+			report.job_durations,
+			vec![
+				JobTypeDurations {
+					job_type: String::from("library_scan"),
+					sample_size: 2,
+					p50_ms: 100,
+					p90_ms: 301,
+					p99_ms: 301,
+				},
+				JobTypeDurations {
+					job_type: String::from("thumbnail_generation"),
+					sample_size: 1,
+					p50_ms: 50,
+					p90_ms: 50,
+					p99_ms: 50,
+				},
+			]
+		);
+	}
+}
@@ -0,0 +1,274 @@
+//! The startup warm-up, which waits for library directories to become available before
+//! anything scans them. Network shares (e.g. on a NAS) are often mounted after Stump starts,
+//! and scanning a library before its directory is mounted would mark all of its books as
+//! missing. Each library root is retried until the configured timeout, and any which never
+//! become available are flagged so that scheduled scans skip them.
+
+use std::{
+	path::Path,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::sync::watch;
+use utoipa::ToSchema;
+
+use crate::{event::CoreEvent, prisma::library, CoreResult, Ctx};
+
+/// How long to wait between checks of the library directories which aren't yet available
+const WARM_UP_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LibraryAvailabilityStatus {
+	/// The library directory is available
+	Available,
+	/// The library directory isn't available yet, and is being retried
+	Waiting,
+	/// The library directory didn't become available before the warm-up timed out
+	Unavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+pub struct LibraryAvailability {
+	pub library_id: String,
+	pub path: String,
+	pub status: LibraryAvailabilityStatus,
+	/// The number of times the directory has been checked
+	pub attempts: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+pub struct WarmUpStatus {
+	/// Whether the warm-up has finished, either because every library is available or
+	/// because it timed out
+	pub is_complete: bool,
+	/// The availability of each library checked during the warm-up
+	pub libraries: Vec<LibraryAvailability>,
+}
+
+/// The warm-up state, shared between every copy of a [Ctx]. Until a warm-up is started, it
+/// is considered complete, so nothing waits on it.
+#[derive(Clone)]
+pub struct WarmUpState(Arc<watch::Sender<WarmUpStatus>>);
+
+impl Default for WarmUpState {
+	fn default() -> Self {
+		Self(Arc::new(
+			watch::channel(WarmUpStatus {
+				is_complete: true,
+				libraries: vec![],
+			})
+			.0,
+		))
+	}
+}
+
+impl WarmUpState {
+	pub fn status(&self) -> WarmUpStatus {
+		self.0.borrow().clone()
+	}
+
+	/// Waits until the warm-up (if any) has finished.
+	pub async fn wait_until_complete(&self) {
+		let mut receiver = self.0.subscribe();
+		// The sender is owned by the state, so this can't fail while it exists
+		let _ = receiver.wait_for(|status| status.is_complete).await;
+	}
+
+	/// Returns true if the library was flagged as unavailable by the warm-up.
+	pub fn is_library_unavailable(&self, library_id: &str) -> bool {
+		self.0.borrow().libraries.iter().any(|library| {
+			library.library_id == library_id
+				&& library.status == LibraryAvailabilityStatus::Unavailable
+		})
+	}
+
+	/// Clears the unavailable flag of a library, once its directory is available again.
+	pub fn mark_library_available(&self, library_id: &str) {
+		self.0.send_modify(|status| {
+			if let Some(library) = status
+				.libraries
+				.iter_mut()
+				.find(|library| library.library_id == library_id)
+			{
+				library.status = LibraryAvailabilityStatus::Available;
+			}
+		});
+	}
+
+	fn update(&self, status: WarmUpStatus) {
+		self.0.send_replace(status);
+	}
+}
+
+/// Returns true if the library directory exists and can be read. A directory which is empty,
+/// for a library which has series, is most likely a mount point for a share which hasn't
+/// been mounted yet, so it isn't considered available.
+pub fn is_library_root_available(path: &Path, has_series: bool) -> bool {
+	match std::fs::read_dir(path) {
+		Ok(mut entries) => !has_series || entries.next().is_some(),
+		Err(_) => false,
+	}
+}
+
+/// Starts the warm-up in the background, unless it is disabled. The state is marked as
+/// incomplete before this returns, so anything started afterwards can wait on it.
+pub fn spawn_warm_up(ctx: Ctx) {
+	let timeout_secs = ctx.config.library_warm_up_timeout_secs;
+	if timeout_secs == 0 {
+		tracing::debug!("Library warm-up is disabled");
+		return;
+	}
+
+	ctx.warm_up.update(WarmUpStatus {
+		is_complete: false,
+		libraries: vec![],
+	});
+
+	tokio::spawn(async move {
+		let timeout = Duration::from_secs(timeout_secs);
+		if let Err(error) = run_warm_up(&ctx, timeout).await {
+			tracing::error!(?error, "Library warm-up failed");
+			// Nothing should be left waiting on a warm-up which can't finish
+			ctx.warm_up.update(WarmUpStatus {
+				is_complete: true,
+				..ctx.warm_up.status()
+			});
+		}
+	});
+}
+
+async fn run_warm_up(ctx: &Ctx, timeout: Duration) -> CoreResult<()> {
+	let libraries = ctx
+		.db
+		.library()
+		.find_many(vec![library::archived::equals(false)])
+		.with(library::series::fetch(vec![]).take(1))
+		.exec()
+		.await?;
+	let has_series = libraries
+		.iter()
+		.map(|library| library.series().map(|s| !s.is_empty()).unwrap_or(false))
+		.collect::<Vec<bool>>();
+
+	let mut status = WarmUpStatus {
+		is_complete: false,
+		libraries: libraries
+			.into_iter()
+			.map(|library| LibraryAvailability {
+				library_id: library.id,
+				path: library.path,
+				status: LibraryAvailabilityStatus::Waiting,
+				attempts: 0,
+			})
+			.collect(),
+	};
+
+	let deadline = Instant::now() + timeout;
+	loop {
+		for (library, has_series) in status.libraries.iter_mut().zip(has_series.iter()) {
+			if library.status != LibraryAvailabilityStatus::Waiting {
+				continue;
+			}
+
+			library.attempts += 1;
+			if is_library_root_available(Path::new(&library.path), *has_series) {
+				library.status = LibraryAvailabilityStatus::Available;
+			} else if library.attempts == 1 {
+				tracing::warn!(
+					path = library.path.as_str(),
+					"Waiting for library directory"
+				);
+				ctx.emit_event(CoreEvent::LibraryWaiting {
+					library_id: library.library_id.clone(),
+					path: library.path.clone(),
+				});
+			}
+		}
+
+		let is_waiting = status
+			.libraries
+			.iter()
+			.any(|library| library.status == LibraryAvailabilityStatus::Waiting);
+		if !is_waiting || Instant::now() + WARM_UP_RETRY_INTERVAL > deadline {
+			break;
+		}
+
+		ctx.warm_up.update(status.clone());
+		tokio::time::sleep(WARM_UP_RETRY_INTERVAL).await;
+	}
+
+	let mut unavailable_library_ids = vec![];
+	for library in status.libraries.iter_mut() {
+		if library.status == LibraryAvailabilityStatus::Waiting {
+			library.status = LibraryAvailabilityStatus::Unavailable;
+			tracing::error!(
+				path = library.path.as_str(),
+				"Library directory did not become available during warm-up"
+			);
+			unavailable_library_ids.push(library.library_id.clone());
+		}
+	}
+
+	status.is_complete = true;
+	ctx.warm_up.update(status);
+	ctx.emit_event(CoreEvent::WarmUpComplete {
+		unavailable_library_ids,
+	});
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_is_library_root_available() {
+		let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+
+		assert!(!is_library_root_available(&dir.path().join("nas"), false));
+		// An empty directory is only available if the library has nothing in it yet
+		assert!(is_library_root_available(dir.path(), false));
+		assert!(!is_library_root_available(dir.path(), true));
+
+		std::fs::create_dir(dir.path().join("Batman")).unwrap();
+		assert!(is_library_root_available(dir.path(), true));
+	}
+
+	#[tokio::test]
+	async fn test_wait_until_complete() {
+		let state = WarmUpState::default();
+		// Without a warm-up, nothing waits
+		state.wait_until_complete().await;
+
+		state.update(WarmUpStatus {
+			is_complete: false,
+			libraries: vec![LibraryAvailability {
+				library_id: String::from("library"),
+				path: String::from("/mnt/nas/comics"),
+				status: LibraryAvailabilityStatus::Waiting,
+				attempts: 1,
+			}],
+		});
+		let waiter = tokio::spawn({
+			let state = state.clone();
+			async move { state.wait_until_complete().await }
+		});
+		tokio::time::sleep(Duration::from_millis(10)).await;
+		assert!(!waiter.is_finished());
+
+		let mut status = state.status();
+		status.is_complete = true;
+		status.libraries[0].status = LibraryAvailabilityStatus::Unavailable;
+		state.update(status);
+		waiter.await.unwrap();
+
+		assert!(state.is_library_unavailable("library"));
+		state.mark_library_available("library");
+		assert!(!state.is_library_unavailable("library"));
+	}
+}
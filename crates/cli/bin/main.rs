@@ -12,9 +12,18 @@ async fn main() {
 		.expect("Failed to initialize stump configuration");
 
 	if let Some(command) = app.command {
-		handle_command(command, &app.config.merge_stump_config(stump_config))
-			.await
-			.expect("Failed to handle command");
+		let result = handle_command(
+			command,
+			&app.config.merge_stump_config(stump_config),
+			app.json,
+		)
+		.await;
+
+		// Any output (e.g. JSON) has already been printed, so just exit with a failure
+		if let Err(error) = result {
+			eprintln!("{}", error);
+			std::process::exit(1);
+		}
 	} else {
 		println!("No command provided! This would start the server IRL");
 	}
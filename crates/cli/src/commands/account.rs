@@ -2,16 +2,34 @@ use std::{thread, time::Duration};
 
 use clap::Subcommand;
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password};
+use serde::Serialize;
 
 use stump_core::{
 	config::StumpConfig,
 	db::create_client,
-	prisma::{session, user},
+	prisma::{session, user, PrismaClient},
 };
 
 use crate::{commands::chain_optional_iter, error::CliResult, CliError};
 
-use super::default_progress_spinner;
+use super::{default_progress_spinner, lock_database_for_writing, print_json};
+
+#[derive(Serialize)]
+struct AccountOutput {
+	username: String,
+	is_locked: bool,
+	is_server_owner: bool,
+}
+
+impl From<user::Data> for AccountOutput {
+	fn from(user: user::Data) -> Self {
+		Self {
+			username: user.username,
+			is_locked: user.is_locked,
+			is_server_owner: user.is_server_owner,
+		}
+	}
+}
 
 /// Subcommands for interacting with Stump accounts
 #[derive(Subcommand, Debug)]
@@ -42,24 +60,35 @@ pub enum Account {
 	},
 	/// Enter a flow to change the server owner to another account
 	ResetOwner,
+	/// Change the server owner to another account without any prompts
+	PromoteOwner {
+		/// The username of the account to assign as server owner
+		#[clap(long)]
+		username: String,
+	},
 }
 
 pub async fn handle_account_command(
 	command: Account,
 	config: &StumpConfig,
+	json: bool,
 ) -> CliResult<()> {
 	match command {
 		Account::Lock { username } => {
-			set_account_lock_status(username, true, config).await
+			set_account_lock_status(username, true, config, json).await
 		},
 		Account::Unlock { username } => {
-			set_account_lock_status(username, false, config).await
+			set_account_lock_status(username, false, config, json).await
 		},
-		Account::List { locked } => print_accounts(locked, config).await,
+		Account::List { locked } => print_accounts(locked, config, json).await,
 		Account::ResetPassword { username } => {
-			reset_account_password(username, config.password_hash_cost, config).await
+			reset_account_password(username, config.password_hash_cost, config, json)
+				.await
+		},
+		Account::ResetOwner => change_server_owner(config, json).await,
+		Account::PromoteOwner { username } => {
+			promote_server_owner(username, config, json).await
 		},
-		Account::ResetOwner => change_server_owner(config).await,
 	}
 }
 
@@ -67,7 +96,10 @@ async fn set_account_lock_status(
 	username: String,
 	lock: bool,
 	config: &StumpConfig,
+	json: bool,
 ) -> CliResult<()> {
+	let _lock = lock_database_for_writing(config)?;
+
 	let progress = default_progress_spinner();
 	progress.set_message(if lock {
 		"Locking account..."
@@ -91,7 +123,7 @@ async fn set_account_lock_status(
 		client
 			.session()
 			.delete_many(vec![session::user::is(vec![user::username::equals(
-				username,
+				username.clone(),
 			)])])
 			.exec()
 			.await?;
@@ -110,6 +142,16 @@ async fn set_account_lock_status(
 		} else {
 			"Account unlocked successfully!"
 		});
+
+		if json {
+			let updated_user = client
+				.user()
+				.find_first(vec![user::username::equals(username)])
+				.exec()
+				.await?;
+			print_json(&updated_user.map(AccountOutput::from))?;
+		}
+
 		Ok(())
 	}
 }
@@ -118,7 +160,9 @@ async fn reset_account_password(
 	username: String,
 	hash_cost: u32,
 	config: &StumpConfig,
+	json: bool,
 ) -> CliResult<()> {
+	let _lock = lock_database_for_writing(config)?;
 	let client = create_client(config).await;
 
 	let theme = &ColorfulTheme::default();
@@ -137,7 +181,7 @@ async fn reset_account_password(
 	let affected_rows = client
 		.user()
 		.update_many(
-			vec![user::username::equals(username)],
+			vec![user::username::equals(username.clone())],
 			vec![user::hashed_password::set(hashed_password)],
 		)
 		.exec()
@@ -152,11 +196,25 @@ async fn reset_account_password(
 		)))
 	} else {
 		progress.finish_with_message("Account password updated successfully!");
+
+		if json {
+			let updated_user = client
+				.user()
+				.find_first(vec![user::username::equals(username)])
+				.exec()
+				.await?;
+			print_json(&updated_user.map(AccountOutput::from))?;
+		}
+
 		Ok(())
 	}
 }
 
-async fn print_accounts(locked: Option<bool>, config: &StumpConfig) -> CliResult<()> {
+async fn print_accounts(
+	locked: Option<bool>,
+	config: &StumpConfig,
+	json: bool,
+) -> CliResult<()> {
 	let progress = default_progress_spinner();
 	progress.set_message("Fetching accounts...");
 
@@ -171,6 +229,16 @@ async fn print_accounts(locked: Option<bool>, config: &StumpConfig) -> CliResult
 		.exec()
 		.await?;
 
+	if json {
+		progress.finish_and_clear();
+		return print_json(
+			&users
+				.into_iter()
+				.map(AccountOutput::from)
+				.collect::<Vec<AccountOutput>>(),
+		);
+	}
+
 	// Print results from database
 	if users.is_empty() {
 		progress.finish_with_message("No accounts found.");
@@ -194,7 +262,8 @@ async fn print_accounts(locked: Option<bool>, config: &StumpConfig) -> CliResult
 	Ok(())
 }
 
-async fn change_server_owner(config: &StumpConfig) -> CliResult<()> {
+async fn change_server_owner(config: &StumpConfig, json: bool) -> CliResult<()> {
+	let _lock = lock_database_for_writing(config)?;
 	let client = create_client(config).await;
 
 	let all_accounts = client
@@ -237,6 +306,90 @@ async fn change_server_owner(config: &StumpConfig) -> CliResult<()> {
 		))?;
 
 	let progress = default_progress_spinner();
+	assign_server_owner(
+		&client,
+		current_server_owner,
+		target_user.clone(),
+		&progress,
+	)
+	.await?;
+	progress.finish_with_message("Successfully changed the server owner!");
+
+	if json {
+		print_json(&AccountOutput {
+			is_server_owner: true,
+			..AccountOutput::from(target_user)
+		})?;
+	}
+
+	Ok(())
+}
+
+async fn promote_server_owner(
+	username: String,
+	config: &StumpConfig,
+	json: bool,
+) -> CliResult<()> {
+	let _lock = lock_database_for_writing(config)?;
+
+	let progress = default_progress_spinner();
+	progress.set_message("Fetching accounts...");
+
+	let client = create_client(config).await;
+
+	let target_user = client
+		.user()
+		.find_first(vec![
+			user::username::equals(username),
+			user::is_locked::equals(false),
+		])
+		.exec()
+		.await?
+		.ok_or_else(|| {
+			progress.abandon_with_message(
+				"An account with that username does not exist or their account is locked",
+			);
+			CliError::OperationFailed(String::from(
+				"An account with that username does not exist or their account is locked",
+			))
+		})?;
+
+	if target_user.is_server_owner {
+		progress.finish_with_message("Account is already the server owner");
+	} else {
+		let current_server_owner = client
+			.user()
+			.find_first(vec![user::is_server_owner::equals(true)])
+			.exec()
+			.await?;
+		assign_server_owner(
+			&client,
+			current_server_owner,
+			target_user.clone(),
+			&progress,
+		)
+		.await?;
+		progress.finish_with_message("Successfully changed the server owner!");
+	}
+
+	if json {
+		print_json(&AccountOutput {
+			is_server_owner: true,
+			..AccountOutput::from(target_user)
+		})?;
+	}
+
+	Ok(())
+}
+
+/// Moves the server owner status from the current owner (if any) to the target user,
+/// removing the active login sessions of both so the change takes effect immediately.
+async fn assign_server_owner(
+	client: &PrismaClient,
+	current_server_owner: Option<user::Data>,
+	target_user: user::Data,
+	progress: &indicatif::ProgressBar,
+) -> CliResult<()> {
 	if let Some(user) = current_server_owner {
 		progress.set_message(format!("Removing owner status from {}", user.username));
 		client
@@ -269,7 +422,5 @@ async fn change_server_owner(config: &StumpConfig) -> CliResult<()> {
 		.exec()
 		.await?;
 
-	progress.finish_with_message("Successfully changed the server owner!");
-
 	Ok(())
 }
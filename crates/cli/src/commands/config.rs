@@ -0,0 +1,57 @@
+use clap::Subcommand;
+use serde::Serialize;
+use stump_core::config::StumpConfig;
+
+use super::print_json;
+use crate::{error::CliResult, CliError};
+
+/// Subcommands for interacting with the Stump configuration
+#[derive(Subcommand, Debug)]
+pub enum Config {
+	/// Validate the configuration resolved from Stump.toml, environment variables and
+	/// any CLI flags
+	Validate,
+}
+
+#[derive(Serialize)]
+struct ValidateOutput {
+	valid: bool,
+	issues: Vec<String>,
+}
+
+pub async fn handle_config_command(
+	command: Config,
+	config: &StumpConfig,
+	json: bool,
+) -> CliResult<()> {
+	match command {
+		Config::Validate => validate_config(config, json),
+	}
+}
+
+fn validate_config(config: &StumpConfig, json: bool) -> CliResult<()> {
+	let issues = config.validate();
+	let valid = issues.is_empty();
+
+	if json {
+		print_json(&ValidateOutput {
+			valid,
+			issues: issues.clone(),
+		})?;
+	} else if valid {
+		println!("Configuration is valid");
+	} else {
+		for issue in &issues {
+			println!("- {}", issue);
+		}
+	}
+
+	if valid {
+		Ok(())
+	} else {
+		Err(CliError::OperationFailed(format!(
+			"Configuration has {} issue(s)",
+			issues.len()
+		)))
+	}
+}
@@ -0,0 +1,54 @@
+use clap::Subcommand;
+use serde::Serialize;
+use stump_core::{
+	config::StumpConfig,
+	db::create_client,
+	job::{cancel_persisted_jobs, JobStatus},
+};
+
+use super::{default_progress_spinner, lock_database_for_writing, print_json};
+use crate::error::CliResult;
+
+/// Subcommands for interacting with the job queue
+#[derive(Subcommand, Debug)]
+pub enum Jobs {
+	/// Cancel all queued and running jobs. The server must be stopped to use this command
+	ClearQueue,
+}
+
+#[derive(Serialize)]
+struct ClearQueueOutput {
+	cancelled_count: i64,
+}
+
+pub async fn handle_jobs_command(
+	command: Jobs,
+	config: &StumpConfig,
+	json: bool,
+) -> CliResult<()> {
+	match command {
+		Jobs::ClearQueue => clear_job_queue(config, json).await,
+	}
+}
+
+async fn clear_job_queue(config: &StumpConfig, json: bool) -> CliResult<()> {
+	let _lock = lock_database_for_writing(config)?;
+
+	let progress = default_progress_spinner();
+	progress.set_message("Connecting to database...");
+
+	let client = create_client(config).await;
+
+	progress.set_message("Cancelling queued jobs...");
+	let cancelled_count =
+		cancel_persisted_jobs(&client, vec![JobStatus::Queued, JobStatus::Running])
+			.await?;
+
+	progress.finish_with_message(format!("Cancelled {} job(s)", cancelled_count));
+
+	if json {
+		print_json(&ClearQueueOutput { cancelled_count })?;
+	}
+
+	Ok(())
+}
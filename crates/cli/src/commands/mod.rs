@@ -1,33 +1,68 @@
 mod account;
+mod config;
+mod jobs;
 mod system;
 
 use std::time::Duration;
 
 use clap::Subcommand;
 use indicatif::{ProgressBar, ProgressStyle};
-use stump_core::config::StumpConfig;
+use serde::Serialize;
+use stump_core::{
+	config::StumpConfig,
+	db::{lock_database, DatabaseLock},
+};
 
-use crate::error::CliResult;
+use crate::{error::CliResult, CliError};
 
-use self::{account::Account, system::System};
+use self::{account::Account, config::Config, jobs::Jobs, system::System};
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
 	#[command(subcommand)]
 	Account(Account),
 	#[command(subcommand)]
+	Config(Config),
+	#[command(subcommand)]
+	Jobs(Jobs),
+	#[command(subcommand)]
 	System(System),
 }
 
-pub async fn handle_command(command: Commands, config: &StumpConfig) -> CliResult<()> {
+pub async fn handle_command(
+	command: Commands,
+	config: &StumpConfig,
+	json: bool,
+) -> CliResult<()> {
 	match command {
 		Commands::Account(account) => {
-			account::handle_account_command(account, config).await
+			account::handle_account_command(account, config, json).await
+		},
+		Commands::Config(command) => {
+			config::handle_config_command(command, config, json).await
+		},
+		Commands::Jobs(jobs) => jobs::handle_jobs_command(jobs, config, json).await,
+		Commands::System(system) => {
+			system::handle_system_command(system, config, json).await
 		},
-		Commands::System(system) => system::handle_system_command(system, config).await,
 	}
 }
 
+/// Takes the exclusive database lock, which the server holds for as long as it runs. Every
+/// command which writes to the database must hold the returned lock until it is done, so
+/// that it can't conflict with a running server (or another command).
+pub(crate) fn lock_database_for_writing(config: &StumpConfig) -> CliResult<DatabaseLock> {
+	Ok(lock_database(config)?)
+}
+
+/// Prints the given value to stdout as JSON, for use with the `--json` flag
+pub(crate) fn print_json<T: Serialize>(value: &T) -> CliResult<()> {
+	let output = serde_json::to_string_pretty(value)
+		.map_err(|e| CliError::Unknown(e.to_string()))?;
+	println!("{}", output);
+	Ok(())
+}
+
 pub(crate) fn default_progress_spinner() -> ProgressBar {
 	let progress = ProgressBar::new_spinner();
 	progress.enable_steady_tick(Duration::from_millis(120));
@@ -1,11 +1,12 @@
 use clap::Subcommand;
 use dialoguer::Confirm;
+use serde::Serialize;
 use stump_core::{
 	config::StumpConfig,
 	db::{create_client, DBPragma, JournalMode},
 };
 
-use super::default_progress_spinner;
+use super::{default_progress_spinner, lock_database_for_writing, print_json};
 use crate::{error::CliResult, CliError};
 
 /// Subcommands for interacting with the system commands
@@ -19,16 +20,28 @@ pub enum System {
 	},
 }
 
+#[derive(Serialize)]
+struct JournalModeOutput {
+	journal_mode: JournalMode,
+}
+
 pub async fn handle_system_command(
 	command: System,
 	config: &StumpConfig,
+	json: bool,
 ) -> CliResult<()> {
 	match command {
-		System::SetJournalMode { mode } => set_journal_mode(mode, config).await,
+		System::SetJournalMode { mode } => set_journal_mode(mode, config, json).await,
 	}
 }
 
-async fn set_journal_mode(mode: JournalMode, config: &StumpConfig) -> CliResult<()> {
+async fn set_journal_mode(
+	mode: JournalMode,
+	config: &StumpConfig,
+	json: bool,
+) -> CliResult<()> {
+	let _lock = lock_database_for_writing(config)?;
+
 	let confirmation = Confirm::new()
     .with_prompt("Changing the journal mode can lead to unexpected behavior. Are you sure you want to continue?")
     .interact()?;
@@ -48,6 +61,11 @@ async fn set_journal_mode(mode: JournalMode, config: &StumpConfig) -> CliResult<
 
 	if current_journal_mode == mode {
 		progress.finish_with_message("Journal mode already set to desired value");
+		if json {
+			print_json(&JournalModeOutput {
+				journal_mode: current_journal_mode,
+			})?;
+		}
 		return Ok(());
 	}
 
@@ -63,5 +81,11 @@ async fn set_journal_mode(mode: JournalMode, config: &StumpConfig) -> CliResult<
 
 	progress.finish_with_message("Journal mode successfully set");
 
+	if json {
+		print_json(&JournalModeOutput {
+			journal_mode: new_journal_mode,
+		})?;
+	}
+
 	Ok(())
 }
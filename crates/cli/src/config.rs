@@ -11,6 +11,10 @@ pub struct CliConfig {
 	/// The desired cost for password hashing. Defaults to 12.
 	#[clap(long)]
 	pub password_hash_cost: Option<u32>,
+	/// Allow migrations annotated as destructive to be applied on startup. Please
+	/// back up your database before using this!
+	#[clap(long)]
+	pub apply_destructive: bool,
 }
 
 impl CliConfig {
@@ -23,6 +27,9 @@ impl CliConfig {
 		if let Some(hash_cost) = self.password_hash_cost {
 			config.password_hash_cost = hash_cost;
 		}
+		if self.apply_destructive {
+			config.apply_destructive_migrations = true;
+		}
 
 		config
 	}
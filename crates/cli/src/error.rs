@@ -9,6 +9,8 @@ pub enum CliError {
 	OperationFailed(String),
 	#[error("{0}")]
 	QueryError(#[from] QueryError),
+	#[error("The database is in use, likely by a running Stump server. Please stop it before running this command")]
+	DatabaseInUse,
 	#[error("{0}")]
 	Unknown(String),
 }
@@ -17,6 +19,7 @@ impl From<CoreError> for CliError {
 	fn from(err: CoreError) -> Self {
 		match err {
 			CoreError::QueryError(err) => CliError::QueryError(err),
+			CoreError::DatabaseLocked(_) => CliError::DatabaseInUse,
 			_ => CliError::Unknown(format!("{:?}", err)),
 		}
 	}
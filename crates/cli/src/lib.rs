@@ -16,6 +16,10 @@ pub struct Cli {
 	#[clap(flatten)]
 	pub config: CliConfig,
 
+	/// Print the output of a subcommand as JSON
+	#[clap(long, global = true)]
+	pub json: bool,
+
 	/// The available subcommands. If no subcommand is provided, the server will be started
 	#[command(subcommand)]
 	pub command: Option<Commands>,
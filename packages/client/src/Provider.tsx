@@ -1,6 +1,6 @@
 import { jobQueryKeys } from '@stump/api'
 import { JobUpdate } from '@stump/types'
-import { ReactElement, useState } from 'react'
+import { ReactElement, useRef, useState } from 'react'
 
 import { queryClient, QueryClientProvider } from './client'
 import {
@@ -29,8 +29,24 @@ export function StumpClientContextProvider({ children, onRedirect }: Props) {
 // of tasks are completed per second. The backend is just too quick and there are too many state changes.
 export function JobContextProvider({ children }: { children: ReactElement }) {
 	const [jobs, setJobs] = useState<Record<string, JobUpdate>>({})
+	// The sequence of the last event seen for each job. Events can arrive out of order, so any
+	// event with a lower (or equal) sequence is stale and ignored, e.g. progress which arrives
+	// after the job completed.
+	const lastSequences = useRef<Record<string, number>>({})
+
+	function isStale(jobId: string, sequence?: BigInt) {
+		if (sequence == null) return false
+
+		const last = lastSequences.current[jobId]
+		if (last != null && Number(sequence) <= last) return true
+
+		lastSequences.current[jobId] = Number(sequence)
+		return false
+	}
 
 	function addJob(newJob: JobUpdate) {
+		if (isStale(newJob.job_id, newJob.sequence)) return
+
 		setJobs((jobs) => {
 			const target = jobs[newJob.job_id]
 
@@ -54,6 +70,8 @@ export function JobContextProvider({ children }: { children: ReactElement }) {
 	}
 
 	function updateJob(jobUpdate: JobUpdate) {
+		if (isStale(jobUpdate.job_id, jobUpdate.sequence)) return
+
 		setJobs((jobs) => {
 			const target = jobs[jobUpdate.job_id]
 
@@ -74,7 +92,9 @@ export function JobContextProvider({ children }: { children: ReactElement }) {
 		})
 	}
 
-	function removeJob(jobId: string) {
+	function removeJob(jobId: string, sequence?: BigInt) {
+		if (isStale(jobId, sequence)) return
+
 		setJobs((jobs) => {
 			const newJobs = { ...jobs }
 			delete newJobs[jobId]
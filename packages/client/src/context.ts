@@ -27,7 +27,7 @@ export interface IJobContext {
 
 	addJob(job: JobUpdate): void
 	updateJob(job: JobUpdate): void
-	removeJob(runnerId: string): void
+	removeJob(runnerId: string, sequence?: BigInt): void
 }
 export const JobContext = createContext<IJobContext | null>(null)
 export const useAppProps = () => {
@@ -32,14 +32,14 @@ export function useCoreEventHandler({
 				updateJob(data)
 				break
 			case 'JobComplete':
-				removeJob(data)
+				removeJob(data.job_id, data.sequence)
 				await new Promise((resolve) => setTimeout(resolve, 300))
 				await invalidateQueries({ keys: core_event_triggers[key].keys })
-				onJobComplete?.(data)
+				onJobComplete?.(data.job_id)
 				break
 			case 'JobFailed':
 				onJobFailed?.(data)
-				removeJob(data.job_id)
+				removeJob(data.job_id, data.sequence)
 				await invalidateQueries({ keys: core_event_triggers[key].keys })
 				break
 			default: